@@ -0,0 +1,6 @@
+BEGIN;
+
+ALTER TABLE workspaces
+    ADD COLUMN IF NOT EXISTS is_template BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMIT;
@@ -0,0 +1,61 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'task_status_category') THEN
+        CREATE TYPE task_status_category AS ENUM ('todo', 'doing', 'done');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS task_statuses
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    category task_status_category NOT NULL,
+    color VARCHAR,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS task_status_transitions
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    from_status_id UUID NOT NULL REFERENCES task_statuses(id) ON DELETE CASCADE,
+    to_status_id UUID NOT NULL REFERENCES task_statuses(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (from_status_id, to_status_id)
+);
+
+ALTER TABLE tasks
+    ADD COLUMN IF NOT EXISTS status_id UUID REFERENCES task_statuses(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_status_id ON tasks (status_id);
+CREATE INDEX IF NOT EXISTS idx_task_statuses_workspace_id ON task_statuses (workspace_id);
+CREATE INDEX IF NOT EXISTS idx_task_status_transitions_workspace_id ON task_status_transitions (workspace_id);
+
+-- Give every existing workspace the default columns.
+INSERT INTO task_statuses (id, workspace_id, name, category, color, position, created_at, updated_at)
+SELECT gen_random_uuid(), w.id, s.name, s.category::task_status_category, s.color, s.position, NOW(), NOW()
+FROM workspaces w
+CROSS JOIN (VALUES
+    ('Backlog', 'todo', '#94a3b8', 0),
+    ('In Progress', 'doing', '#3b82f6', 1),
+    ('Review', 'doing', '#f59e0b', 2),
+    ('Done', 'done', '#22c55e', 3)
+) AS s(name, category, color, position)
+WHERE NOT EXISTS (SELECT 1 FROM task_statuses ts WHERE ts.workspace_id = w.id);
+
+-- Map existing tasks onto the default columns from their completed flag.
+UPDATE tasks t
+SET status_id = ts.id
+FROM topics tp, task_statuses ts
+WHERE t.topic_id = tp.id
+  AND ts.workspace_id = tp.workspace_id
+  AND t.status_id IS NULL
+  AND ts.name = CASE WHEN t.completed THEN 'Done' ELSE 'Backlog' END;
+
+COMMIT;
@@ -0,0 +1,30 @@
+BEGIN;
+
+ALTER TABLE tasks
+    ADD COLUMN IF NOT EXISTS rank VARCHAR COLLATE "C" NOT NULL DEFAULT '';
+
+ALTER TABLE task_substeps
+    ADD COLUMN IF NOT EXISTS rank VARCHAR COLLATE "C" NOT NULL DEFAULT '';
+
+-- Seed fixed-width keys in the current timestamp order. Keys end in 'i' so
+-- there is always room to insert between neighbours.
+UPDATE tasks t
+SET rank = lpad(o.n::text, 10, '0') || 'i'
+FROM (
+    SELECT id, row_number() OVER (ORDER BY start_time NULLS LAST, created_at, id) AS n
+    FROM tasks
+) o
+WHERE t.id = o.id AND t.rank = '';
+
+UPDATE task_substeps s
+SET rank = lpad(o.n::text, 10, '0') || 'i'
+FROM (
+    SELECT id, row_number() OVER (PARTITION BY task_id ORDER BY created_at, id) AS n
+    FROM task_substeps
+) o
+WHERE s.id = o.id AND s.rank = '';
+
+CREATE INDEX IF NOT EXISTS idx_tasks_rank ON tasks (rank);
+CREATE INDEX IF NOT EXISTS idx_task_substeps_task_id_rank ON task_substeps (task_id, rank);
+
+COMMIT;
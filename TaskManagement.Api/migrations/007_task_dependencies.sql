@@ -0,0 +1,25 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'task_dependency_type') THEN
+        CREATE TYPE task_dependency_type AS ENUM
+            ('finish_to_start', 'start_to_start', 'finish_to_finish', 'start_to_finish');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS task_dependencies
+(
+    id UUID PRIMARY KEY,
+    blocking_task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    blocked_task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    dependency_type task_dependency_type NOT NULL DEFAULT 'finish_to_start',
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (blocking_task_id, blocked_task_id),
+    CHECK (blocking_task_id <> blocked_task_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_dependencies_blocked_task_id ON task_dependencies (blocked_task_id);
+
+COMMIT;
@@ -0,0 +1,8 @@
+BEGIN;
+
+ALTER TABLE tasks
+    ADD COLUMN IF NOT EXISTS parent_task_id UUID REFERENCES tasks(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_parent_task_id ON tasks (parent_task_id);
+
+COMMIT;
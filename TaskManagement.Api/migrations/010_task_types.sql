@@ -0,0 +1,60 @@
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS task_types
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    icon VARCHAR,
+    color VARCHAR NOT NULL,
+    default_duration_minutes INTEGER,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_types_workspace_id ON task_types (workspace_id);
+
+ALTER TABLE tasks
+    ADD COLUMN IF NOT EXISTS task_type_id UUID REFERENCES task_types(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_task_type_id ON tasks (task_type_id);
+
+-- Every workspace gets the five former enum values as its starting types.
+INSERT INTO task_types
+    (id, workspace_id, name, icon, color, default_duration_minutes, position, created_at, updated_at)
+SELECT gen_random_uuid(), w.id, d.name, d.icon, d.color, d.duration, d.position, NOW(), NOW()
+FROM workspaces w
+CROSS JOIN (
+    VALUES
+        ('Work', 'briefcase', '#3b82f6', 60, 0),
+        ('Personal', 'user', '#22c55e', 30, 1),
+        ('Meeting', 'users', '#a855f7', 30, 2),
+        ('Deadline', 'flag', '#ef4444', NULL, 3),
+        ('Event', 'calendar', '#f59e0b', 60, 4)
+) AS d(name, icon, color, duration, position)
+ON CONFLICT (workspace_id, name) DO NOTHING;
+
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'tasks' AND column_name = 'task_type'
+    ) THEN
+        UPDATE tasks t
+        SET task_type_id = tt.id
+        FROM topics tp, task_types tt
+        WHERE tp.id = t.topic_id
+          AND tt.workspace_id = tp.workspace_id
+          AND lower(tt.name) = t.task_type::text
+          AND t.task_type_id IS NULL;
+
+        ALTER TABLE tasks DROP COLUMN task_type;
+    END IF;
+END
+$$;
+
+DROP TYPE IF EXISTS task_type;
+
+COMMIT;
@@ -0,0 +1,41 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'custom_field_type') THEN
+        CREATE TYPE custom_field_type AS ENUM ('text', 'number', 'date', 'select', 'checkbox');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS custom_fields
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    field_type custom_field_type NOT NULL,
+    options VARCHAR[],
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS task_custom_field_values
+(
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    field_id UUID NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+    text_value TEXT,
+    number_value DOUBLE PRECISION,
+    date_value DATE,
+    bool_value BOOLEAN,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (task_id, field_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_custom_fields_workspace_id ON custom_fields (workspace_id);
+CREATE INDEX IF NOT EXISTS idx_task_custom_field_values_field_id
+    ON task_custom_field_values (field_id);
+
+COMMIT;
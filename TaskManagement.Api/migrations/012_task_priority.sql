@@ -0,0 +1,31 @@
+BEGIN;
+
+-- Values are declared from lowest to highest so that ORDER BY and range
+-- comparisons on the column follow the priority order.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'task_priority') THEN
+        CREATE TYPE task_priority AS ENUM ('none', 'low', 'medium', 'high', 'critical');
+    END IF;
+END
+$$;
+
+ALTER TABLE tasks
+    ADD COLUMN IF NOT EXISTS priority task_priority NOT NULL DEFAULT 'none';
+
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'tasks' AND column_name = 'urgent'
+    ) THEN
+        UPDATE tasks SET priority = 'high' WHERE urgent AND priority = 'none';
+
+        ALTER TABLE tasks DROP COLUMN urgent;
+    END IF;
+END
+$$;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks (priority);
+
+COMMIT;
@@ -0,0 +1,18 @@
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS attachments
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    uploaded_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename VARCHAR NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    mime_type VARCHAR NOT NULL,
+    checksum VARCHAR NOT NULL, -- hex SHA-256
+    storage_key VARCHAR NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_task_id ON attachments (task_id);
+
+COMMIT;
@@ -0,0 +1,27 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'reminder_anchor') THEN
+        CREATE TYPE reminder_anchor AS ENUM ('start_time', 'due_date');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS task_reminders
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    anchor reminder_anchor NOT NULL,
+    offset_minutes INTEGER NOT NULL CHECK (offset_minutes >= 0),
+    fired_for TIMESTAMPTZ,
+    fired_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (task_id, user_id, anchor, offset_minutes)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_reminders_task_id ON task_reminders (task_id);
+
+COMMIT;
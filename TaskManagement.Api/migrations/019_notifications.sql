@@ -0,0 +1,35 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'notification_kind') THEN
+        CREATE TYPE notification_kind AS ENUM
+            ('reminder', 'assignment', 'mention', 'workspace_transfer');
+    END IF;
+END
+$$;
+
+-- Task and workspace references are kept loose: a notification outlives
+-- what it points to.
+CREATE TABLE IF NOT EXISTS notifications
+(
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind notification_kind NOT NULL,
+    title VARCHAR NOT NULL,
+    body TEXT,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    workspace_id UUID,
+    task_id UUID,
+    read_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    delivery_attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_id_created_at
+    ON notifications (user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_notifications_undelivered
+    ON notifications (created_at) WHERE delivered_at IS NULL;
+
+COMMIT;
@@ -0,0 +1,43 @@
+BEGIN;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'activity_entity') THEN
+        CREATE TYPE activity_entity AS ENUM
+            ('workspace', 'topic', 'task', 'substep', 'comment', 'attachment', 'time_entry',
+             'reminder', 'dependency', 'label', 'task_status', 'task_type', 'custom_field',
+             'share_link', 'webhook', 'transfer');
+    END IF;
+END
+$$;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'activity_action') THEN
+        CREATE TYPE activity_action AS ENUM
+            ('created', 'updated', 'deleted', 'restored', 'archived', 'unarchived', 'moved');
+    END IF;
+END
+$$;
+
+-- Append-only. Entity and task references are kept loose so the history
+-- outlives what it describes; it goes with its workspace.
+CREATE TABLE IF NOT EXISTS activity_log
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    entity activity_entity NOT NULL,
+    entity_id UUID NOT NULL,
+    task_id UUID,
+    action activity_action NOT NULL,
+    changes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_log_workspace_id_created_at
+    ON activity_log (workspace_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_activity_log_task_id_created_at
+    ON activity_log (task_id, created_at) WHERE task_id IS NOT NULL;
+
+COMMIT;
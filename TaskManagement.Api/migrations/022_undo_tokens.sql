@@ -0,0 +1,19 @@
+BEGIN;
+
+-- A destructive operation hands out one of these so it can be reversed
+-- within the undo window; `steps` is the serialized list of undo steps.
+CREATE TABLE IF NOT EXISTS undo_tokens
+(
+    id UUID PRIMARY KEY,
+    token VARCHAR NOT NULL UNIQUE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    steps JSONB NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_undo_tokens_expires_at ON undo_tokens (expires_at);
+
+COMMIT;
@@ -1,6 +1,38 @@
--- Create task_type enum
-CREATE TYPE task_type AS ENUM
-('work', 'personal', 'meeting', 'deadline', 'event');
+-- Create task_dependency_type enum
+CREATE TYPE task_dependency_type AS ENUM
+('finish_to_start', 'start_to_start', 'finish_to_finish', 'start_to_finish');
+
+-- Create task_status_category enum
+CREATE TYPE task_status_category AS ENUM
+('todo', 'doing', 'done');
+
+-- Create task_priority enum (lowest to highest)
+CREATE TYPE task_priority AS ENUM
+('none', 'low', 'medium', 'high', 'critical');
+
+-- Create custom_field_type enum
+CREATE TYPE custom_field_type AS ENUM
+('text', 'number', 'date', 'select', 'checkbox');
+
+-- Create notification_kind enum
+CREATE TYPE notification_kind AS ENUM
+('reminder', 'assignment', 'mention', 'workspace_transfer');
+
+-- Create reminder_anchor enum
+CREATE TYPE reminder_anchor AS ENUM
+('start_time', 'due_date');
+
+-- Create webhook_delivery_status enum
+CREATE TYPE webhook_delivery_status AS ENUM
+('pending', 'succeeded', 'dead');
+
+-- Create activity_entity enum
+CREATE TYPE activity_entity AS ENUM
+('workspace', 'topic', 'task', 'substep', 'comment', 'attachment', 'time_entry', 'reminder', 'dependency', 'label', 'task_status', 'task_type', 'custom_field', 'share_link', 'webhook', 'transfer');
+
+-- Create activity_action enum
+CREATE TYPE activity_action AS ENUM
+('created', 'updated', 'deleted', 'restored', 'archived', 'unarchived', 'moved');
 
 -- Create users table
 CREATE TABLE
@@ -24,6 +56,9 @@ IF NOT EXISTS workspaces
     owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
     name VARCHAR NOT NULL,
     description TEXT,
+    is_template BOOLEAN NOT NULL DEFAULT FALSE,
+    archived_at TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ,
     created_at TIMESTAMPTZ NOT NULL,
     updated_at TIMESTAMPTZ NOT NULL
 );
@@ -47,10 +82,54 @@ DELETE CASCADE,
 NOT NULL,
     description TEXT,
     color VARCHAR NOT NULL,
+    archived_at TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ,
     created_at TIMESTAMPTZ NOT NULL,
     updated_at TIMESTAMPTZ NOT NULL
 );
 
+-- Create task statuses table (per-workspace Kanban columns)
+CREATE TABLE
+IF NOT EXISTS task_statuses
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    category task_status_category NOT NULL,
+    color VARCHAR,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+-- Create allowed status transitions table
+CREATE TABLE
+IF NOT EXISTS task_status_transitions
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    from_status_id UUID NOT NULL REFERENCES task_statuses(id) ON DELETE CASCADE,
+    to_status_id UUID NOT NULL REFERENCES task_statuses(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (from_status_id, to_status_id)
+);
+
+-- Create task types table (per-workspace, user-defined)
+CREATE TABLE
+IF NOT EXISTS task_types
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    icon VARCHAR,
+    color VARCHAR NOT NULL,
+    default_duration_minutes INTEGER,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
 -- Create tasks table
 CREATE TABLE
 IF NOT EXISTS tasks
@@ -59,24 +138,193 @@ IF NOT EXISTS tasks
     topic_id UUID NOT NULL REFERENCES topics
 (id) ON
 DELETE CASCADE,
+    parent_task_id UUID REFERENCES tasks(id) ON DELETE CASCADE,
     title VARCHAR NOT NULL,
     description TEXT,
     start_time TIMESTAMPTZ,
     end_time TIMESTAMPTZ,
-    task_type task_type
-NOT NULL,
+    task_type_id UUID REFERENCES task_types(id) ON DELETE SET NULL,
+    status_id UUID REFERENCES task_statuses(id) ON DELETE SET NULL,
+    rank VARCHAR COLLATE "C" NOT NULL DEFAULT '',
     color VARCHAR NOT NULL,
-    urgent BOOLEAN NOT NULL DEFAULT FALSE,
+    priority task_priority NOT NULL DEFAULT 'none',
     completed BOOLEAN NOT NULL DEFAULT FALSE,
     due_date TIMESTAMPTZ,
+    estimate_minutes INTEGER CHECK (estimate_minutes >= 0),
+    remaining_minutes INTEGER CHECK (remaining_minutes >= 0),
     recurrence_type VARCHAR, -- 'daily', 'weekly', 'monthly', or NULL
     recurrence_interval INTEGER,
     recurrence_days INTEGER[], -- e.g., [1,3,5] for Mon,Wed,Fri
     recurrence_end_date TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+-- Create task dependencies table
+CREATE TABLE
+IF NOT EXISTS task_dependencies
+(
+    id UUID PRIMARY KEY,
+    blocking_task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    blocked_task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    dependency_type task_dependency_type NOT NULL DEFAULT 'finish_to_start',
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (blocking_task_id, blocked_task_id),
+    CHECK (blocking_task_id <> blocked_task_id)
+);
+
+-- Create labels table
+CREATE TABLE
+IF NOT EXISTS labels
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    color VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
+-- Create task labels join table
+CREATE TABLE
+IF NOT EXISTS task_labels
+(
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    label_id UUID NOT NULL REFERENCES labels(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (task_id, label_id)
+);
+
+-- Create task assignees join table
+CREATE TABLE
+IF NOT EXISTS task_assignees
+(
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (task_id, user_id)
+);
+
+-- Create task comments table
+CREATE TABLE
+IF NOT EXISTS task_comments
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL, -- Markdown
+    edited_at TIMESTAMPTZ,
     created_at TIMESTAMPTZ NOT NULL,
     updated_at TIMESTAMPTZ NOT NULL
 );
 
+-- Create task comment mentions table
+CREATE TABLE
+IF NOT EXISTS task_comment_mentions
+(
+    comment_id UUID NOT NULL REFERENCES task_comments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (comment_id, user_id)
+);
+
+-- Create attachments table (contents live in blob storage)
+CREATE TABLE
+IF NOT EXISTS attachments
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    uploaded_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename VARCHAR NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    mime_type VARCHAR NOT NULL,
+    checksum VARCHAR NOT NULL, -- hex SHA-256
+    storage_key VARCHAR NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+-- Create notifications table (task/workspace references are kept loose so
+-- a notification outlives what it points to)
+CREATE TABLE
+IF NOT EXISTS notifications
+(
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind notification_kind NOT NULL,
+    title VARCHAR NOT NULL,
+    body TEXT,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    workspace_id UUID,
+    task_id UUID,
+    read_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    delivery_attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+-- Create task reminders table (fired_for is the anchor time last fired for)
+CREATE TABLE
+IF NOT EXISTS task_reminders
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    anchor reminder_anchor NOT NULL,
+    offset_minutes INTEGER NOT NULL CHECK (offset_minutes >= 0),
+    fired_for TIMESTAMPTZ,
+    fired_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (task_id, user_id, anchor, offset_minutes)
+);
+
+-- Create time entries table (ended_at is NULL while a timer runs)
+CREATE TABLE
+IF NOT EXISTS time_entries
+(
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    CHECK (ended_at IS NULL OR ended_at >= started_at)
+);
+
+-- Create custom field definitions table
+CREATE TABLE
+IF NOT EXISTS custom_fields
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    field_type custom_field_type NOT NULL,
+    options VARCHAR[], -- allowed values of a select field
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
+-- Create task custom field values table (one typed column per field type)
+CREATE TABLE
+IF NOT EXISTS task_custom_field_values
+(
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    field_id UUID NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+    text_value TEXT,
+    number_value DOUBLE PRECISION,
+    date_value DATE,
+    bool_value BOOLEAN,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (task_id, field_id)
+);
+
 -- Create task_exceptions table
 CREATE TABLE
 IF NOT EXISTS task_exceptions
@@ -104,6 +352,7 @@ DELETE CASCADE,
     description TEXT
 NOT NULL,
     completed BOOLEAN NOT NULL DEFAULT FALSE,
+    rank VARCHAR COLLATE "C" NOT NULL DEFAULT '',
     created_at TIMESTAMPTZ NOT NULL,
     updated_at TIMESTAMPTZ NOT NULL
 );
@@ -122,11 +371,92 @@ CREATE INDEX
 IF NOT EXISTS idx_tasks_completed ON tasks
 (completed);
 CREATE INDEX
+IF NOT EXISTS idx_tasks_status_id ON tasks
+(status_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_statuses_workspace_id ON task_statuses
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_status_transitions_workspace_id ON task_status_transitions
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_tasks_deleted_at ON tasks
+(deleted_at);
+CREATE INDEX
+IF NOT EXISTS idx_topics_deleted_at ON topics
+(deleted_at);
+CREATE INDEX
+IF NOT EXISTS idx_workspaces_deleted_at ON workspaces
+(deleted_at);
+CREATE INDEX
 IF NOT EXISTS idx_task_substeps_task_id ON task_substeps
 (task_id);
 CREATE INDEX
 IF NOT EXISTS idx_task_substeps_completed ON task_substeps
 (completed);
+CREATE INDEX
+IF NOT EXISTS idx_task_dependencies_blocked_task_id ON task_dependencies
+(blocked_task_id);
+CREATE INDEX
+IF NOT EXISTS idx_tasks_parent_task_id ON tasks
+(parent_task_id);
+CREATE INDEX
+IF NOT EXISTS idx_tasks_rank ON tasks
+(rank);
+CREATE INDEX
+IF NOT EXISTS idx_tasks_priority ON tasks
+(priority);
+CREATE INDEX
+IF NOT EXISTS idx_task_substeps_task_id_rank ON task_substeps
+(task_id, rank);
+CREATE INDEX
+IF NOT EXISTS idx_task_types_workspace_id ON task_types
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_tasks_task_type_id ON tasks
+(task_type_id);
+CREATE INDEX
+IF NOT EXISTS idx_custom_fields_workspace_id ON custom_fields
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_custom_field_values_field_id ON task_custom_field_values
+(field_id);
+CREATE INDEX
+IF NOT EXISTS idx_labels_workspace_id ON labels
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_labels_label_id ON task_labels
+(label_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_assignees_user_id ON task_assignees
+(user_id);
+CREATE INDEX
+IF NOT EXISTS idx_task_comments_task_id_created_at ON task_comments
+(task_id, created_at);
+CREATE INDEX
+IF NOT EXISTS idx_task_comment_mentions_user_id ON task_comment_mentions
+(user_id);
+CREATE INDEX
+IF NOT EXISTS idx_attachments_task_id ON attachments
+(task_id);
+CREATE INDEX
+IF NOT EXISTS idx_time_entries_task_id ON time_entries
+(task_id);
+CREATE INDEX
+IF NOT EXISTS idx_time_entries_user_id_started_at ON time_entries
+(user_id, started_at);
+CREATE UNIQUE INDEX
+IF NOT EXISTS idx_time_entries_running_user_id ON time_entries
+(user_id) WHERE ended_at IS NULL;
+CREATE INDEX
+IF NOT EXISTS idx_task_reminders_task_id ON task_reminders
+(task_id);
+CREATE INDEX
+IF NOT EXISTS idx_notifications_user_id_created_at ON notifications
+(user_id, created_at);
+CREATE INDEX
+IF NOT EXISTS idx_notifications_undelivered ON notifications
+(created_at) WHERE delivered_at IS NULL;
 
 -- Create workspace share links table
 CREATE TABLE
@@ -147,3 +477,106 @@ IF NOT EXISTS idx_workspace_share_links_workspace_id ON workspace_share_links
 CREATE INDEX
 IF NOT EXISTS idx_workspace_share_links_token ON workspace_share_links
 (token);
+
+-- Create workspace ownership transfers table
+CREATE TABLE
+IF NOT EXISTS workspace_transfers
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    from_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    to_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    accepted_at TIMESTAMPTZ,
+    declined_at TIMESTAMPTZ,
+    cancelled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX
+IF NOT EXISTS idx_workspace_transfers_workspace_id ON workspace_transfers
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_workspace_transfers_to_user_id ON workspace_transfers
+(to_user_id);
+
+-- Create webhook subscriptions table (events lists the subscribed event names)
+CREATE TABLE
+IF NOT EXISTS webhooks
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    url VARCHAR NOT NULL,
+    secret VARCHAR NOT NULL,
+    events VARCHAR[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+-- Create webhook deliveries table (the outbox; payload is stored as signed)
+CREATE TABLE
+IF NOT EXISTS webhook_deliveries
+(
+    id UUID PRIMARY KEY,
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event VARCHAR NOT NULL,
+    payload TEXT NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    last_attempt_at TIMESTAMPTZ,
+    response_status INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX
+IF NOT EXISTS idx_webhooks_workspace_id ON webhooks
+(workspace_id);
+CREATE INDEX
+IF NOT EXISTS idx_webhook_deliveries_webhook_id_created_at ON webhook_deliveries
+(webhook_id, created_at);
+CREATE INDEX
+IF NOT EXISTS idx_webhook_deliveries_pending ON webhook_deliveries
+(next_attempt_at) WHERE status = 'pending';
+
+-- Create activity log table (append-only, field-level before/after diffs)
+CREATE TABLE
+IF NOT EXISTS activity_log
+(
+    id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    entity activity_entity NOT NULL,
+    entity_id UUID NOT NULL,
+    task_id UUID,
+    action activity_action NOT NULL,
+    changes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX
+IF NOT EXISTS idx_activity_log_workspace_id_created_at ON activity_log
+(workspace_id, created_at);
+CREATE INDEX
+IF NOT EXISTS idx_activity_log_task_id_created_at ON activity_log
+(task_id, created_at) WHERE task_id IS NOT NULL;
+
+-- Create undo tokens table (reverses a destructive operation within the undo window)
+CREATE TABLE
+IF NOT EXISTS undo_tokens
+(
+    id UUID PRIMARY KEY,
+    token VARCHAR NOT NULL UNIQUE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    steps JSONB NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX
+IF NOT EXISTS idx_undo_tokens_expires_at ON undo_tokens
+(expires_at);
@@ -1,7 +1,7 @@
 use crate::errors::{AppError, AppResult};
 use mobc::{Connection, Pool};
 use mobc_redis::RedisConnectionManager;
-use mobc_redis::redis::{Client, AsyncCommands};
+use mobc_redis::redis::{aio::PubSub, Client, AsyncCommands};
 use serde::{de::DeserializeOwned, Serialize};
 use std::ops::DerefMut;
 use std::time::Duration;
@@ -9,6 +9,7 @@ use std::time::Duration;
 pub type RedisPool = Pool<RedisConnectionManager>;
 pub type RedisConnection = Connection<RedisConnectionManager>;
 
+#[derive(Clone)]
 pub struct Cache {
     pool: RedisPool,
     default_ttl: u64,
@@ -106,6 +107,37 @@ impl Cache {
         Ok(result)
     }
 
+    pub async fn publish<T>(&self, channel: &str, message: &T) -> AppResult<()>
+    where
+        T: Serialize,
+    {
+        let mut conn = self.get_connection().await?;
+
+        let json_str = serde_json::to_string(message)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize message: {}", e)))?;
+
+        conn.deref_mut()
+            .publish::<_, _, ()>(channel, json_str)
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis publish error: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Subscribes to a pub/sub channel. A subscribed connection cannot run
+    /// other commands, so it is taken out of the pool for good.
+    pub async fn subscribe(&self, channel: &str) -> AppResult<PubSub> {
+        let conn = self.get_connection().await?;
+
+        let mut pubsub = Connection::into_inner(conn).into_pubsub();
+        pubsub
+            .subscribe(channel)
+            .await
+            .map_err(|e| AppError::Internal(format!("Redis subscribe error: {}", e)))?;
+
+        Ok(pubsub)
+    }
+
     pub fn generate_cache_key(&self, prefix: &str, user_id: &uuid::Uuid, suffix: Option<&str>) -> String {
         match suffix {
             Some(s) => format!("{}:{}:{}", prefix, user_id, s),
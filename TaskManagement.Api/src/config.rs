@@ -9,6 +9,36 @@ pub struct Config {
     pub bcrypt_cost: u32,
     pub redis_url: String,
     pub cache_ttl_seconds: u64,
+    pub trash_retention_days: i64,
+    pub trash_purge_interval_seconds: u64,
+    pub max_topics_per_workspace: u64,
+    pub max_tasks_per_workspace: u64,
+    pub max_substeps_per_task: u64,
+    pub max_share_links_per_workspace: u64,
+    pub max_topics_per_user: u64,
+    pub max_tasks_per_user: u64,
+    pub max_share_links_per_user: u64,
+    pub attachment_storage: String,
+    pub attachment_local_dir: String,
+    pub attachment_max_bytes: u64,
+    pub attachment_allowed_mime_types: Vec<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    pub reminder_poll_interval_seconds: u64,
+    pub reminder_batch_size: u64,
+    pub notification_channel: String,
+    pub notification_dispatch_interval_seconds: u64,
+    pub notification_batch_size: u64,
+    pub webhook_poll_interval_seconds: u64,
+    pub webhook_batch_size: u64,
+    pub webhook_timeout_seconds: u64,
+    pub webhook_max_attempts: i32,
+    pub webhook_allow_private_targets: bool,
+    pub undo_window_seconds: i64,
+    pub workspace_import_max_bytes: u64,
 }
 
 impl Config {
@@ -39,6 +69,155 @@ impl Config {
             .parse::<u64>()
             .unwrap_or(30);
 
+        let trash_retention_days = env::var("TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i64>()
+            .unwrap_or(30);
+
+        let trash_purge_interval_seconds = env::var("TRASH_PURGE_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse::<u64>()
+            .unwrap_or(3600);
+
+        // Quota limits; 0 disables a limit.
+        let max_topics_per_workspace = env::var("MAX_TOPICS_PER_WORKSPACE")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse::<u64>()
+            .unwrap_or(500);
+
+        let max_tasks_per_workspace = env::var("MAX_TASKS_PER_WORKSPACE")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<u64>()
+            .unwrap_or(10000);
+
+        let max_substeps_per_task = env::var("MAX_SUBSTEPS_PER_TASK")
+            .unwrap_or_else(|_| "200".to_string())
+            .parse::<u64>()
+            .unwrap_or(200);
+
+        let max_share_links_per_workspace = env::var("MAX_SHARE_LINKS_PER_WORKSPACE")
+            .unwrap_or_else(|_| "50".to_string())
+            .parse::<u64>()
+            .unwrap_or(50);
+
+        let max_topics_per_user = env::var("MAX_TOPICS_PER_USER")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse::<u64>()
+            .unwrap_or(5000);
+
+        let max_tasks_per_user = env::var("MAX_TASKS_PER_USER")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse::<u64>()
+            .unwrap_or(100000);
+
+        let max_share_links_per_user = env::var("MAX_SHARE_LINKS_PER_USER")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse::<u64>()
+            .unwrap_or(500);
+
+        // Attachment storage: "local" (files below ATTACHMENT_LOCAL_DIR) or
+        // "s3" (any S3-compatible bucket).
+        let attachment_storage = env::var("ATTACHMENT_STORAGE")
+            .unwrap_or_else(|_| "local".to_string())
+            .to_lowercase();
+
+        let attachment_local_dir = env::var("ATTACHMENT_LOCAL_DIR")
+            .unwrap_or_else(|_| "./attachments".to_string());
+
+        let attachment_max_bytes = env::var("ATTACHMENT_MAX_BYTES")
+            .unwrap_or_else(|_| "26214400".to_string())
+            .parse::<u64>()
+            .unwrap_or(26214400);
+
+        // Comma-separated; entries may end in "/*" to allow a whole type.
+        let attachment_allowed_mime_types = env::var("ATTACHMENT_ALLOWED_MIME_TYPES")
+            .unwrap_or_else(|_| {
+                "image/*,application/pdf,text/plain,text/markdown,text/csv,application/zip"
+                    .to_string()
+            })
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let s3_bucket = env::var("S3_BUCKET").ok();
+
+        let s3_region = env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+
+        let s3_endpoint = env::var("S3_ENDPOINT").ok();
+
+        let s3_access_key_id = env::var("S3_ACCESS_KEY_ID").ok();
+
+        let s3_secret_access_key = env::var("S3_SECRET_ACCESS_KEY").ok();
+
+        let reminder_poll_interval_seconds = env::var("REMINDER_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u64>()
+            .unwrap_or(30);
+
+        let reminder_batch_size = env::var("REMINDER_BATCH_SIZE")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<u64>()
+            .unwrap_or(100);
+
+        // Where notifications such as reminders are delivered: "log" prints
+        // them to stdout.
+        let notification_channel = env::var("NOTIFICATION_CHANNEL")
+            .unwrap_or_else(|_| "log".to_string())
+            .to_lowercase();
+
+        let notification_dispatch_interval_seconds =
+            env::var("NOTIFICATION_DISPATCH_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse::<u64>()
+                .unwrap_or(5);
+
+        let notification_batch_size = env::var("NOTIFICATION_BATCH_SIZE")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<u64>()
+            .unwrap_or(100);
+
+        let webhook_poll_interval_seconds = env::var("WEBHOOK_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u64>()
+            .unwrap_or(5);
+
+        let webhook_batch_size = env::var("WEBHOOK_BATCH_SIZE")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse::<u64>()
+            .unwrap_or(20);
+
+        let webhook_timeout_seconds = env::var("WEBHOOK_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<u64>()
+            .unwrap_or(10);
+
+        // Attempts before a delivery is marked dead; with the doubling
+        // backoff, 8 attempts span about an hour
+        let webhook_max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse::<i32>()
+            .unwrap_or(8);
+
+        // Lets webhooks target loopback, private and internal hosts, for a
+        // receiver on the local machine or network during development
+        let webhook_allow_private_targets = env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+            .map(|value| value == "true" || value == "1")
+            .unwrap_or(false);
+
+        // How long the undo token of a deletion stays redeemable
+        let undo_window_seconds = env::var("UNDO_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<i64>()
+            .unwrap_or(60);
+
+        // Workspace exports are imported as one JSON document, well past
+        // Rocket's default 1 MiB JSON limit for larger workspaces
+        let workspace_import_max_bytes = env::var("WORKSPACE_IMPORT_MAX_BYTES")
+            .unwrap_or_else(|_| "67108864".to_string())
+            .parse::<u64>()
+            .unwrap_or(67108864);
+
         Ok(Config {
             database_url,
             jwt_secret,
@@ -46,6 +225,36 @@ impl Config {
             bcrypt_cost,
             redis_url,
             cache_ttl_seconds,
+            trash_retention_days,
+            trash_purge_interval_seconds,
+            max_topics_per_workspace,
+            max_tasks_per_workspace,
+            max_substeps_per_task,
+            max_share_links_per_workspace,
+            max_topics_per_user,
+            max_tasks_per_user,
+            max_share_links_per_user,
+            attachment_storage,
+            attachment_local_dir,
+            attachment_max_bytes,
+            attachment_allowed_mime_types,
+            s3_bucket,
+            s3_region,
+            s3_endpoint,
+            s3_access_key_id,
+            s3_secret_access_key,
+            reminder_poll_interval_seconds,
+            reminder_batch_size,
+            notification_channel,
+            notification_dispatch_interval_seconds,
+            notification_batch_size,
+            webhook_poll_interval_seconds,
+            webhook_batch_size,
+            webhook_timeout_seconds,
+            webhook_max_attempts,
+            webhook_allow_private_targets,
+            undo_window_seconds,
+            workspace_import_max_bytes,
         })
     }
 }
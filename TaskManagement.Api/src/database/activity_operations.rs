@@ -0,0 +1,286 @@
+use crate::database::find_owned_workspace;
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{self, ActivityAction, ActivityEntity, Entity as ActivityLog},
+    task::{self, Entity as Task},
+    topic, workspace,
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Bookkeeping fields that never show up in a diff.
+const IGNORED_FIELDS: &[&str] = &["id", "created_at", "updated_at", "storage_key"];
+
+/// Fields whose values must not be copied into the log; a change is still
+/// recorded, with the values masked.
+const REDACTED_FIELDS: &[&str] = &["secret", "token", "password_hash"];
+
+/// An entry to append with `record_activity`.
+#[derive(Debug, Clone)]
+pub struct NewActivity {
+    pub workspace_id: Uuid,
+    pub actor_id: Uuid,
+    pub entity: ActivityEntity,
+    pub entity_id: Uuid,
+    pub task_id: Option<Uuid>,
+    pub action: ActivityAction,
+    pub changes: Value,
+}
+
+impl NewActivity {
+    pub fn new(
+        workspace_id: Uuid,
+        actor_id: Uuid,
+        entity: ActivityEntity,
+        entity_id: Uuid,
+        action: ActivityAction,
+    ) -> Self {
+        Self {
+            workspace_id,
+            actor_id,
+            entity,
+            entity_id,
+            task_id: (entity == ActivityEntity::Task).then_some(entity_id),
+            action,
+            changes: Value::Object(Map::new()),
+        }
+    }
+
+    /// Files the entry under a task's history.
+    pub fn on_task(mut self, task_id: Uuid) -> Self {
+        self.task_id = Some(task_id);
+        self
+    }
+
+    pub fn with_changes(mut self, changes: Value) -> Self {
+        self.changes = changes;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub entity: ActivityEntity,
+    pub entity_id: Uuid,
+    pub task_id: Option<Uuid>,
+    pub action: ActivityAction,
+    pub changes: Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<activity_log::Model> for ActivityResponse {
+    fn from(entry: activity_log::Model) -> Self {
+        Self {
+            id: entry.id,
+            workspace_id: entry.workspace_id,
+            actor_id: entry.actor_id,
+            entity: entry.entity,
+            entity_id: entry.entity_id,
+            task_id: entry.task_id,
+            action: entry.action,
+            changes: entry.changes,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityListResponse {
+    pub activities: Vec<ActivityResponse>,
+    /// Matching entries across all pages.
+    pub total: u64,
+}
+
+/// Narrows the workspace activity feed; every filter is optional.
+#[derive(Debug, Default)]
+pub struct ActivityFilter {
+    pub entity: Option<ActivityEntity>,
+    pub action: Option<ActivityAction>,
+    pub actor_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Field-level diff of two serialized states, as
+/// `{ "<field>": { "before": .., "after": .. } }` for every field that
+/// differs. Pass `None` as `before` for a creation and as `after` for a
+/// deletion to capture the full state.
+pub fn field_changes<T: Serialize>(before: Option<&T>, after: Option<&T>) -> Value {
+    let before = as_object(before);
+    let after = as_object(after);
+
+    let mut changes = Map::new();
+    for field in before.keys().chain(after.keys()) {
+        if IGNORED_FIELDS.contains(&field.as_str()) || changes.contains_key(field) {
+            continue;
+        }
+
+        let old = before.get(field).unwrap_or(&Value::Null);
+        let new = after.get(field).unwrap_or(&Value::Null);
+        if old == new {
+            continue;
+        }
+
+        let (old, new) = if REDACTED_FIELDS.contains(&field.as_str()) {
+            (redact(old), redact(new))
+        } else {
+            (old.clone(), new.clone())
+        };
+        changes.insert(
+            field.clone(),
+            serde_json::json!({ "before": old, "after": new }),
+        );
+    }
+
+    Value::Object(changes)
+}
+
+/// A single-field diff, for changes that are not a column of the entity
+/// such as a task's labels or assignees.
+pub fn field_change<B: Serialize, A: Serialize>(field: &str, before: B, after: A) -> Value {
+    serde_json::json!({ field: { "before": before, "after": after } })
+}
+
+fn as_object<T: Serialize>(value: Option<&T>) -> Map<String, Value> {
+    match value.map(serde_json::to_value) {
+        Some(Ok(Value::Object(map))) => map,
+        _ => Map::new(),
+    }
+}
+
+fn redact(value: &Value) -> Value {
+    if value.is_null() {
+        Value::Null
+    } else {
+        Value::String("[redacted]".to_string())
+    }
+}
+
+/// Appends an entry to the workspace's activity log. Mutations call this
+/// inside their transaction if they have one, so the log only records
+/// changes that were kept. Updates that changed nothing are not recorded.
+pub async fn record_activity<C: ConnectionTrait>(db: &C, activity: NewActivity) -> AppResult<()> {
+    let unchanged = activity
+        .changes
+        .as_object()
+        .is_some_and(|changes| changes.is_empty());
+    if activity.action == ActivityAction::Updated && unchanged {
+        return Ok(());
+    }
+
+    activity_log::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        workspace_id: Set(activity.workspace_id),
+        actor_id: Set(Some(activity.actor_id)),
+        entity: Set(activity.entity),
+        entity_id: Set(activity.entity_id),
+        task_id: Set(activity.task_id),
+        action: Set(activity.action),
+        changes: Set(activity.changes),
+        created_at: Set(chrono::Utc::now()),
+    }
+    .insert(db)
+    .await?;
+
+    Ok(())
+}
+
+/// The workspace a task is in, whether or not the task is in the trash.
+pub(crate) async fn task_workspace_id<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+) -> AppResult<Uuid> {
+    let (_, topic) = Task::find_by_id(task_id)
+        .find_also_related(topic::Entity)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+
+    topic
+        .map(|topic| topic.workspace_id)
+        .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))
+}
+
+async fn activity_page<C: ConnectionTrait>(
+    db: &C,
+    query: Select<ActivityLog>,
+    limit: u64,
+    offset: u64,
+) -> AppResult<ActivityListResponse> {
+    let total = query.clone().count(db).await?;
+
+    let activities = query
+        .order_by_desc(activity_log::Column::CreatedAt)
+        .order_by_desc(activity_log::Column::Id)
+        .limit(limit)
+        .offset(offset)
+        .all(db)
+        .await?;
+
+    Ok(ActivityListResponse {
+        activities: activities.into_iter().map(ActivityResponse::from).collect(),
+        total,
+    })
+}
+
+/// The history of a task and everything filed under it, newest first.
+/// Trashed tasks keep their history readable.
+pub async fn get_task_history(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+    limit: u64,
+    offset: u64,
+) -> AppResult<ActivityListResponse> {
+    Task::find_by_id(task_id)
+        .join(JoinType::InnerJoin, task::Relation::Topic.def())
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+
+    let query = ActivityLog::find().filter(activity_log::Column::TaskId.eq(task_id));
+    activity_page(db, query, limit, offset).await
+}
+
+/// The workspace's activity feed, newest first.
+pub async fn get_workspace_activity(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    filter: ActivityFilter,
+    limit: u64,
+    offset: u64,
+) -> AppResult<ActivityListResponse> {
+    find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let mut query = ActivityLog::find().filter(activity_log::Column::WorkspaceId.eq(workspace_id));
+    if let Some(entity) = filter.entity {
+        query = query.filter(activity_log::Column::Entity.eq(entity));
+    }
+    if let Some(action) = filter.action {
+        query = query.filter(activity_log::Column::Action.eq(action));
+    }
+    if let Some(actor_id) = filter.actor_id {
+        query = query.filter(activity_log::Column::ActorId.eq(actor_id));
+    }
+    if let Some(task_id) = filter.task_id {
+        query = query.filter(activity_log::Column::TaskId.eq(task_id));
+    }
+    if let Some(since) = filter.since {
+        query = query.filter(activity_log::Column::CreatedAt.gte(since));
+    }
+    if let Some(until) = filter.until {
+        query = query.filter(activity_log::Column::CreatedAt.lt(until));
+    }
+
+    activity_page(db, query, limit, offset).await
+}
@@ -0,0 +1,308 @@
+use crate::config::Config;
+use crate::database::{field_changes, find_owned_task, record_activity, NewActivity};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    attachment::{self, Entity as Attachment},
+};
+use crate::storage::{BlobReader, BlobStorage};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+const MAX_FILENAME_LEN: usize = 255;
+
+/// Describes an upload whose contents are read separately.
+#[derive(Debug)]
+pub struct AttachmentUpload {
+    pub filename: String,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub uploaded_by: Uuid,
+    pub filename: String,
+    pub size_bytes: i64,
+    pub mime_type: String,
+    pub checksum: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<attachment::Model> for AttachmentResponse {
+    fn from(attachment: attachment::Model) -> Self {
+        Self {
+            id: attachment.id,
+            task_id: attachment.task_id,
+            uploaded_by: attachment.uploaded_by,
+            filename: attachment.filename,
+            size_bytes: attachment.size_bytes,
+            mime_type: attachment.mime_type,
+            checksum: attachment.checksum,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+/// Keeps the last path component of a client-supplied name and drops control
+/// characters, so the name is safe to echo back in a download header.
+fn sanitize_filename(filename: &str) -> AppResult<String> {
+    let name: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect();
+    let name = name.trim();
+
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(AppError::Validation(
+            "Attachment filename is required".to_string(),
+        ));
+    }
+
+    if name.chars().count() > MAX_FILENAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Attachment filename cannot be longer than {} characters",
+            MAX_FILENAME_LEN
+        )));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Checks a MIME type against `ATTACHMENT_ALLOWED_MIME_TYPES`. Entries such
+/// as `image/*` allow a whole type; `*/*` allows everything.
+fn check_mime_type_allowed(config: &Config, mime_type: &str) -> AppResult<()> {
+    let main_type = mime_type.split('/').next().unwrap_or_default();
+
+    let allowed =
+        config
+            .attachment_allowed_mime_types
+            .iter()
+            .any(|entry| match entry.strip_suffix("/*") {
+                Some("*") => true,
+                Some(prefix) => prefix == main_type,
+                None => *entry == mime_type,
+            });
+
+    if !allowed {
+        return Err(AppError::Validation(format!(
+            "Attachments of type {} are not allowed",
+            mime_type
+        )));
+    }
+
+    Ok(())
+}
+
+/// Copies an upload into a temporary file, hashing it on the way. Fails
+/// once more than `max_bytes` have been read.
+async fn spool_upload<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    path: &std::path::Path,
+    max_bytes: u64,
+) -> AppResult<(u64, String)> {
+    let io_error = |e: std::io::Error| AppError::Internal(format!("Failed to store upload: {}", e));
+
+    let mut file = tokio::fs::File::create(path).await.map_err(io_error)?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buffer).await.map_err(io_error)?;
+        if read == 0 {
+            break;
+        }
+
+        size += read as u64;
+        if size > max_bytes {
+            return Err(AppError::Validation(format!(
+                "Attachments cannot be larger than {} bytes",
+                max_bytes
+            )));
+        }
+
+        hasher.update(&buffer[..read]);
+        file.write_all(&buffer[..read]).await.map_err(io_error)?;
+    }
+
+    file.flush().await.map_err(io_error)?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+/// Loads an attachment of a live task in one of the user's workspaces, along
+/// with the ID of that workspace.
+async fn find_accessible_attachment<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> AppResult<(attachment::Model, Uuid)> {
+    let attachment = Attachment::find_by_id(attachment_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+    let (_, topic) = find_owned_task(db, user_id, attachment.task_id)
+        .await
+        .map_err(|_| AppError::NotFound("Attachment not found".to_string()))?;
+
+    Ok((attachment, topic.workspace_id))
+}
+
+pub async fn get_attachments_by_task(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<Vec<AttachmentResponse>> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+
+    let attachments = Attachment::find()
+        .filter(attachment::Column::TaskId.eq(task.id))
+        .order_by_asc(attachment::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(attachments
+        .into_iter()
+        .map(AttachmentResponse::from)
+        .collect())
+}
+
+pub async fn get_attachment_by_id(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> AppResult<AttachmentResponse> {
+    let (attachment, _) = find_accessible_attachment(db, user_id, attachment_id).await?;
+    Ok(AttachmentResponse::from(attachment))
+}
+
+/// Streams an upload into blob storage and records its metadata. The reader
+/// should already be capped a little above `ATTACHMENT_MAX_BYTES` so an
+/// oversized body is never read in full.
+pub async fn create_attachment<R: AsyncRead + Unpin>(
+    db: &DatabaseConnection,
+    storage: &dyn BlobStorage,
+    config: &Config,
+    user_id: Uuid,
+    task_id: Uuid,
+    upload: AttachmentUpload,
+    reader: &mut R,
+) -> AppResult<AttachmentResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    let filename = sanitize_filename(&upload.filename)?;
+    let mime_type = upload.mime_type.to_lowercase();
+    check_mime_type_allowed(config, &mime_type)?;
+
+    let attachment_id = Uuid::new_v4();
+    let storage_key = format!("tasks/{}/{}", task.id, attachment_id);
+    let spool_path = std::env::temp_dir().join(format!("upload-{}", attachment_id));
+
+    let spooled = spool_upload(reader, &spool_path, config.attachment_max_bytes).await;
+    let stored = match spooled {
+        Ok(_) => {
+            storage
+                .put_file(&storage_key, &spool_path, &mime_type)
+                .await
+        }
+        Err(_) => Ok(()),
+    };
+    let _ = tokio::fs::remove_file(&spool_path).await;
+    let (size, checksum) = spooled?;
+    stored?;
+
+    let attachment = attachment::ActiveModel {
+        id: Set(attachment_id),
+        task_id: Set(task.id),
+        uploaded_by: Set(user_id),
+        filename: Set(filename),
+        size_bytes: Set(size as i64),
+        mime_type: Set(mime_type),
+        checksum: Set(checksum),
+        storage_key: Set(storage_key.clone()),
+        ..Default::default()
+    };
+
+    let inserted = async {
+        let txn = db.begin().await?;
+        let attachment = attachment.insert(&txn).await?;
+        record_activity(
+            &txn,
+            NewActivity::new(
+                topic.workspace_id,
+                user_id,
+                ActivityEntity::Attachment,
+                attachment.id,
+                ActivityAction::Created,
+            )
+            .on_task(task.id)
+            .with_changes(field_changes(None, Some(&attachment))),
+        )
+        .await?;
+        txn.commit().await?;
+        AppResult::Ok(attachment)
+    }
+    .await;
+
+    match inserted {
+        Ok(attachment) => Ok(AttachmentResponse::from(attachment)),
+        Err(e) => {
+            if let Err(cleanup) = storage.delete(&storage_key).await {
+                eprintln!("Failed to clean up blob of failed upload: {}", cleanup);
+            }
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_attachment_content(
+    db: &DatabaseConnection,
+    storage: &dyn BlobStorage,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> AppResult<(AttachmentResponse, BlobReader)> {
+    let (attachment, _) = find_accessible_attachment(db, user_id, attachment_id).await?;
+    let reader = storage.get(&attachment.storage_key).await?;
+    Ok((AttachmentResponse::from(attachment), reader))
+}
+
+/// Deletes the metadata first so a storage failure can at worst leave an
+/// orphaned blob, never an attachment without contents.
+pub async fn delete_attachment(
+    db: &DatabaseConnection,
+    storage: &dyn BlobStorage,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> AppResult<bool> {
+    let (attachment, workspace_id) = find_accessible_attachment(db, user_id, attachment_id).await?;
+
+    let txn = db.begin().await?;
+    Attachment::delete_by_id(attachment.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Attachment,
+            attachment.id,
+            ActivityAction::Deleted,
+        )
+        .on_task(attachment.task_id)
+        .with_changes(field_changes(Some(&attachment), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    if let Err(e) = storage.delete(&attachment.storage_key).await {
+        eprintln!("Failed to delete attachment blob: {}", e);
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,407 @@
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::database::{
+    apply_task_move, apply_task_update, change_task_labels, check_workspace_labels,
+    filtered_task_query, invalidate_tasks_cache, issue_undo_token, resolve_workspace_id,
+    trash_task, validate_effort_minutes, MoveTaskRequest, TaskFilterOptions, TaskLabelChange,
+    TaskRevision, UndoStep, UndoTokenResponse, UpdateTaskRequest,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    task::{self, Entity as Task},
+    topic::{self, Entity as Topic},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Most tasks one bulk operation may touch.
+pub const MAX_BULK_TASKS: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkTaskRequest {
+    /// Tasks to operate on. When omitted, the operation applies to the
+    /// tasks matching the query string filters instead.
+    pub task_ids: Option<Vec<Uuid>>,
+    pub operation: BulkTaskOperation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkTaskOperation {
+    /// Sets the given fields on every task, as `PUT /api/tasks/<id>` would.
+    Update {
+        fields: Box<UpdateTaskRequest>,
+    },
+    /// Moves every task into a topic and/or status, at the end of the list.
+    Move {
+        topic_id: Option<Uuid>,
+        status_id: Option<Uuid>,
+        force: Option<bool>,
+    },
+    /// Completes (or with `completed: false` reopens) every task.
+    Complete {
+        completed: Option<bool>,
+        force: Option<bool>,
+    },
+    /// Moves every task and its subtasks to the trash.
+    Delete,
+    AddLabels {
+        label_ids: Vec<Uuid>,
+    },
+    RemoveLabels {
+        label_ids: Vec<Uuid>,
+    },
+}
+
+/// Which tasks a bulk operation applies to.
+pub enum BulkTaskSelection {
+    Ids(Vec<Uuid>),
+    Filter(Box<TaskFilterOptions>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkTaskItemResult {
+    pub task_id: Uuid,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkTaskResponse {
+    /// One result per selected task, in the order they were processed.
+    pub results: Vec<BulkTaskItemResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Reverses every change that succeeded; absent when nothing changed.
+    pub undo: Option<UndoTokenResponse>,
+}
+
+/// Applies one operation to many tasks of a workspace in a single
+/// transaction. Each task runs in its own savepoint, so a task that fails
+/// (not found, blocked, ...) is reported and rolled back while the others
+/// are kept.
+pub async fn bulk_update_tasks(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Option<Uuid>,
+    selection: BulkTaskSelection,
+    operation: BulkTaskOperation,
+) -> AppResult<BulkTaskResponse> {
+    let workspace_id = resolve_workspace_id(db, user_id, workspace_id).await?;
+    validate_bulk_operation(db, workspace_id, &operation).await?;
+    let task_ids = select_task_ids(db, user_id, workspace_id, selection).await?;
+
+    let now = chrono::Utc::now();
+    let txn = db.begin().await?;
+
+    let mut results = Vec::with_capacity(task_ids.len());
+    let mut changed: Vec<task::Model> = Vec::new();
+    let mut snapshots: Vec<TaskRevision> = Vec::new();
+    let mut trashed_ids: Vec<Uuid> = Vec::new();
+    let mut label_snapshots: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+
+    for task_id in task_ids {
+        // Subtasks go to the trash with their parent
+        if trashed_ids.contains(&task_id) {
+            results.push(BulkTaskItemResult {
+                task_id,
+                success: true,
+                error: None,
+            });
+            continue;
+        }
+
+        let item = txn.begin().await?;
+        let applied =
+            apply_bulk_operation(&item, user_id, workspace_id, task_id, &operation, now).await;
+        let error = match applied {
+            Ok(outcome) => {
+                item.commit().await?;
+                match outcome {
+                    BulkItemOutcome::Changed { before, after } => {
+                        snapshots.push(TaskRevision {
+                            before: *before,
+                            after: after.clone(),
+                        });
+                        changed.push(after);
+                    }
+                    BulkItemOutcome::Trashed { task, task_ids } => {
+                        trashed_ids.extend(task_ids);
+                        changed.push(task);
+                    }
+                    BulkItemOutcome::Relabeled { task, label_ids } => {
+                        label_snapshots.insert(task.id, label_ids);
+                        changed.push(task);
+                    }
+                }
+                None
+            }
+            Err(e) => {
+                item.rollback().await?;
+                Some(e.to_string())
+            }
+        };
+
+        results.push(BulkTaskItemResult {
+            task_id,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    let mut steps = Vec::new();
+    if !snapshots.is_empty() {
+        steps.push(UndoStep::RevertTasks { tasks: snapshots });
+    }
+    if !trashed_ids.is_empty() {
+        steps.push(UndoStep::RestoreTasks {
+            task_ids: trashed_ids,
+            deleted_at: now,
+        });
+    }
+    if !label_snapshots.is_empty() {
+        steps.push(UndoStep::RestoreTaskLabels {
+            label_ids: label_snapshots,
+        });
+    }
+    let undo = if steps.is_empty() {
+        None
+    } else {
+        Some(issue_undo_token(&txn, config, user_id, workspace_id, steps).await?)
+    };
+
+    txn.commit().await?;
+
+    if !changed.is_empty() {
+        invalidate_tasks_cache(cache, user_id).await;
+    }
+    let action = match operation {
+        BulkTaskOperation::Delete => ChangeAction::Deleted,
+        _ => ChangeAction::Updated,
+    };
+    for task in &changed {
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::task(action, user_id, workspace_id, task),
+        )
+        .await;
+    }
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    Ok(BulkTaskResponse {
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+        undo,
+    })
+}
+
+/// What applying the operation did to one task.
+enum BulkItemOutcome {
+    Changed {
+        before: Box<task::Model>,
+        after: task::Model,
+    },
+    Trashed {
+        task: task::Model,
+        task_ids: Vec<Uuid>,
+    },
+    Relabeled {
+        task: task::Model,
+        label_ids: Vec<Uuid>,
+    },
+}
+
+/// Rejects operations that would fail for every task alike, before any
+/// task is touched.
+async fn validate_bulk_operation(
+    db: &DatabaseConnection,
+    workspace_id: Uuid,
+    operation: &BulkTaskOperation,
+) -> AppResult<()> {
+    match operation {
+        BulkTaskOperation::Update { fields } => {
+            if fields.instance_date.is_some() {
+                return Err(AppError::BadRequest(
+                    "Single occurrences cannot be updated in bulk".to_string(),
+                ));
+            }
+            validate_effort_minutes(fields.estimate_minutes, fields.remaining_minutes)?;
+        }
+        BulkTaskOperation::Move {
+            topic_id,
+            status_id,
+            ..
+        } => {
+            if topic_id.is_none() && status_id.is_none() {
+                return Err(AppError::BadRequest(
+                    "A move needs a topic_id or a status_id".to_string(),
+                ));
+            }
+            if let Some(topic_id) = topic_id {
+                Topic::find_by_id(*topic_id)
+                    .filter(topic::Column::WorkspaceId.eq(workspace_id))
+                    .filter(topic::Column::DeletedAt.is_null())
+                    .one(db)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::BadRequest(
+                            "Target topic must be in the same workspace".to_string(),
+                        )
+                    })?;
+            }
+        }
+        BulkTaskOperation::AddLabels { label_ids } => {
+            if label_ids.is_empty() {
+                return Err(AppError::BadRequest(
+                    "label_ids must not be empty".to_string(),
+                ));
+            }
+            check_workspace_labels(db, workspace_id, label_ids).await?;
+        }
+        BulkTaskOperation::RemoveLabels { label_ids } => {
+            if label_ids.is_empty() {
+                return Err(AppError::BadRequest(
+                    "label_ids must not be empty".to_string(),
+                ));
+            }
+        }
+        BulkTaskOperation::Complete { .. } | BulkTaskOperation::Delete => {}
+    }
+
+    Ok(())
+}
+
+/// The IDs the operation applies to, without duplicates and in a stable
+/// order. A filter has to narrow the workspace down; it cannot select all
+/// of it.
+async fn select_task_ids(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    selection: BulkTaskSelection,
+) -> AppResult<Vec<Uuid>> {
+    let task_ids: Vec<Uuid> = match selection {
+        BulkTaskSelection::Ids(task_ids) => {
+            let mut seen = HashSet::new();
+            task_ids.into_iter().filter(|id| seen.insert(*id)).collect()
+        }
+        BulkTaskSelection::Filter(options) => {
+            if !narrows_tasks(&options) {
+                return Err(AppError::BadRequest(
+                    "Give task_ids or at least one filter".to_string(),
+                ));
+            }
+            filtered_task_query(db, user_id, workspace_id, &options)
+                .await?
+                .order_by_asc(task::Column::Rank)
+                .order_by_asc(task::Column::Id)
+                .limit(MAX_BULK_TASKS as u64 + 1)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|task| task.id)
+                .collect()
+        }
+    };
+
+    if task_ids.is_empty() {
+        return Err(AppError::BadRequest("No tasks selected".to_string()));
+    }
+    if task_ids.len() > MAX_BULK_TASKS {
+        return Err(AppError::BadRequest(format!(
+            "A bulk operation can change at most {} tasks",
+            MAX_BULK_TASKS
+        )));
+    }
+
+    Ok(task_ids)
+}
+
+fn narrows_tasks(options: &TaskFilterOptions) -> bool {
+    options.topic_id.is_some()
+        || options.start_date.is_some()
+        || options.end_date.is_some()
+        || options.task_types.is_some()
+        || options.completed.is_some()
+        || options.status_ids.is_some()
+        || options.parent_task_id.is_some()
+        || options.top_level_only
+        || options.labels.is_some()
+        || !options.custom_fields.is_empty()
+        || options.assignee.is_some()
+        || options.priorities.is_some()
+        || options.urgent.is_some()
+        || options.title_contains.is_some()
+}
+
+async fn apply_bulk_operation<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task_id: Uuid,
+    operation: &BulkTaskOperation,
+    now: chrono::DateTime<chrono::Utc>,
+) -> AppResult<BulkItemOutcome> {
+    let task = Task::find_by_id(task_id)
+        .inner_join(Topic)
+        .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+    let before = Box::new(task.clone());
+
+    let outcome = match operation {
+        BulkTaskOperation::Update { fields } => {
+            let fields = UpdateTaskRequest::clone(fields);
+            let after = apply_task_update(db, user_id, workspace_id, task, fields).await?;
+            BulkItemOutcome::Changed { before, after }
+        }
+        BulkTaskOperation::Complete { completed, force } => {
+            let fields = UpdateTaskRequest {
+                completed: Some(completed.unwrap_or(true)),
+                force: *force,
+                ..Default::default()
+            };
+            let after = apply_task_update(db, user_id, workspace_id, task, fields).await?;
+            BulkItemOutcome::Changed { before, after }
+        }
+        BulkTaskOperation::Move {
+            topic_id,
+            status_id,
+            force,
+        } => {
+            let request = MoveTaskRequest {
+                topic_id: *topic_id,
+                status_id: *status_id,
+                force: *force,
+                ..Default::default()
+            };
+            let after = apply_task_move(db, user_id, workspace_id, task, &request).await?;
+            BulkItemOutcome::Changed { before, after }
+        }
+        BulkTaskOperation::Delete => {
+            let (task, task_ids) = trash_task(db, user_id, workspace_id, task, now).await?;
+            BulkItemOutcome::Trashed { task, task_ids }
+        }
+        BulkTaskOperation::AddLabels { label_ids } => {
+            let change = TaskLabelChange::Add(label_ids);
+            let label_ids = change_task_labels(db, workspace_id, user_id, task.id, change).await?;
+            BulkItemOutcome::Relabeled { task, label_ids }
+        }
+        BulkTaskOperation::RemoveLabels { label_ids } => {
+            let change = TaskLabelChange::Remove(label_ids);
+            let label_ids = change_task_labels(db, workspace_id, user_id, task.id, change).await?;
+            BulkItemOutcome::Relabeled { task, label_ids }
+        }
+    };
+
+    Ok(outcome)
+}
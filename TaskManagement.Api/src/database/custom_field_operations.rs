@@ -0,0 +1,731 @@
+use crate::cache::Cache;
+use crate::database::{
+    field_change, field_changes, find_owned_task, find_owned_workspace, get_task_by_id,
+    invalidate_tasks_cache, record_activity, NewActivity, TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    custom_field::{self, CustomFieldType, Entity as CustomField},
+    task::{self, Entity as Task},
+    task_custom_field_value::{self, Entity as TaskCustomFieldValue},
+};
+use sea_orm::sea_query::{Expr, Query, SimpleExpr};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCustomFieldRequest {
+    pub name: String,
+    pub field_type: CustomFieldType,
+    /// Required for `select` fields, not allowed for the others.
+    pub options: Option<Vec<String>>,
+    pub position: Option<i32>,
+}
+
+/// The type of a field cannot be changed once it has values.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCustomFieldRequest {
+    pub name: Option<String>,
+    /// Replaces the options of a `select` field. Tasks using a removed
+    /// option lose their value.
+    pub options: Option<Vec<String>>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustomFieldResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub field_type: CustomFieldType,
+    pub options: Option<Vec<String>>,
+    pub position: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<custom_field::Model> for CustomFieldResponse {
+    fn from(field: custom_field::Model) -> Self {
+        Self {
+            id: field.id,
+            workspace_id: field.workspace_id,
+            name: field.name,
+            field_type: field.field_type,
+            options: field.options,
+            position: field.position,
+            created_at: field.created_at,
+            updated_at: field.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomFieldValueResponse {
+    pub field_id: Uuid,
+    /// A string, number, `YYYY-MM-DD` date or boolean, depending on the
+    /// field type.
+    pub value: serde_json::Value,
+}
+
+/// Sets custom field values on a task. A `null` value clears the field;
+/// fields that are not mentioned keep their value.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetCustomFieldValuesRequest {
+    pub values: HashMap<Uuid, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomFieldOp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Contains,
+}
+
+impl std::str::FromStr for CustomFieldOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "eq" => Ok(CustomFieldOp::Eq),
+            "ne" => Ok(CustomFieldOp::Ne),
+            "lt" => Ok(CustomFieldOp::Lt),
+            "lte" | "le" => Ok(CustomFieldOp::Lte),
+            "gt" => Ok(CustomFieldOp::Gt),
+            "gte" | "ge" => Ok(CustomFieldOp::Gte),
+            "contains" => Ok(CustomFieldOp::Contains),
+            _ => Err(format!("Invalid custom field operator: {}", s)),
+        }
+    }
+}
+
+/// One `custom_field=<field_id>:<op>:<value>` task filter. The operator may
+/// be left out (`<field_id>:<value>`) to test for equality. Tasks without a
+/// value for the field never match.
+#[derive(Debug, Clone)]
+pub struct CustomFieldFilter {
+    pub field_id: Uuid,
+    pub op: CustomFieldOp,
+    pub value: String,
+}
+
+impl std::str::FromStr for CustomFieldFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let field_id = parts
+            .next()
+            .and_then(|id| Uuid::parse_str(id.trim()).ok())
+            .ok_or_else(|| format!("Invalid custom field filter: {}", s))?;
+        let (op, value) = match (parts.next(), parts.next()) {
+            (Some(op), Some(value)) => (op.parse()?, value),
+            (Some(value), None) => (CustomFieldOp::Eq, value),
+            _ => return Err(format!("Invalid custom field filter: {}", s)),
+        };
+
+        Ok(CustomFieldFilter {
+            field_id,
+            op,
+            value: value.to_string(),
+        })
+    }
+}
+
+async fn find_workspace_custom_field<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    field_id: Uuid,
+) -> AppResult<custom_field::Model> {
+    CustomField::find_by_id(field_id)
+        .filter(custom_field::Column::WorkspaceId.eq(workspace_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Custom field not found".to_string()))
+}
+
+async fn check_custom_field_name_available<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    name: &str,
+    field_id: Option<Uuid>,
+) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Custom field name is required".to_string(),
+        ));
+    }
+
+    let mut query = CustomField::find()
+        .filter(custom_field::Column::WorkspaceId.eq(workspace_id))
+        .filter(custom_field::Column::Name.eq(name));
+    if let Some(field_id) = field_id {
+        query = query.filter(custom_field::Column::Id.ne(field_id));
+    }
+
+    if query.one(db).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "A custom field named \"{}\" already exists in this workspace",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_custom_field_options(
+    field_type: CustomFieldType,
+    options: Option<&Vec<String>>,
+) -> AppResult<()> {
+    match (field_type, options) {
+        (CustomFieldType::Select, Some(options)) => {
+            if options.is_empty() || options.iter().any(|o| o.trim().is_empty()) {
+                return Err(AppError::Validation(
+                    "Select fields need at least one non-empty option".to_string(),
+                ));
+            }
+            let unique: HashSet<&String> = options.iter().collect();
+            if unique.len() != options.len() {
+                return Err(AppError::Validation(
+                    "Select options must be unique".to_string(),
+                ));
+            }
+            Ok(())
+        }
+        (CustomFieldType::Select, None) => Err(AppError::Validation(
+            "Select fields need at least one option".to_string(),
+        )),
+        (_, Some(_)) => Err(AppError::Validation(
+            "Only select fields have options".to_string(),
+        )),
+        (_, None) => Ok(()),
+    }
+}
+
+fn parse_date(value: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Checks a JSON value against the field definition and turns it into a
+/// row. `null` yields `None`, meaning the value should be cleared.
+pub(crate) fn build_custom_field_value(
+    field: &custom_field::Model,
+    task_id: Uuid,
+    value: &serde_json::Value,
+    now: chrono::DateTime<chrono::Utc>,
+) -> AppResult<Option<task_custom_field_value::Model>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    let mut row = task_custom_field_value::Model {
+        task_id,
+        field_id: field.id,
+        text_value: None,
+        number_value: None,
+        date_value: None,
+        bool_value: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let invalid = |expected: &str| {
+        AppError::Validation(format!(
+            "Custom field \"{}\" expects {}",
+            field.name, expected
+        ))
+    };
+
+    match field.field_type {
+        CustomFieldType::Text => {
+            row.text_value = Some(
+                value
+                    .as_str()
+                    .ok_or_else(|| invalid("a string"))?
+                    .to_string(),
+            );
+        }
+        CustomFieldType::Number => {
+            row.number_value = Some(value.as_f64().ok_or_else(|| invalid("a number"))?);
+        }
+        CustomFieldType::Date => {
+            row.date_value = Some(
+                value
+                    .as_str()
+                    .and_then(parse_date)
+                    .ok_or_else(|| invalid("a date (YYYY-MM-DD)"))?,
+            );
+        }
+        CustomFieldType::Select => {
+            let option = value
+                .as_str()
+                .filter(|v| field.options.iter().flatten().any(|o| o == v))
+                .ok_or_else(|| invalid("one of its options"))?;
+            row.text_value = Some(option.to_string());
+        }
+        CustomFieldType::Checkbox => {
+            row.bool_value = Some(value.as_bool().ok_or_else(|| invalid("true or false"))?);
+        }
+    }
+
+    Ok(Some(row))
+}
+
+pub(crate) fn custom_field_value_json(value: &task_custom_field_value::Model) -> serde_json::Value {
+    if let Some(text) = &value.text_value {
+        serde_json::Value::from(text.clone())
+    } else if let Some(number) = value.number_value {
+        serde_json::Value::from(number)
+    } else if let Some(date) = value.date_value {
+        serde_json::Value::from(date.format("%Y-%m-%d").to_string())
+    } else if let Some(flag) = value.bool_value {
+        serde_json::Value::from(flag)
+    } else {
+        serde_json::Value::Null
+    }
+}
+
+/// Validates and stores custom field values of a task. Every field must
+/// belong to the given workspace.
+pub async fn set_custom_field_values<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    task_id: Uuid,
+    values: &HashMap<Uuid, serde_json::Value>,
+) -> AppResult<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let fields: HashMap<Uuid, custom_field::Model> = CustomField::find()
+        .filter(custom_field::Column::WorkspaceId.eq(workspace_id))
+        .filter(custom_field::Column::Id.is_in(values.keys().copied()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|field| (field.id, field))
+        .collect();
+
+    let now = chrono::Utc::now();
+    let mut rows = Vec::new();
+    for (field_id, value) in values {
+        let field = fields.get(field_id).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Custom field {} does not belong to this workspace",
+                field_id
+            ))
+        })?;
+        if let Some(row) = build_custom_field_value(field, task_id, value, now)? {
+            rows.push(task_custom_field_value::ActiveModel::from(row));
+        }
+    }
+
+    TaskCustomFieldValue::delete_many()
+        .filter(task_custom_field_value::Column::TaskId.eq(task_id))
+        .filter(task_custom_field_value::Column::FieldId.is_in(values.keys().copied()))
+        .exec(db)
+        .await?;
+
+    if !rows.is_empty() {
+        TaskCustomFieldValue::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+/// The task's values of the given fields, keyed by field ID; unset fields
+/// are `null`.
+async fn task_custom_field_values<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    field_ids: impl IntoIterator<Item = Uuid>,
+) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+    let field_ids: Vec<Uuid> = field_ids.into_iter().collect();
+    let mut values: serde_json::Map<String, serde_json::Value> = field_ids
+        .iter()
+        .map(|id| (id.to_string(), serde_json::Value::Null))
+        .collect();
+
+    let rows = TaskCustomFieldValue::find()
+        .filter(task_custom_field_value::Column::TaskId.eq(task_id))
+        .filter(task_custom_field_value::Column::FieldId.is_in(field_ids))
+        .all(db)
+        .await?;
+    for row in rows {
+        values.insert(row.field_id.to_string(), custom_field_value_json(&row));
+    }
+
+    Ok(values)
+}
+
+/// Fills `custom_fields` on the given responses with a single query.
+pub async fn attach_task_custom_fields<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let rows = TaskCustomFieldValue::find()
+        .filter(task_custom_field_value::Column::TaskId.is_in(ids))
+        .find_also_related(CustomField)
+        .order_by_asc(custom_field::Column::Position)
+        .order_by_asc(custom_field::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut values: HashMap<Uuid, Vec<CustomFieldValueResponse>> = HashMap::new();
+    for (value, _) in rows {
+        values
+            .entry(value.task_id)
+            .or_default()
+            .push(CustomFieldValueResponse {
+                field_id: value.field_id,
+                value: custom_field_value_json(&value),
+            });
+    }
+
+    for task in tasks.iter_mut() {
+        task.custom_fields = values.get(&task.id).cloned().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+fn custom_field_value_column(field_type: CustomFieldType) -> task_custom_field_value::Column {
+    match field_type {
+        CustomFieldType::Text | CustomFieldType::Select => {
+            task_custom_field_value::Column::TextValue
+        }
+        CustomFieldType::Number => task_custom_field_value::Column::NumberValue,
+        CustomFieldType::Date => task_custom_field_value::Column::DateValue,
+        CustomFieldType::Checkbox => task_custom_field_value::Column::BoolValue,
+    }
+}
+
+fn compare_custom_field_value<V>(
+    column: task_custom_field_value::Column,
+    op: CustomFieldOp,
+    value: V,
+) -> AppResult<SimpleExpr>
+where
+    V: Into<sea_orm::Value>,
+{
+    Ok(match op {
+        CustomFieldOp::Eq => column.eq(value),
+        CustomFieldOp::Ne => column.ne(value),
+        CustomFieldOp::Lt => column.lt(value),
+        CustomFieldOp::Lte => column.lte(value),
+        CustomFieldOp::Gt => column.gt(value),
+        CustomFieldOp::Gte => column.gte(value),
+        CustomFieldOp::Contains => {
+            return Err(AppError::BadRequest(
+                "The contains operator only works on text fields".to_string(),
+            ))
+        }
+    })
+}
+
+/// Builds the condition for a set of custom field filters; all of them have
+/// to match.
+pub async fn custom_field_filter_condition<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    filters: &[CustomFieldFilter],
+) -> AppResult<Condition> {
+    let mut condition = Condition::all();
+
+    for filter in filters {
+        let field = find_workspace_custom_field(db, workspace_id, filter.field_id)
+            .await
+            .map_err(|_| {
+                AppError::BadRequest(format!("Unknown custom field {}", filter.field_id))
+            })?;
+        let column = custom_field_value_column(field.field_type);
+        let invalid = || {
+            AppError::BadRequest(format!(
+                "Invalid value for custom field \"{}\": {}",
+                field.name, filter.value
+            ))
+        };
+
+        let comparison = match field.field_type {
+            CustomFieldType::Text | CustomFieldType::Select
+                if filter.op == CustomFieldOp::Contains =>
+            {
+                column.contains(&filter.value)
+            }
+            CustomFieldType::Text | CustomFieldType::Select => {
+                compare_custom_field_value(column, filter.op, filter.value.clone())?
+            }
+            CustomFieldType::Number => {
+                let number: f64 = filter.value.parse().map_err(|_| invalid())?;
+                compare_custom_field_value(column, filter.op, number)?
+            }
+            CustomFieldType::Date => {
+                let date = parse_date(&filter.value).ok_or_else(invalid)?;
+                compare_custom_field_value(column, filter.op, date)?
+            }
+            CustomFieldType::Checkbox => {
+                let flag: bool = filter.value.parse().map_err(|_| invalid())?;
+                compare_custom_field_value(column, filter.op, flag)?
+            }
+        };
+
+        let mut subquery = Query::select();
+        subquery
+            .column(task_custom_field_value::Column::TaskId)
+            .from(TaskCustomFieldValue)
+            .and_where(task_custom_field_value::Column::FieldId.eq(field.id))
+            .and_where(comparison);
+
+        condition = condition.add(task::Column::Id.in_subquery(subquery.to_owned()));
+    }
+
+    Ok(condition)
+}
+
+/// The value of a custom field for the task in the current row, for use in
+/// `ORDER BY`. Tasks without a value sort as NULL.
+pub async fn custom_field_sort_expr<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    field_id: Uuid,
+) -> AppResult<SimpleExpr> {
+    let field = find_workspace_custom_field(db, workspace_id, field_id)
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Unknown custom field {}", field_id)))?;
+
+    let mut subquery = Query::select();
+    subquery
+        .column(custom_field_value_column(field.field_type))
+        .from(TaskCustomFieldValue)
+        .and_where(
+            Expr::col((
+                TaskCustomFieldValue,
+                task_custom_field_value::Column::TaskId,
+            ))
+            .equals((Task, task::Column::Id)),
+        )
+        .and_where(task_custom_field_value::Column::FieldId.eq(field.id));
+
+    Ok(SimpleExpr::SubQuery(
+        None,
+        Box::new(subquery.to_owned().into_sub_query_statement()),
+    ))
+}
+
+pub async fn list_custom_fields(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<CustomFieldResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    get_custom_fields_by_workspace(db, workspace.id).await
+}
+
+pub async fn get_custom_fields_by_workspace(
+    db: &DatabaseConnection,
+    workspace_id: Uuid,
+) -> AppResult<Vec<CustomFieldResponse>> {
+    let fields = CustomField::find()
+        .filter(custom_field::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(custom_field::Column::Position)
+        .order_by_asc(custom_field::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(fields.into_iter().map(CustomFieldResponse::from).collect())
+}
+
+pub async fn create_custom_field(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateCustomFieldRequest,
+) -> AppResult<CustomFieldResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    check_custom_field_name_available(db, workspace.id, &request.name, None).await?;
+    validate_custom_field_options(request.field_type, request.options.as_ref())?;
+
+    let position = match request.position {
+        Some(position) => position,
+        None => CustomField::find()
+            .filter(custom_field::Column::WorkspaceId.eq(workspace.id))
+            .order_by_desc(custom_field::Column::Position)
+            .one(db)
+            .await?
+            .map_or(0, |last| last.position + 1),
+    };
+
+    let field = custom_field::ActiveModel {
+        workspace_id: Set(workspace.id),
+        name: Set(request.name),
+        field_type: Set(request.field_type),
+        options: Set(request.options),
+        position: Set(position),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let field = field.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::CustomField,
+            field.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&field))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(CustomFieldResponse::from(field))
+}
+
+pub async fn update_custom_field(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    field_id: Uuid,
+    request: UpdateCustomFieldRequest,
+) -> AppResult<CustomFieldResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let field = find_workspace_custom_field(db, workspace.id, field_id).await?;
+
+    if let Some(name) = &request.name {
+        check_custom_field_name_available(db, workspace.id, name, Some(field.id)).await?;
+    }
+    if request.options.is_some() {
+        validate_custom_field_options(field.field_type, request.options.as_ref())?;
+    }
+
+    let before = field.clone();
+    let txn = db.begin().await?;
+    let mut field: custom_field::ActiveModel = field.into();
+
+    if let Some(name) = request.name {
+        field.name = Set(name);
+    }
+
+    if let Some(options) = request.options {
+        TaskCustomFieldValue::delete_many()
+            .filter(task_custom_field_value::Column::FieldId.eq(field_id))
+            .filter(task_custom_field_value::Column::TextValue.is_not_in(options.clone()))
+            .exec(&txn)
+            .await?;
+        field.options = Set(Some(options));
+    }
+
+    if let Some(position) = request.position {
+        field.position = Set(position);
+    }
+
+    field.updated_at = Set(chrono::Utc::now());
+    let field = field.update(&txn).await?;
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::CustomField,
+            field.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&field))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(CustomFieldResponse::from(field))
+}
+
+pub async fn delete_custom_field(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    field_id: Uuid,
+) -> AppResult<bool> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let field = find_workspace_custom_field(db, workspace.id, field_id).await?;
+
+    let txn = db.begin().await?;
+    CustomField::delete_by_id(field.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::CustomField,
+            field.id,
+            ActivityAction::Deleted,
+        )
+        .with_changes(field_changes(Some(&field), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(true)
+}
+
+pub async fn update_task_custom_fields(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: SetCustomFieldValuesRequest,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+
+    let field_ids = request.values.keys().copied();
+    let txn = db.begin().await?;
+    let before = task_custom_field_values(&txn, task.id, field_ids.clone()).await?;
+    set_custom_field_values(&txn, topic.workspace_id, task.id, &request.values).await?;
+    let after = task_custom_field_values(&txn, task.id, field_ids).await?;
+
+    if before != after {
+        record_activity(
+            &txn,
+            NewActivity::new(
+                topic.workspace_id,
+                user_id,
+                ActivityEntity::Task,
+                task.id,
+                ActivityAction::Updated,
+            )
+            .with_changes(field_change("custom_fields", before, after)),
+        )
+        .await?;
+    }
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    get_task_by_id(db, user_id, task.id).await
+}
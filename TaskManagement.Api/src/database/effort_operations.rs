@@ -0,0 +1,258 @@
+use crate::database::{TaskResponse, TopicResponse, MAX_TASK_DEPTH};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    task::{self, Entity as Task},
+    time_entry::{self, Entity as TimeEntry},
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Planned against tracked effort of a task together with its subtasks, or
+/// of all tasks of a topic.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EffortSummary {
+    pub estimate_minutes: i64,
+    /// Completed tasks count as done; open tasks without a remaining
+    /// figure fall back to their estimate.
+    pub remaining_minutes: i64,
+    pub tracked_minutes: i64,
+    /// Tracked minus estimated time, positive when over the estimate.
+    /// `None` while nothing has been estimated.
+    pub variance_minutes: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct EffortTotals {
+    estimate_minutes: i64,
+    remaining_minutes: i64,
+    tracked_seconds: i64,
+    estimated: bool,
+}
+
+impl EffortTotals {
+    fn for_task(
+        estimate_minutes: Option<i32>,
+        remaining_minutes: Option<i32>,
+        completed: bool,
+        tracked_seconds: i64,
+    ) -> Self {
+        let remaining_minutes = if completed {
+            0
+        } else {
+            remaining_minutes.or(estimate_minutes).unwrap_or(0)
+        };
+
+        Self {
+            estimate_minutes: estimate_minutes.unwrap_or(0) as i64,
+            remaining_minutes: remaining_minutes as i64,
+            tracked_seconds,
+            estimated: estimate_minutes.is_some(),
+        }
+    }
+
+    fn add(&mut self, other: EffortTotals) {
+        self.estimate_minutes += other.estimate_minutes;
+        self.remaining_minutes += other.remaining_minutes;
+        self.tracked_seconds += other.tracked_seconds;
+        self.estimated |= other.estimated;
+    }
+
+    fn summary(self) -> EffortSummary {
+        let tracked_minutes = self.tracked_seconds / 60;
+        EffortSummary {
+            estimate_minutes: self.estimate_minutes,
+            remaining_minutes: self.remaining_minutes,
+            tracked_minutes,
+            variance_minutes: self
+                .estimated
+                .then(|| tracked_minutes - self.estimate_minutes),
+        }
+    }
+}
+
+struct EffortNode {
+    topic_id: Uuid,
+    parent_task_id: Option<Uuid>,
+    totals: EffortTotals,
+}
+
+pub fn validate_effort_minutes(
+    estimate_minutes: Option<i32>,
+    remaining_minutes: Option<i32>,
+) -> AppResult<()> {
+    if estimate_minutes.is_some_and(|m| m < 0) || remaining_minutes.is_some_and(|m| m < 0) {
+        return Err(AppError::Validation(
+            "Estimates cannot be negative".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Loads the own effort of every live task matching `condition`.
+async fn load_effort_nodes<C: ConnectionTrait>(
+    db: &C,
+    condition: Condition,
+) -> AppResult<HashMap<Uuid, EffortNode>> {
+    let tracked: HashMap<Uuid, i64> = TimeEntry::find()
+        .select_only()
+        .column(time_entry::Column::TaskId)
+        .column_as(
+            Expr::cust("CAST(SUM(EXTRACT(EPOCH FROM (ended_at - started_at))) AS BIGINT)"),
+            "seconds",
+        )
+        .inner_join(Task)
+        .filter(condition.clone())
+        .filter(task::Column::DeletedAt.is_null())
+        .filter(time_entry::Column::EndedAt.is_not_null())
+        .group_by(time_entry::Column::TaskId)
+        .into_tuple::<(Uuid, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let tasks = Task::find()
+        .select_only()
+        .column(task::Column::Id)
+        .column(task::Column::TopicId)
+        .column(task::Column::ParentTaskId)
+        .column(task::Column::EstimateMinutes)
+        .column(task::Column::RemainingMinutes)
+        .column(task::Column::Completed)
+        .filter(condition)
+        .filter(task::Column::DeletedAt.is_null())
+        .into_tuple::<(Uuid, Uuid, Option<Uuid>, Option<i32>, Option<i32>, bool)>()
+        .all(db)
+        .await?;
+
+    Ok(tasks
+        .into_iter()
+        .map(
+            |(id, topic_id, parent_task_id, estimate, remaining, completed)| {
+                let tracked_seconds = tracked.get(&id).copied().unwrap_or_default();
+                let node = EffortNode {
+                    topic_id,
+                    parent_task_id,
+                    totals: EffortTotals::for_task(estimate, remaining, completed, tracked_seconds),
+                };
+                (id, node)
+            },
+        )
+        .collect())
+}
+
+/// Loads the own effort of the given live tasks and of their live
+/// subtasks, level by level, whichever topic the subtasks are filed in.
+async fn load_subtree_effort_nodes<C: ConnectionTrait>(
+    db: &C,
+    task_ids: HashSet<Uuid>,
+) -> AppResult<HashMap<Uuid, EffortNode>> {
+    let roots = Condition::all().add(task::Column::Id.is_in(task_ids));
+    let mut nodes = load_effort_nodes(db, roots).await?;
+    let mut frontier: Vec<Uuid> = nodes.keys().copied().collect();
+
+    for _ in 0..MAX_TASK_DEPTH {
+        if frontier.is_empty() {
+            break;
+        }
+
+        let level = Condition::all().add(task::Column::ParentTaskId.is_in(frontier));
+        let children = load_effort_nodes(db, level).await?;
+        frontier = children
+            .keys()
+            .filter(|id| !nodes.contains_key(id))
+            .copied()
+            .collect();
+        nodes.extend(children);
+    }
+
+    Ok(nodes)
+}
+
+fn subtree_totals(
+    task_id: Uuid,
+    nodes: &HashMap<Uuid, EffortNode>,
+    children: &HashMap<Uuid, Vec<Uuid>>,
+    memo: &mut HashMap<Uuid, EffortTotals>,
+) -> EffortTotals {
+    if let Some(totals) = memo.get(&task_id) {
+        return *totals;
+    }
+
+    let mut totals = nodes[&task_id].totals;
+    for child_id in children.get(&task_id).into_iter().flatten() {
+        totals.add(subtree_totals(*child_id, nodes, children, memo));
+    }
+
+    memo.insert(task_id, totals);
+    totals
+}
+
+/// Fills `effort` on the given responses with the rollup of each task and
+/// its live subtasks. Expects `tracked_seconds` to be filled already.
+pub async fn attach_task_effort<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let task_ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let nodes = load_subtree_effort_nodes(db, task_ids).await?;
+
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (id, node) in &nodes {
+        if let Some(parent_id) = node.parent_task_id {
+            children.entry(parent_id).or_default().push(*id);
+        }
+    }
+
+    let mut memo = HashMap::new();
+    for task in tasks.iter_mut() {
+        let totals = if nodes.contains_key(&task.id) {
+            subtree_totals(task.id, &nodes, &children, &mut memo)
+        } else {
+            // Trashed tasks are not part of any rollup
+            EffortTotals::for_task(
+                task.estimate_minutes,
+                task.remaining_minutes,
+                task.completed,
+                task.tracked_seconds,
+            )
+        };
+        task.effort = totals.summary();
+    }
+
+    Ok(())
+}
+
+/// Fills `effort` on the given responses with the totals of each topic's
+/// live tasks.
+pub async fn attach_topic_effort<C: ConnectionTrait>(
+    db: &C,
+    topics: &mut [TopicResponse],
+) -> AppResult<()> {
+    if topics.is_empty() {
+        return Ok(());
+    }
+
+    let topic_ids: HashSet<Uuid> = topics.iter().map(|t| t.id).collect();
+    let in_topics = Condition::all().add(task::Column::TopicId.is_in(topic_ids));
+    let nodes = load_effort_nodes(db, in_topics).await?;
+
+    let mut totals: HashMap<Uuid, EffortTotals> = HashMap::new();
+    for node in nodes.values() {
+        totals.entry(node.topic_id).or_default().add(node.totals);
+    }
+
+    for topic in topics.iter_mut() {
+        topic.effort = totals.remove(&topic.id).unwrap_or_default().summary();
+    }
+
+    Ok(())
+}
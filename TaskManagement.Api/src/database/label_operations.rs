@@ -0,0 +1,581 @@
+use crate::cache::Cache;
+use crate::database::{
+    field_change, field_changes, find_owned_task, find_owned_workspace, get_task_by_id,
+    invalidate_tasks_cache, record_activity, NewActivity, TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    label::{self, Entity as Label},
+    task,
+    task_label::{self, Entity as TaskLabel},
+};
+use sea_orm::sea_query::{Expr, Query, SimpleExpr};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateLabelRequest {
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateLabelRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTaskLabelsRequest {
+    pub label_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LabelResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<label::Model> for LabelResponse {
+    fn from(label: label::Model) -> Self {
+        Self {
+            id: label.id,
+            workspace_id: label.workspace_id,
+            name: label.name,
+            color: label.color,
+            created_at: label.created_at,
+            updated_at: label.updated_at,
+        }
+    }
+}
+
+/// How a multi-label filter combines: tasks with any of the labels, or only
+/// tasks that carry all of them.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum LabelMatch {
+    #[default]
+    Any,
+    All,
+}
+
+impl std::str::FromStr for LabelMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "any" | "or" => Ok(LabelMatch::Any),
+            "all" | "and" => Ok(LabelMatch::All),
+            _ => Err(format!("Invalid label match: {}", s)),
+        }
+    }
+}
+
+async fn find_workspace_label<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    label_id: Uuid,
+) -> AppResult<label::Model> {
+    Label::find_by_id(label_id)
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Label not found".to_string()))
+}
+
+async fn check_label_name_available<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    name: &str,
+    label_id: Option<Uuid>,
+) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation("Label name is required".to_string()));
+    }
+
+    let mut query = Label::find()
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .filter(label::Column::Name.eq(name));
+    if let Some(label_id) = label_id {
+        query = query.filter(label::Column::Id.ne(label_id));
+    }
+
+    if query.one(db).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "A label named \"{}\" already exists in this workspace",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Makes sure every ID names a label of the given workspace.
+pub async fn check_workspace_labels<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    label_ids: &[Uuid],
+) -> AppResult<()> {
+    let unique: HashSet<Uuid> = label_ids.iter().copied().collect();
+    if unique.is_empty() {
+        return Ok(());
+    }
+
+    let found = Label::find()
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .filter(label::Column::Id.is_in(unique.iter().copied()))
+        .count(db)
+        .await?;
+
+    if found as usize != unique.len() {
+        return Err(AppError::BadRequest(
+            "Labels must belong to the task's workspace".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Replaces the labels of a task. The IDs must already have been checked
+/// with `check_workspace_labels`.
+pub async fn replace_task_labels<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    label_ids: &[Uuid],
+) -> AppResult<()> {
+    TaskLabel::delete_many()
+        .filter(task_label::Column::TaskId.eq(task_id))
+        .exec(db)
+        .await?;
+
+    let mut seen = HashSet::new();
+    let now = chrono::Utc::now();
+    let rows: Vec<task_label::ActiveModel> = label_ids
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .map(|label_id| task_label::ActiveModel {
+            task_id: Set(task_id),
+            label_id: Set(*label_id),
+            created_at: Set(now),
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        TaskLabel::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+async fn task_label_ids<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let rows = TaskLabel::find()
+        .filter(task_label::Column::TaskId.eq(task_id))
+        .order_by_asc(task_label::Column::LabelId)
+        .all(db)
+        .await?;
+    Ok(rows.into_iter().map(|row| row.label_id).collect())
+}
+
+/// Logs a change of the task's labels from `before` to what it has now.
+async fn record_task_labels_change<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    task_id: Uuid,
+    before: Vec<Uuid>,
+) -> AppResult<()> {
+    let after = task_label_ids(db, task_id).await?;
+    if before == after {
+        return Ok(());
+    }
+
+    record_activity(
+        db,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task_id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_change("label_ids", before, after)),
+    )
+    .await
+}
+
+/// A change to a task's labels made by `change_task_labels`.
+pub(crate) enum TaskLabelChange<'a> {
+    Add(&'a [Uuid]),
+    Remove(&'a [Uuid]),
+    Replace(&'a [Uuid]),
+}
+
+/// Changes a task's labels within the caller's transaction and logs the
+/// change. Added labels must belong to the workspace. Returns the task's
+/// label IDs from before the change.
+pub(crate) async fn change_task_labels<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    task_id: Uuid,
+    change: TaskLabelChange<'_>,
+) -> AppResult<Vec<Uuid>> {
+    let before = task_label_ids(db, task_id).await?;
+
+    match change {
+        TaskLabelChange::Add(label_ids) => {
+            check_workspace_labels(db, workspace_id, label_ids).await?;
+            let mut seen: HashSet<Uuid> = before.iter().copied().collect();
+            let now = chrono::Utc::now();
+            let rows: Vec<task_label::ActiveModel> = label_ids
+                .iter()
+                .filter(|id| seen.insert(**id))
+                .map(|label_id| task_label::ActiveModel {
+                    task_id: Set(task_id),
+                    label_id: Set(*label_id),
+                    created_at: Set(now),
+                })
+                .collect();
+            if !rows.is_empty() {
+                TaskLabel::insert_many(rows).exec(db).await?;
+            }
+        }
+        TaskLabelChange::Remove(label_ids) => {
+            TaskLabel::delete_many()
+                .filter(task_label::Column::TaskId.eq(task_id))
+                .filter(task_label::Column::LabelId.is_in(label_ids.iter().copied()))
+                .exec(db)
+                .await?;
+        }
+        TaskLabelChange::Replace(label_ids) => {
+            check_workspace_labels(db, workspace_id, label_ids).await?;
+            replace_task_labels(db, task_id, label_ids).await?;
+        }
+    }
+
+    record_task_labels_change(db, workspace_id, user_id, task_id, before.clone()).await?;
+    Ok(before)
+}
+
+/// Fills `labels` on the given responses with a single query.
+pub async fn attach_task_labels<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let rows = TaskLabel::find()
+        .filter(task_label::Column::TaskId.is_in(ids))
+        .find_also_related(Label)
+        .order_by_asc(label::Column::Name)
+        .all(db)
+        .await?;
+
+    let mut labels: HashMap<Uuid, Vec<LabelResponse>> = HashMap::new();
+    for (task_label, label) in rows {
+        if let Some(label) = label {
+            labels
+                .entry(task_label.task_id)
+                .or_default()
+                .push(LabelResponse::from(label));
+        }
+    }
+
+    for task in tasks.iter_mut() {
+        task.labels = labels.get(&task.id).cloned().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Builds the condition for a `labels=a,b` task filter. Labels are referenced
+/// by ID or by name (case-insensitive). With `All`, a reference that matches
+/// no label in the workspace means no task can match.
+pub async fn task_label_filter<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    references: &[String],
+    mode: LabelMatch,
+) -> AppResult<SimpleExpr> {
+    let labels = Label::find()
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .all(db)
+        .await?;
+
+    let mut label_ids = HashSet::new();
+    let mut unresolved = false;
+    for reference in references {
+        let found = labels.iter().find(|label| {
+            Uuid::parse_str(reference).is_ok_and(|id| id == label.id)
+                || label.name.to_lowercase() == reference.to_lowercase()
+        });
+        match found {
+            Some(label) => {
+                label_ids.insert(label.id);
+            }
+            None => unresolved = true,
+        }
+    }
+
+    if unresolved && matches!(mode, LabelMatch::All) {
+        label_ids.clear();
+    }
+
+    let required = label_ids.len() as i64;
+    let mut subquery = Query::select();
+    subquery
+        .column(task_label::Column::TaskId)
+        .from(TaskLabel)
+        .and_where(task_label::Column::LabelId.is_in(label_ids));
+
+    if matches!(mode, LabelMatch::All) {
+        subquery
+            .group_by_col(task_label::Column::TaskId)
+            .and_having(
+                Expr::col(task_label::Column::LabelId)
+                    .count_distinct()
+                    .eq(required),
+            );
+    }
+
+    Ok(task::Column::Id.in_subquery(subquery.to_owned()))
+}
+
+pub async fn list_labels(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<LabelResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    get_labels_by_workspace(db, workspace.id).await
+}
+
+pub async fn get_labels_by_workspace(
+    db: &DatabaseConnection,
+    workspace_id: Uuid,
+) -> AppResult<Vec<LabelResponse>> {
+    let labels = Label::find()
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(label::Column::Name)
+        .all(db)
+        .await?;
+
+    Ok(labels.into_iter().map(LabelResponse::from).collect())
+}
+
+pub async fn create_label(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateLabelRequest,
+) -> AppResult<LabelResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    check_label_name_available(db, workspace.id, &request.name, None).await?;
+
+    let label = label::ActiveModel {
+        workspace_id: Set(workspace.id),
+        name: Set(request.name),
+        color: Set(request.color),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let label = label.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Label,
+            label.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&label))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(LabelResponse::from(label))
+}
+
+pub async fn update_label(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    label_id: Uuid,
+    request: UpdateLabelRequest,
+) -> AppResult<LabelResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let label = find_workspace_label(db, workspace.id, label_id).await?;
+
+    if let Some(name) = &request.name {
+        check_label_name_available(db, workspace.id, name, Some(label.id)).await?;
+    }
+
+    let before = label.clone();
+    let mut label: label::ActiveModel = label.into();
+
+    if let Some(name) = request.name {
+        label.name = Set(name);
+    }
+
+    if let Some(color) = request.color {
+        label.color = Set(color);
+    }
+
+    label.updated_at = Set(chrono::Utc::now());
+
+    let txn = db.begin().await?;
+    let label = label.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Label,
+            label.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&label))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    // Cached task lists embed the label.
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(LabelResponse::from(label))
+}
+
+pub async fn delete_label(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    label_id: Uuid,
+) -> AppResult<bool> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let label = find_workspace_label(db, workspace.id, label_id).await?;
+
+    let txn = db.begin().await?;
+    Label::delete_by_id(label.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Label,
+            label.id,
+            ActivityAction::Deleted,
+        )
+        .with_changes(field_changes(Some(&label), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(true)
+}
+
+pub async fn set_task_labels(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: SetTaskLabelsRequest,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    check_workspace_labels(db, topic.workspace_id, &request.label_ids).await?;
+
+    let txn = db.begin().await?;
+    let before = task_label_ids(&txn, task.id).await?;
+    replace_task_labels(&txn, task.id, &request.label_ids).await?;
+    record_task_labels_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    get_task_by_id(db, user_id, task.id).await
+}
+
+pub async fn add_task_label(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    label_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    let label = find_workspace_label(db, topic.workspace_id, label_id).await?;
+
+    let existing = TaskLabel::find_by_id((task.id, label.id)).one(db).await?;
+    if existing.is_none() {
+        let task_label = task_label::ActiveModel {
+            task_id: Set(task.id),
+            label_id: Set(label.id),
+            ..Default::default()
+        };
+
+        let txn = db.begin().await?;
+        let before = task_label_ids(&txn, task.id).await?;
+        task_label.insert(&txn).await?;
+        record_task_labels_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+        txn.commit().await?;
+
+        invalidate_tasks_cache(cache, user_id).await;
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+        )
+        .await;
+    }
+
+    get_task_by_id(db, user_id, task.id).await
+}
+
+pub async fn remove_task_label(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    label_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+
+    let txn = db.begin().await?;
+    let before = task_label_ids(&txn, task.id).await?;
+    let result = TaskLabel::delete_by_id((task.id, label_id))
+        .exec(&txn)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Label is not on this task".to_string()));
+    }
+    record_task_labels_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    get_task_by_id(db, user_id, task.id).await
+}
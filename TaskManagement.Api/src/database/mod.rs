@@ -1,13 +1,55 @@
+pub mod activity_operations;
+pub mod attachment_operations;
+pub mod bulk_task_operations;
+pub mod custom_field_operations;
+pub mod effort_operations;
+pub mod label_operations;
+pub mod notification_operations;
+pub mod quota_operations;
 pub mod substep_operations;
+pub mod task_assignee_operations;
+pub mod task_comment_operations;
+pub mod task_dependency_operations;
+pub mod task_hierarchy_operations;
 pub mod task_operations;
+pub mod task_reminder_operations;
+pub mod task_status_operations;
+pub mod task_type_operations;
+pub mod time_entry_operations;
 pub mod topic_operations;
+pub mod trash_operations;
+pub mod undo_operations;
 pub mod user_operations;
+pub mod webhook_operations;
+pub mod workspace_export_operations;
 pub mod workspace_operations;
 pub mod workspace_share_link_operations;
+pub mod workspace_template_operations;
 
+pub use activity_operations::*;
+pub use attachment_operations::*;
+pub use bulk_task_operations::*;
+pub use custom_field_operations::*;
+pub use effort_operations::*;
+pub use label_operations::*;
+pub use notification_operations::*;
+pub use quota_operations::*;
 pub use substep_operations::*;
+pub use task_assignee_operations::*;
+pub use task_comment_operations::*;
+pub use task_dependency_operations::*;
+pub use task_hierarchy_operations::*;
 pub use task_operations::*;
+pub use task_reminder_operations::*;
+pub use task_status_operations::*;
+pub use task_type_operations::*;
+pub use time_entry_operations::*;
 pub use topic_operations::*;
+pub use trash_operations::*;
+pub use undo_operations::*;
 pub use user_operations::*;
+pub use webhook_operations::*;
+pub use workspace_export_operations::*;
 pub use workspace_operations::*;
 pub use workspace_share_link_operations::*;
+pub use workspace_template_operations::*;
@@ -0,0 +1,243 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::notification::{self, Entity as Notification, NotificationKind};
+use crate::notifications::NotificationChannel;
+use sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Deliveries are given up after this many failed attempts; the
+/// notification stays in the inbox either way.
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+
+/// A notification to record with `notify`.
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<notification::Model> for NotificationResponse {
+    fn from(notification: notification::Model) -> Self {
+        Self {
+            id: notification.id,
+            kind: notification.kind,
+            title: notification.title,
+            body: notification.body,
+            actor_id: notification.actor_id,
+            workspace_id: notification.workspace_id,
+            task_id: notification.task_id,
+            read_at: notification.read_at,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationListResponse {
+    pub notifications: Vec<NotificationResponse>,
+    /// Matching notifications across all pages.
+    pub total: u64,
+    pub unread_count: u64,
+}
+
+/// Records notifications in the recipients' inboxes. Any operation can call
+/// this, inside its transaction if it has one, so a notification is only
+/// kept when the change that produced it is. Delivery through the
+/// notification channel happens later in the background.
+///
+/// Users are not notified about their own actions.
+pub async fn notify<C: ConnectionTrait>(
+    db: &C,
+    notifications: impl IntoIterator<Item = NewNotification>,
+) -> AppResult<()> {
+    let now = chrono::Utc::now();
+    let rows: Vec<notification::ActiveModel> = notifications
+        .into_iter()
+        .filter(|n| n.actor_id != Some(n.user_id))
+        .map(|n| notification::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(n.user_id),
+            kind: Set(n.kind),
+            title: Set(n.title),
+            body: Set(n.body),
+            actor_id: Set(n.actor_id),
+            workspace_id: Set(n.workspace_id),
+            task_id: Set(n.task_id),
+            read_at: Set(None),
+            delivered_at: Set(None),
+            delivery_attempts: Set(0),
+            created_at: Set(now),
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        Notification::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+async fn find_own_notification<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> AppResult<notification::Model> {
+    Notification::find_by_id(notification_id)
+        .filter(notification::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Notification not found".to_string()))
+}
+
+/// Lists the user's notifications, newest first.
+pub async fn get_notifications_by_user(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    unread_only: bool,
+    limit: u64,
+    offset: u64,
+) -> AppResult<NotificationListResponse> {
+    let mut query = Notification::find().filter(notification::Column::UserId.eq(user_id));
+    if unread_only {
+        query = query.filter(notification::Column::ReadAt.is_null());
+    }
+
+    let total = query.clone().count(db).await?;
+
+    let unread_count = Notification::find()
+        .filter(notification::Column::UserId.eq(user_id))
+        .filter(notification::Column::ReadAt.is_null())
+        .count(db)
+        .await?;
+
+    let notifications = query
+        .order_by_desc(notification::Column::CreatedAt)
+        .order_by_desc(notification::Column::Id)
+        .limit(limit)
+        .offset(offset)
+        .all(db)
+        .await?;
+
+    Ok(NotificationListResponse {
+        notifications: notifications
+            .into_iter()
+            .map(NotificationResponse::from)
+            .collect(),
+        total,
+        unread_count,
+    })
+}
+
+pub async fn mark_notification_read(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> AppResult<NotificationResponse> {
+    let notification = find_own_notification(db, user_id, notification_id).await?;
+    if notification.read_at.is_some() {
+        return Ok(NotificationResponse::from(notification));
+    }
+
+    let mut notification: notification::ActiveModel = notification.into();
+    notification.read_at = Set(Some(chrono::Utc::now()));
+    let notification = notification.update(db).await?;
+
+    Ok(NotificationResponse::from(notification))
+}
+
+/// Marks every unread notification of the user as read and returns how
+/// many there were.
+pub async fn mark_all_notifications_read(db: &DatabaseConnection, user_id: Uuid) -> AppResult<u64> {
+    let result = Notification::update_many()
+        .col_expr(
+            notification::Column::ReadAt,
+            Expr::value(chrono::Utc::now()),
+        )
+        .filter(notification::Column::UserId.eq(user_id))
+        .filter(notification::Column::ReadAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected)
+}
+
+pub async fn delete_notification(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> AppResult<bool> {
+    let notification = find_own_notification(db, user_id, notification_id).await?;
+
+    Notification::delete_by_id(notification.id).exec(db).await?;
+
+    Ok(true)
+}
+
+/// Hands one batch of undelivered notifications to the channel and returns
+/// how many were delivered. A failed delivery is retried on a later run,
+/// up to `MAX_DELIVERY_ATTEMPTS` times. Rows are claimed with `SKIP LOCKED`, so
+/// dispatchers on several replicas never deliver the same notification.
+pub async fn dispatch_pending_notifications(
+    db: &DatabaseConnection,
+    channel: &dyn NotificationChannel,
+    batch_size: u64,
+) -> AppResult<u64> {
+    let txn = db.begin().await?;
+
+    let pending = Notification::find()
+        .filter(notification::Column::DeliveredAt.is_null())
+        .filter(notification::Column::DeliveryAttempts.lt(MAX_DELIVERY_ATTEMPTS))
+        .order_by_asc(notification::Column::CreatedAt)
+        .limit(batch_size)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(&txn)
+        .await?;
+
+    let mut delivered = 0;
+    for notification in &pending {
+        let update = match channel.send(notification).await {
+            Ok(()) => {
+                delivered += 1;
+                Notification::update_many().col_expr(
+                    notification::Column::DeliveredAt,
+                    Expr::value(chrono::Utc::now()),
+                )
+            }
+            Err(e) => {
+                eprintln!("Failed to deliver notification {}: {}", notification.id, e);
+                Notification::update_many().col_expr(
+                    notification::Column::DeliveryAttempts,
+                    Expr::col(notification::Column::DeliveryAttempts).add(1),
+                )
+            }
+        };
+
+        update
+            .filter(notification::Column::Id.eq(notification.id))
+            .exec(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(delivered)
+}
@@ -0,0 +1,287 @@
+use crate::config::Config;
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    task::{self, Entity as Task},
+    task_substep::{self, Entity as TaskSubstep},
+    topic::{self, Entity as Topic},
+    user::Entity as User,
+    workspace::{self, Entity as Workspace},
+    workspace_share_link::{self, Entity as WorkspaceShareLink},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceUsage {
+    pub topics: u64,
+    pub tasks: u64,
+    pub share_links: u64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct WorkspaceCount {
+    workspace_id: Uuid,
+    count: i64,
+}
+
+fn active_share_link_condition() -> Condition {
+    Condition::all()
+        .add(workspace_share_link::Column::RevokedAt.is_null())
+        .add(
+            Condition::any()
+                .add(workspace_share_link::Column::ExpiresAt.is_null())
+                .add(workspace_share_link::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+}
+
+fn exceeds(limit: u64, current: u64, additional: u64) -> bool {
+    limit > 0 && current + additional > limit
+}
+
+/// Counts live topics, tasks and active share links for each of the given
+/// workspaces using one grouped query per resource.
+pub async fn get_workspace_usages<C: ConnectionTrait>(
+    db: &C,
+    workspace_ids: &[Uuid],
+) -> AppResult<HashMap<Uuid, WorkspaceUsage>> {
+    let mut usages: HashMap<Uuid, WorkspaceUsage> = workspace_ids
+        .iter()
+        .map(|id| (*id, WorkspaceUsage::default()))
+        .collect();
+
+    if workspace_ids.is_empty() {
+        return Ok(usages);
+    }
+
+    let topic_counts = Topic::find()
+        .select_only()
+        .column(topic::Column::WorkspaceId)
+        .column_as(topic::Column::Id.count(), "count")
+        .filter(topic::Column::WorkspaceId.is_in(workspace_ids.to_vec()))
+        .filter(topic::Column::DeletedAt.is_null())
+        .group_by(topic::Column::WorkspaceId)
+        .into_model::<WorkspaceCount>()
+        .all(db)
+        .await?;
+
+    for row in topic_counts {
+        if let Some(usage) = usages.get_mut(&row.workspace_id) {
+            usage.topics = row.count as u64;
+        }
+    }
+
+    let task_counts = Task::find()
+        .inner_join(Topic)
+        .select_only()
+        .column_as(topic::Column::WorkspaceId, "workspace_id")
+        .column_as(task::Column::Id.count(), "count")
+        .filter(topic::Column::WorkspaceId.is_in(workspace_ids.to_vec()))
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .group_by(topic::Column::WorkspaceId)
+        .into_model::<WorkspaceCount>()
+        .all(db)
+        .await?;
+
+    for row in task_counts {
+        if let Some(usage) = usages.get_mut(&row.workspace_id) {
+            usage.tasks = row.count as u64;
+        }
+    }
+
+    let share_link_counts = WorkspaceShareLink::find()
+        .select_only()
+        .column(workspace_share_link::Column::WorkspaceId)
+        .column_as(workspace_share_link::Column::Id.count(), "count")
+        .filter(workspace_share_link::Column::WorkspaceId.is_in(workspace_ids.to_vec()))
+        .filter(active_share_link_condition())
+        .group_by(workspace_share_link::Column::WorkspaceId)
+        .into_model::<WorkspaceCount>()
+        .all(db)
+        .await?;
+
+    for row in share_link_counts {
+        if let Some(usage) = usages.get_mut(&row.workspace_id) {
+            usage.share_links = row.count as u64;
+        }
+    }
+
+    Ok(usages)
+}
+
+pub async fn get_workspace_usage<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<WorkspaceUsage> {
+    let mut usages = get_workspace_usages(db, &[workspace_id]).await?;
+    Ok(usages.remove(&workspace_id).unwrap_or_default())
+}
+
+async fn count_user_topics<C: ConnectionTrait>(db: &C, user_id: Uuid) -> AppResult<u64> {
+    Ok(Topic::find()
+        .inner_join(Workspace)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .count(db)
+        .await?)
+}
+
+async fn count_user_tasks<C: ConnectionTrait>(db: &C, user_id: Uuid) -> AppResult<u64> {
+    Ok(Task::find()
+        .inner_join(Topic)
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .count(db)
+        .await?)
+}
+
+async fn count_user_share_links<C: ConnectionTrait>(db: &C, user_id: Uuid) -> AppResult<u64> {
+    Ok(WorkspaceShareLink::find()
+        .inner_join(Workspace)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(active_share_link_condition())
+        .count(db)
+        .await?)
+}
+
+/// Locks the user's and the workspace's rows for the rest of the
+/// transaction, so that concurrent checks for the same owner run one after
+/// the other and each counts what the others committed.
+async fn lock_quota_scope<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<()> {
+    User::find_by_id(user_id).lock_exclusive().one(db).await?;
+    Workspace::find_by_id(workspace_id)
+        .lock_exclusive()
+        .one(db)
+        .await?;
+    Ok(())
+}
+
+/// Fails with `AppError::QuotaExceeded` if adding `additional` topics to the
+/// workspace would go over the per-workspace or per-user topic limit.
+///
+/// Like the other quota checks this must run inside the transaction that
+/// adds the rows, before it commits: it locks the rows the usage belongs
+/// to, so concurrent additions cannot all pass. Rows brought back from the
+/// trash count as additions too; check with `additional` 0 after
+/// restoring them.
+pub async fn check_topic_quota<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    additional: u64,
+) -> AppResult<()> {
+    lock_quota_scope(db, user_id, workspace_id).await?;
+    let usage = get_workspace_usage(db, workspace_id).await?;
+    if exceeds(config.max_topics_per_workspace, usage.topics, additional) {
+        return Err(AppError::QuotaExceeded(format!(
+            "Workspace topic limit of {} reached",
+            config.max_topics_per_workspace
+        )));
+    }
+
+    let user_topics = count_user_topics(db, user_id).await?;
+    if exceeds(config.max_topics_per_user, user_topics, additional) {
+        return Err(AppError::QuotaExceeded(format!(
+            "User topic limit of {} reached",
+            config.max_topics_per_user
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn check_task_quota<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    additional: u64,
+) -> AppResult<()> {
+    lock_quota_scope(db, user_id, workspace_id).await?;
+    let usage = get_workspace_usage(db, workspace_id).await?;
+    if exceeds(config.max_tasks_per_workspace, usage.tasks, additional) {
+        return Err(AppError::QuotaExceeded(format!(
+            "Workspace task limit of {} reached",
+            config.max_tasks_per_workspace
+        )));
+    }
+
+    let user_tasks = count_user_tasks(db, user_id).await?;
+    if exceeds(config.max_tasks_per_user, user_tasks, additional) {
+        return Err(AppError::QuotaExceeded(format!(
+            "User task limit of {} reached",
+            config.max_tasks_per_user
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn check_substep_quota<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    task_id: Uuid,
+    additional: u64,
+) -> AppResult<()> {
+    Task::find_by_id(task_id).lock_exclusive().one(db).await?;
+    let substeps = TaskSubstep::find()
+        .filter(task_substep::Column::TaskId.eq(task_id))
+        .count(db)
+        .await?;
+
+    if exceeds(config.max_substeps_per_task, substeps, additional) {
+        return Err(AppError::QuotaExceeded(format!(
+            "Task substep limit of {} reached",
+            config.max_substeps_per_task
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn check_share_link_quota<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    additional: u64,
+) -> AppResult<()> {
+    lock_quota_scope(db, user_id, workspace_id).await?;
+    let usage = get_workspace_usage(db, workspace_id).await?;
+    if exceeds(
+        config.max_share_links_per_workspace,
+        usage.share_links,
+        additional,
+    ) {
+        return Err(AppError::QuotaExceeded(format!(
+            "Workspace share link limit of {} reached",
+            config.max_share_links_per_workspace
+        )));
+    }
+
+    let user_share_links = count_user_share_links(db, user_id).await?;
+    if exceeds(
+        config.max_share_links_per_user,
+        user_share_links,
+        additional,
+    ) {
+        return Err(AppError::QuotaExceeded(format!(
+            "User share link limit of {} reached",
+            config.max_share_links_per_user
+        )));
+    }
+
+    Ok(())
+}
@@ -1,10 +1,20 @@
 use crate::cache::{keys, Cache};
+use crate::config::Config;
+use crate::database::{
+    check_substep_quota, enqueue_webhook_event, field_changes, find_owned_task, issue_undo_token,
+    record_activity, NewActivity, UndoStep, UndoTokenResponse,
+};
 use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
 use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
     task::Entity as Task,
     task_substep::{self, Entity as TaskSubstep},
+    webhook::WebhookEvent,
     workspace,
 };
+use crate::rank::{rank_after, rank_between, rank_sequence, MAX_RANK_LEN};
+use sea_orm::sea_query::Expr;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -20,12 +30,20 @@ pub struct UpdateSubstepRequest {
     pub completed: Option<bool>,
 }
 
+/// Places a substep directly before or after one of its siblings.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveSubstepRequest {
+    pub before_id: Option<Uuid>,
+    pub after_id: Option<Uuid>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SubstepResponse {
     pub id: Uuid,
     pub task_id: Uuid,
     pub description: String,
     pub completed: bool,
+    pub rank: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -37,6 +55,7 @@ impl From<task_substep::Model> for SubstepResponse {
             task_id: substep.task_id,
             description: substep.description,
             completed: substep.completed,
+            rank: substep.rank,
             created_at: substep.created_at,
             updated_at: substep.updated_at,
         }
@@ -46,6 +65,7 @@ impl From<task_substep::Model> for SubstepResponse {
 pub async fn create_substep(
     db: &DatabaseConnection,
     cache: &Cache,
+    config: &Config,
     user_id: Uuid,
     task_id: Uuid,
     request: CreateSubstepRequest,
@@ -58,7 +78,7 @@ pub async fn create_substep(
         .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
 
     // Check if the task belongs to the user through topic ownership
-    let (_, topic) = task;
+    let (task, topic) = task;
     let topic = topic.ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
     let workspace = workspace::Entity::find_by_id(topic.workspace_id)
@@ -70,17 +90,56 @@ pub async fn create_substep(
         return Err(AppError::Forbidden("Access denied".to_string()));
     }
 
+    if task.deleted_at.is_some() || topic.deleted_at.is_some() || workspace.deleted_at.is_some() {
+        return Err(AppError::NotFound("Task not found".to_string()));
+    }
+
+    let txn = db.begin().await?;
+    check_substep_quota(&txn, config, task_id, 1).await?;
+
+    let last = TaskSubstep::find()
+        .filter(task_substep::Column::TaskId.eq(task_id))
+        .order_by_desc(task_substep::Column::Rank)
+        .one(&txn)
+        .await?;
+
     let substep = task_substep::ActiveModel {
         task_id: Set(task_id),
         description: Set(request.description),
         completed: Set(false),
+        rank: Set(rank_after(last.as_ref().map_or("", |s| s.rank.as_str()))),
         ..Default::default()
     };
 
-    let substep = substep.insert(db).await?;
+    let substep = substep.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Substep,
+            substep.id,
+            ActivityAction::Created,
+        )
+        .on_task(task_id)
+        .with_changes(field_changes(None, Some(&substep))),
+    )
+    .await?;
+    txn.commit().await?;
 
     // Invalidate cache for this task's substeps
     invalidate_substeps_cache(cache, user_id, task_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::substep(
+            ChangeAction::Created,
+            user_id,
+            workspace.id,
+            task_id,
+            substep.id,
+        ),
+    )
+    .await;
 
     Ok(SubstepResponse::from(substep))
 }
@@ -107,7 +166,7 @@ pub async fn get_substeps_by_task(
         .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
 
     // Check if the task belongs to the user through topic ownership
-    let (_, topic) = task;
+    let (task, topic) = task;
     let topic = topic.ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
     let workspace = workspace::Entity::find_by_id(topic.workspace_id)
@@ -119,8 +178,13 @@ pub async fn get_substeps_by_task(
         return Err(AppError::Forbidden("Access denied".to_string()));
     }
 
+    if task.deleted_at.is_some() || topic.deleted_at.is_some() || workspace.deleted_at.is_some() {
+        return Err(AppError::NotFound("Task not found".to_string()));
+    }
+
     let substeps = TaskSubstep::find()
         .filter(task_substep::Column::TaskId.eq(task_id))
+        .order_by_asc(task_substep::Column::Rank)
         .order_by_asc(task_substep::Column::CreatedAt)
         .all(db)
         .await?;
@@ -165,23 +229,29 @@ pub async fn get_substep_by_id(
         return Err(AppError::Forbidden("Access denied".to_string()));
     }
 
+    if task.deleted_at.is_some() || topic.deleted_at.is_some() || workspace.deleted_at.is_some() {
+        return Err(AppError::NotFound("Substep not found".to_string()));
+    }
+
     Ok(SubstepResponse::from(substep_model))
 }
 
 pub async fn update_substep(
     db: &DatabaseConnection,
+    cache: &Cache,
     user_id: Uuid,
     substep_id: Uuid,
     request: UpdateSubstepRequest,
 ) -> AppResult<SubstepResponse> {
     // First get the substep and verify ownership
-    let _existing_substep = get_substep_by_id(db, user_id, substep_id).await?;
+    let existing_substep = get_substep_by_id(db, user_id, substep_id).await?;
+    let (task, topic) = find_owned_task(db, user_id, existing_substep.task_id).await?;
 
-    let mut substep: task_substep::ActiveModel = TaskSubstep::find_by_id(substep_id)
+    let before = TaskSubstep::find_by_id(substep_id)
         .one(db)
         .await?
-        .ok_or_else(|| AppError::NotFound("Substep not found".to_string()))?
-        .into();
+        .ok_or_else(|| AppError::NotFound("Substep not found".to_string()))?;
+    let mut substep: task_substep::ActiveModel = before.clone().into();
 
     if let Some(description) = request.description {
         substep.description = Set(description);
@@ -191,24 +261,277 @@ pub async fn update_substep(
         substep.completed = Set(completed);
     }
 
-    let substep = substep.update(db).await?;
+    let txn = db.begin().await?;
+    let substep = substep.update(&txn).await?;
+
+    if substep.completed && !existing_substep.completed {
+        enqueue_webhook_event(
+            &txn,
+            topic.workspace_id,
+            WebhookEvent::SubstepCompleted,
+            serde_json::json!({ "task_id": task.id, "substep": &substep }),
+        )
+        .await?;
+    }
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Substep,
+            substep.id,
+            ActivityAction::Updated,
+        )
+        .on_task(task.id)
+        .with_changes(field_changes(Some(&before), Some(&substep))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    invalidate_substeps_cache(cache, user_id, task.id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::substep(
+            ChangeAction::Updated,
+            user_id,
+            topic.workspace_id,
+            task.id,
+            substep.id,
+        ),
+    )
+    .await;
+
     Ok(SubstepResponse::from(substep))
 }
 
+/// Deletes a substep. The returned token puts it back within the undo
+/// window.
 pub async fn delete_substep(
     db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
     user_id: Uuid,
     substep_id: Uuid,
-) -> AppResult<bool> {
+) -> AppResult<UndoTokenResponse> {
     // Verify ownership first
-    let _ = get_substep_by_id(db, user_id, substep_id).await?;
+    let existing = get_substep_by_id(db, user_id, substep_id).await?;
+    let (_, topic) = find_owned_task(db, user_id, existing.task_id).await?;
+    let substep = TaskSubstep::find_by_id(substep_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Substep not found".to_string()))?;
+
+    let txn = db.begin().await?;
+    let result = TaskSubstep::delete_by_id(substep_id).exec(&txn).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Substep not found".to_string()));
+    }
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Substep,
+            substep.id,
+            ActivityAction::Deleted,
+        )
+        .on_task(substep.task_id)
+        .with_changes(field_changes(Some(&substep), None)),
+    )
+    .await?;
+    let undo = issue_undo_token(
+        &txn,
+        config,
+        user_id,
+        topic.workspace_id,
+        vec![UndoStep::ReinsertSubsteps {
+            substeps: vec![substep],
+        }],
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_substeps_cache(cache, user_id, existing.task_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::substep(
+            ChangeAction::Deleted,
+            user_id,
+            topic.workspace_id,
+            existing.task_id,
+            substep_id,
+        ),
+    )
+    .await;
+
+    Ok(undo)
+}
+
+pub async fn move_substep(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    substep_id: Uuid,
+    request: MoveSubstepRequest,
+) -> AppResult<SubstepResponse> {
+    let existing = get_substep_by_id(db, user_id, substep_id).await?;
+    let (_, topic) = find_owned_task(db, user_id, existing.task_id).await?;
+
+    if request.before_id.is_none() && request.after_id.is_none() {
+        return Err(AppError::BadRequest(
+            "Provide before_id or after_id to move a substep".to_string(),
+        ));
+    }
+    if request.before_id == Some(substep_id) || request.after_id == Some(substep_id) {
+        return Err(AppError::BadRequest(
+            "A substep cannot be moved relative to itself".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+
+    let mut rank = substep_rank_between(
+        &txn,
+        existing.task_id,
+        substep_id,
+        request.after_id,
+        request.before_id,
+    )
+    .await?;
+    if rank.is_none() {
+        rebalance_substep_ranks(&txn, existing.task_id).await?;
+        rank = substep_rank_between(
+            &txn,
+            existing.task_id,
+            substep_id,
+            request.after_id,
+            request.before_id,
+        )
+        .await?;
+    }
+    let rank = rank
+        .ok_or_else(|| AppError::Internal("Could not compute a rank for the substep".to_string()))?;
+
+    let before = TaskSubstep::find_by_id(substep_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Substep not found".to_string()))?;
+    let mut substep: task_substep::ActiveModel = before.clone().into();
+    substep.rank = Set(rank);
+    substep.updated_at = Set(chrono::Utc::now());
+    let substep = substep.update(&txn).await?;
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Substep,
+            substep.id,
+            ActivityAction::Moved,
+        )
+        .on_task(existing.task_id)
+        .with_changes(field_changes(Some(&before), Some(&substep))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    invalidate_substeps_cache(cache, user_id, existing.task_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::substep(
+            ChangeAction::Updated,
+            user_id,
+            topic.workspace_id,
+            existing.task_id,
+            substep.id,
+        ),
+    )
+    .await;
+
+    Ok(SubstepResponse::from(substep))
+}
+
+/// Computes a rank between the anchors within the task, or `None` when the
+/// neighbours leave no room and the task's substeps need rebalancing.
+async fn substep_rank_between<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    substep_id: Uuid,
+    after_id: Option<Uuid>,
+    before_id: Option<Uuid>,
+) -> AppResult<Option<String>> {
+    let siblings = || {
+        TaskSubstep::find()
+            .filter(task_substep::Column::TaskId.eq(task_id))
+            .filter(task_substep::Column::Id.ne(substep_id))
+    };
+
+    let anchor = |id: Uuid| async move {
+        siblings()
+            .filter(task_substep::Column::Id.eq(id))
+            .one(db)
+            .await?
+            .map(|s| s.rank)
+            .ok_or_else(|| AppError::BadRequest("Sibling substep not found in task".to_string()))
+    };
+
+    let (lower, upper) = match (after_id, before_id) {
+        (Some(after_id), Some(before_id)) => {
+            (Some(anchor(after_id).await?), Some(anchor(before_id).await?))
+        }
+        (Some(after_id), None) => {
+            let lower = anchor(after_id).await?;
+            let upper = siblings()
+                .filter(task_substep::Column::Rank.gt(lower.clone()))
+                .order_by_asc(task_substep::Column::Rank)
+                .one(db)
+                .await?
+                .map(|s| s.rank);
+            (Some(lower), upper)
+        }
+        (None, Some(before_id)) => {
+            let upper = anchor(before_id).await?;
+            let lower = siblings()
+                .filter(task_substep::Column::Rank.lt(upper.clone()))
+                .order_by_desc(task_substep::Column::Rank)
+                .one(db)
+                .await?
+                .map(|s| s.rank);
+            (lower, Some(upper))
+        }
+        (None, None) => return Ok(None),
+    };
+
+    Ok(rank_between(lower.as_deref(), upper.as_deref()).filter(|rank| rank.len() <= MAX_RANK_LEN))
+}
+
+async fn rebalance_substep_ranks<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<()> {
+    let substeps = TaskSubstep::find()
+        .filter(task_substep::Column::TaskId.eq(task_id))
+        .order_by_asc(task_substep::Column::Rank)
+        .order_by_asc(task_substep::Column::CreatedAt)
+        .order_by_asc(task_substep::Column::Id)
+        .all(db)
+        .await?;
+
+    let ranks = rank_sequence(substeps.len());
+    for (substep, rank) in substeps.into_iter().zip(ranks) {
+        TaskSubstep::update_many()
+            .col_expr(task_substep::Column::Rank, Expr::value(rank))
+            .filter(task_substep::Column::Id.eq(substep.id))
+            .exec(db)
+            .await?;
+    }
 
-    let result = TaskSubstep::delete_by_id(substep_id).exec(db).await?;
-    Ok(result.rows_affected > 0)
+    Ok(())
 }
 
 // Cache helper functions
-async fn invalidate_substeps_cache(cache: &Cache, user_id: Uuid, task_id: Uuid) {
+pub(crate) async fn invalidate_substeps_cache(cache: &Cache, user_id: Uuid, task_id: Uuid) {
     let cache_key = cache.generate_cache_key(keys::SUBSTEPS, &user_id, Some(&task_id.to_string()));
     if let Err(e) = cache.delete(&cache_key).await {
         eprintln!("Failed to invalidate substeps cache: {}", e);
@@ -0,0 +1,366 @@
+use crate::cache::Cache;
+use crate::database::{
+    attach_task_details, field_change, find_owned_task, get_task_by_id, invalidate_tasks_cache,
+    notify, record_activity, NewActivity, NewNotification, TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    notification::NotificationKind,
+    task::{self, Entity as Task},
+    task_assignee::{self, Entity as TaskAssignee},
+    topic::{self, Entity as Topic},
+    workspace::{self, Entity as Workspace},
+};
+use sea_orm::sea_query::{Query, SimpleExpr};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTaskAssigneesRequest {
+    pub user_ids: Vec<Uuid>,
+}
+
+/// The value of an `assignee=` task filter.
+#[derive(Debug, Clone, Copy)]
+pub enum AssigneeFilter {
+    /// Tasks assigned to the requesting user.
+    Me,
+    User(Uuid),
+    /// Tasks without any assignee.
+    Unassigned,
+}
+
+impl std::str::FromStr for AssigneeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "me" => Ok(AssigneeFilter::Me),
+            "none" => Ok(AssigneeFilter::Unassigned),
+            other => Uuid::parse_str(other)
+                .map(AssigneeFilter::User)
+                .map_err(|_| format!("Invalid assignee: {}", s)),
+        }
+    }
+}
+
+/// Users that can be assigned tasks of a workspace. Workspaces are only
+/// accessible to their owner, so that is the one member for now.
+pub async fn workspace_member_ids<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<HashSet<Uuid>> {
+    let workspace = Workspace::find_by_id(workspace_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+
+    Ok(HashSet::from([workspace.owner_id]))
+}
+
+/// Makes sure every ID names a member of the given workspace.
+pub async fn check_workspace_assignees<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    user_ids: &[Uuid],
+) -> AppResult<()> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let members = workspace_member_ids(db, workspace_id).await?;
+    if let Some(user_id) = user_ids.iter().find(|id| !members.contains(id)) {
+        return Err(AppError::BadRequest(format!(
+            "User {} is not a member of the task's workspace",
+            user_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Replaces the assignees of a task and returns the users that were not
+/// assigned before. The IDs must already have been checked with
+/// `check_workspace_assignees`.
+pub async fn replace_task_assignees<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    user_ids: &[Uuid],
+) -> AppResult<Vec<Uuid>> {
+    let previous: HashSet<Uuid> = TaskAssignee::find()
+        .filter(task_assignee::Column::TaskId.eq(task_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.user_id)
+        .collect();
+
+    TaskAssignee::delete_many()
+        .filter(task_assignee::Column::TaskId.eq(task_id))
+        .exec(db)
+        .await?;
+
+    let mut seen = HashSet::new();
+    let now = chrono::Utc::now();
+    let rows: Vec<task_assignee::ActiveModel> = user_ids
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .map(|user_id| task_assignee::ActiveModel {
+            task_id: Set(task_id),
+            user_id: Set(*user_id),
+            created_at: Set(now),
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        TaskAssignee::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(seen
+        .into_iter()
+        .filter(|id| !previous.contains(id))
+        .collect())
+}
+
+async fn task_assignee_ids<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let rows = TaskAssignee::find()
+        .filter(task_assignee::Column::TaskId.eq(task_id))
+        .order_by_asc(task_assignee::Column::UserId)
+        .all(db)
+        .await?;
+    Ok(rows.into_iter().map(|row| row.user_id).collect())
+}
+
+/// Logs a change of the task's assignees from `before` to who it has now.
+async fn record_task_assignees_change<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    task_id: Uuid,
+    before: Vec<Uuid>,
+) -> AppResult<()> {
+    let after = task_assignee_ids(db, task_id).await?;
+    if before == after {
+        return Ok(());
+    }
+
+    record_activity(
+        db,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task_id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_change("assignee_ids", before, after)),
+    )
+    .await
+}
+
+/// Lets newly assigned users know about a task.
+pub async fn notify_task_assignees<C: ConnectionTrait>(
+    db: &C,
+    actor_id: Uuid,
+    task: &task::Model,
+    workspace_id: Uuid,
+    assignee_ids: &[Uuid],
+) -> AppResult<()> {
+    notify(
+        db,
+        assignee_ids.iter().map(|assignee_id| NewNotification {
+            user_id: *assignee_id,
+            kind: NotificationKind::Assignment,
+            title: format!("You were assigned to {}", task.title),
+            body: None,
+            actor_id: Some(actor_id),
+            workspace_id: Some(workspace_id),
+            task_id: Some(task.id),
+        }),
+    )
+    .await
+}
+
+/// Fills `assignee_ids` on the given responses with a single query.
+pub async fn attach_task_assignees<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let rows = TaskAssignee::find()
+        .filter(task_assignee::Column::TaskId.is_in(ids))
+        .order_by_asc(task_assignee::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut assignees: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for row in rows {
+        assignees.entry(row.task_id).or_default().push(row.user_id);
+    }
+
+    for task in tasks.iter_mut() {
+        task.assignee_ids = assignees.get(&task.id).cloned().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Builds the condition for an `assignee=` task filter on behalf of
+/// `user_id`.
+pub fn task_assignee_filter(filter: AssigneeFilter, user_id: Uuid) -> SimpleExpr {
+    let mut subquery = Query::select();
+    subquery
+        .column(task_assignee::Column::TaskId)
+        .from(TaskAssignee);
+
+    match filter {
+        AssigneeFilter::Me => {
+            subquery.and_where(task_assignee::Column::UserId.eq(user_id));
+            task::Column::Id.in_subquery(subquery.to_owned())
+        }
+        AssigneeFilter::User(assignee_id) => {
+            subquery.and_where(task_assignee::Column::UserId.eq(assignee_id));
+            task::Column::Id.in_subquery(subquery.to_owned())
+        }
+        AssigneeFilter::Unassigned => task::Column::Id.not_in_subquery(subquery.to_owned()),
+    }
+}
+
+pub async fn set_task_assignees(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: SetTaskAssigneesRequest,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    check_workspace_assignees(db, topic.workspace_id, &request.user_ids).await?;
+
+    let txn = db.begin().await?;
+    let before = task_assignee_ids(&txn, task.id).await?;
+    let added = replace_task_assignees(&txn, task.id, &request.user_ids).await?;
+    notify_task_assignees(&txn, user_id, &task, topic.workspace_id, &added).await?;
+    record_task_assignees_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    get_task_by_id(db, user_id, task.id).await
+}
+
+pub async fn add_task_assignee(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    assignee_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    check_workspace_assignees(db, topic.workspace_id, &[assignee_id]).await?;
+
+    let existing = TaskAssignee::find_by_id((task.id, assignee_id))
+        .one(db)
+        .await?;
+    if existing.is_none() {
+        let task_assignee = task_assignee::ActiveModel {
+            task_id: Set(task.id),
+            user_id: Set(assignee_id),
+            ..Default::default()
+        };
+        let txn = db.begin().await?;
+        let before = task_assignee_ids(&txn, task.id).await?;
+        task_assignee.insert(&txn).await?;
+        notify_task_assignees(&txn, user_id, &task, topic.workspace_id, &[assignee_id]).await?;
+        record_task_assignees_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+        txn.commit().await?;
+
+        invalidate_tasks_cache(cache, user_id).await;
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+        )
+        .await;
+    }
+
+    get_task_by_id(db, user_id, task.id).await
+}
+
+pub async fn remove_task_assignee(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    assignee_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+
+    let txn = db.begin().await?;
+    let before = task_assignee_ids(&txn, task.id).await?;
+    let result = TaskAssignee::delete_by_id((task.id, assignee_id))
+        .exec(&txn)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(
+            "User is not assigned to this task".to_string(),
+        ));
+    }
+    record_task_assignees_change(&txn, topic.workspace_id, user_id, task.id, before).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    get_task_by_id(db, user_id, task.id).await
+}
+
+/// Tasks assigned to the user across every workspace they can access,
+/// most pressing first. Archived workspaces and topics are left out, like
+/// in the per-workspace task lists.
+pub async fn get_assigned_tasks(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    completed: Option<bool>,
+) -> AppResult<Vec<TaskResponse>> {
+    let mut query = Task::find()
+        .inner_join(Topic)
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::ArchivedAt.is_null())
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::ArchivedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .filter(task_assignee_filter(AssigneeFilter::Me, user_id));
+
+    if let Some(completed) = completed {
+        query = query.filter(task::Column::Completed.eq(completed));
+    }
+
+    let tasks = query
+        .order_by_desc(task::Column::Priority)
+        .order_by_asc(task::Column::DueDate)
+        .order_by_asc(task::Column::Rank)
+        .all(db)
+        .await?;
+
+    let mut responses: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
+    attach_task_details(db, &mut responses).await?;
+    Ok(responses)
+}
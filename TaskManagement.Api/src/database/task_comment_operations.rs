@@ -0,0 +1,403 @@
+use crate::database::{
+    enqueue_webhook_event, field_changes, find_owned_task, notify, record_activity,
+    workspace_member_ids, NewActivity, NewNotification,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    notification::NotificationKind,
+    task,
+    task_comment::{self, Entity as TaskComment},
+    task_comment_mention::{self, Entity as TaskCommentMention},
+    user::{self, Entity as User},
+    webhook::WebhookEvent,
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCommentRequest {
+    /// Markdown; `@username` mentions of workspace members are recorded.
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCommentRequest {
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub author_id: Uuid,
+    pub body: String,
+    pub mentioned_user_ids: Vec<Uuid>,
+    pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<task_comment::Model> for CommentResponse {
+    fn from(comment: task_comment::Model) -> Self {
+        Self {
+            id: comment.id,
+            task_id: comment.task_id,
+            author_id: comment.author_id,
+            body: comment.body,
+            mentioned_user_ids: Vec::new(),
+            edited_at: comment.edited_at,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+        }
+    }
+}
+
+fn validate_comment_body(body: &str) -> AppResult<()> {
+    if body.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Comment body cannot be empty".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Usernames mentioned as `@username` in a markdown body. Mentions inside
+/// code spans and fenced code blocks, and `@` signs within words such as
+/// email addresses, are ignored.
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let mut mentions = Vec::new();
+    let mut in_code = false;
+    let mut previous: Option<char> = None;
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c == '`' {
+            // A run of backticks opens or closes a span (or a fence)
+            while chars.next_if(|(_, next)| *next == '`').is_some() {}
+            in_code = !in_code;
+            previous = Some(c);
+            continue;
+        }
+
+        let starts_mention = c == '@' && !previous.is_some_and(is_username_char);
+        previous = Some(c);
+        if in_code || !starts_mention {
+            continue;
+        }
+
+        let rest = &body[index + 1..];
+        let end = rest
+            .find(|c: char| !is_username_char(c))
+            .unwrap_or(rest.len());
+        // A trailing full stop ends the sentence, not the username
+        let username = rest[..end].trim_end_matches('.');
+        if !username.is_empty() && !mentions.iter().any(|m| m == username) {
+            mentions.push(username.to_string());
+        }
+    }
+
+    mentions
+}
+
+/// Resolves the mentions of a body to the IDs of users who are members of
+/// the workspace. Unknown usernames and non-members are dropped.
+async fn resolve_mentions<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    body: &str,
+) -> AppResult<Vec<Uuid>> {
+    let usernames = parse_mentions(body);
+    if usernames.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let members = workspace_member_ids(db, workspace_id).await?;
+    let users = User::find()
+        .filter(user::Column::Username.is_in(usernames))
+        .all(db)
+        .await?;
+
+    Ok(users
+        .into_iter()
+        .map(|user| user.id)
+        .filter(|id| members.contains(id))
+        .collect())
+}
+
+/// Replaces the mentions of a comment and returns the users that were not
+/// mentioned before.
+async fn replace_comment_mentions<C: ConnectionTrait>(
+    db: &C,
+    comment_id: Uuid,
+    user_ids: &[Uuid],
+) -> AppResult<Vec<Uuid>> {
+    let previous: HashSet<Uuid> = TaskCommentMention::find()
+        .filter(task_comment_mention::Column::CommentId.eq(comment_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.user_id)
+        .collect();
+
+    TaskCommentMention::delete_many()
+        .filter(task_comment_mention::Column::CommentId.eq(comment_id))
+        .exec(db)
+        .await?;
+
+    let now = chrono::Utc::now();
+    let rows: Vec<task_comment_mention::ActiveModel> = user_ids
+        .iter()
+        .map(|user_id| task_comment_mention::ActiveModel {
+            comment_id: Set(comment_id),
+            user_id: Set(*user_id),
+            created_at: Set(now),
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        TaskCommentMention::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(user_ids
+        .iter()
+        .copied()
+        .filter(|id| !previous.contains(id))
+        .collect())
+}
+
+async fn notify_comment_mentions<C: ConnectionTrait>(
+    db: &C,
+    comment: &task_comment::Model,
+    task: &task::Model,
+    workspace_id: Uuid,
+    user_ids: &[Uuid],
+) -> AppResult<()> {
+    notify(
+        db,
+        user_ids.iter().map(|user_id| NewNotification {
+            user_id: *user_id,
+            kind: NotificationKind::Mention,
+            title: format!("You were mentioned on {}", task.title),
+            body: Some(comment.body.clone()),
+            actor_id: Some(comment.author_id),
+            workspace_id: Some(workspace_id),
+            task_id: Some(task.id),
+        }),
+    )
+    .await
+}
+
+async fn attach_comment_mentions<C: ConnectionTrait>(
+    db: &C,
+    comments: &mut [CommentResponse],
+) -> AppResult<()> {
+    if comments.is_empty() {
+        return Ok(());
+    }
+
+    let ids: HashSet<Uuid> = comments.iter().map(|c| c.id).collect();
+    let rows = TaskCommentMention::find()
+        .filter(task_comment_mention::Column::CommentId.is_in(ids))
+        .all(db)
+        .await?;
+
+    let mut mentions: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for row in rows {
+        mentions
+            .entry(row.comment_id)
+            .or_default()
+            .push(row.user_id);
+    }
+
+    for comment in comments.iter_mut() {
+        comment.mentioned_user_ids = mentions.remove(&comment.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Loads a comment on a live task of one of the user's workspaces, together
+/// with its task and the workspace it belongs to.
+async fn find_accessible_comment<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    comment_id: Uuid,
+) -> AppResult<(task_comment::Model, task::Model, Uuid)> {
+    let comment = TaskComment::find_by_id(comment_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Comment not found".to_string()))?;
+
+    let (task, topic) = find_owned_task(db, user_id, comment.task_id)
+        .await
+        .map_err(|_| AppError::NotFound("Comment not found".to_string()))?;
+
+    Ok((comment, task, topic.workspace_id))
+}
+
+async fn comment_response<C: ConnectionTrait>(
+    db: &C,
+    comment: task_comment::Model,
+) -> AppResult<CommentResponse> {
+    let mut response = CommentResponse::from(comment);
+    attach_comment_mentions(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
+pub async fn get_comments_by_task(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<Vec<CommentResponse>> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+
+    let comments = TaskComment::find()
+        .filter(task_comment::Column::TaskId.eq(task.id))
+        .order_by_asc(task_comment::Column::CreatedAt)
+        .order_by_asc(task_comment::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut responses: Vec<CommentResponse> =
+        comments.into_iter().map(CommentResponse::from).collect();
+    attach_comment_mentions(db, &mut responses).await?;
+    Ok(responses)
+}
+
+pub async fn create_comment(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: CreateCommentRequest,
+) -> AppResult<CommentResponse> {
+    validate_comment_body(&request.body)?;
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    let mentions = resolve_mentions(db, topic.workspace_id, &request.body).await?;
+
+    let txn = db.begin().await?;
+
+    let comment = task_comment::ActiveModel {
+        task_id: Set(task.id),
+        author_id: Set(user_id),
+        body: Set(request.body),
+        edited_at: Set(None),
+        ..Default::default()
+    };
+    let comment = comment.insert(&txn).await?;
+    let mentioned = replace_comment_mentions(&txn, comment.id, &mentions).await?;
+    notify_comment_mentions(&txn, &comment, &task, topic.workspace_id, &mentioned).await?;
+    enqueue_webhook_event(
+        &txn,
+        topic.workspace_id,
+        WebhookEvent::CommentCreated,
+        serde_json::json!({ "comment": &comment }),
+    )
+    .await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Comment,
+            comment.id,
+            ActivityAction::Created,
+        )
+        .on_task(comment.task_id)
+        .with_changes(field_changes(None, Some(&comment))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    comment_response(db, comment).await
+}
+
+/// Edits a comment. Only its author may do so; the edit is recorded in
+/// `edited_at` and the mentions are re-resolved from the new body.
+pub async fn update_comment(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    comment_id: Uuid,
+    request: UpdateCommentRequest,
+) -> AppResult<CommentResponse> {
+    validate_comment_body(&request.body)?;
+    let (comment, task, workspace_id) = find_accessible_comment(db, user_id, comment_id).await?;
+
+    if comment.author_id != user_id {
+        return Err(AppError::Forbidden(
+            "Only the author can edit a comment".to_string(),
+        ));
+    }
+
+    let mentions = resolve_mentions(db, workspace_id, &request.body).await?;
+
+    let txn = db.begin().await?;
+
+    let now = chrono::Utc::now();
+    let before = comment.clone();
+    let mut comment: task_comment::ActiveModel = comment.into();
+    comment.body = Set(request.body);
+    comment.edited_at = Set(Some(now));
+    comment.updated_at = Set(now);
+    let comment = comment.update(&txn).await?;
+    let mentioned = replace_comment_mentions(&txn, comment.id, &mentions).await?;
+    notify_comment_mentions(&txn, &comment, &task, workspace_id, &mentioned).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Comment,
+            comment.id,
+            ActivityAction::Updated,
+        )
+        .on_task(comment.task_id)
+        .with_changes(field_changes(Some(&before), Some(&comment))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    comment_response(db, comment).await
+}
+
+pub async fn delete_comment(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    comment_id: Uuid,
+) -> AppResult<bool> {
+    let (comment, _, workspace_id) = find_accessible_comment(db, user_id, comment_id).await?;
+
+    if comment.author_id != user_id {
+        return Err(AppError::Forbidden(
+            "Only the author can delete a comment".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+    TaskComment::delete_by_id(comment.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Comment,
+            comment.id,
+            ActivityAction::Deleted,
+        )
+        .on_task(comment.task_id)
+        .with_changes(field_changes(Some(&comment), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(true)
+}
@@ -0,0 +1,468 @@
+use crate::cache::Cache;
+use crate::database::{
+    field_changes, find_owned_task, invalidate_tasks_cache, record_activity, NewActivity,
+    TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    task::{self, Entity as Task},
+    task_dependency::{self, Entity as TaskDependency, TaskDependencyType},
+    task_status::{Entity as TaskStatus, TaskStatusCategory},
+    topic,
+    workspace::Entity as Workspace,
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTaskDependencyRequest {
+    pub blocking_task_id: Uuid,
+    pub dependency_type: Option<TaskDependencyType>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDependencyResponse {
+    pub id: Uuid,
+    pub blocking_task_id: Uuid,
+    pub blocked_task_id: Uuid,
+    pub dependency_type: TaskDependencyType,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<task_dependency::Model> for TaskDependencyResponse {
+    fn from(dependency: task_dependency::Model) -> Self {
+        Self {
+            id: dependency.id,
+            blocking_task_id: dependency.blocking_task_id,
+            blocked_task_id: dependency.blocked_task_id,
+            dependency_type: dependency.dependency_type,
+            created_at: dependency.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDependenciesResponse {
+    pub blocked_by: Vec<TaskDependencyResponse>,
+    pub blocking: Vec<TaskDependencyResponse>,
+}
+
+/// Whether adding `blocking -> blocked` would close a loop, i.e. `blocked`
+/// already (transitively) blocks `blocking`.
+async fn would_create_cycle<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    blocking_task_id: Uuid,
+    blocked_task_id: Uuid,
+) -> AppResult<bool> {
+    let edges = TaskDependency::find()
+        .join(
+            JoinType::InnerJoin,
+            task_dependency::Relation::BlockingTask.def(),
+        )
+        .join(JoinType::InnerJoin, task::Relation::Topic.def())
+        .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .all(db)
+        .await?;
+
+    let mut successors: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for edge in edges {
+        successors
+            .entry(edge.blocking_task_id)
+            .or_default()
+            .push(edge.blocked_task_id);
+    }
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([blocked_task_id]);
+    while let Some(current) = queue.pop_front() {
+        if current == blocking_task_id {
+            return Ok(true);
+        }
+        if !visited.insert(current) {
+            continue;
+        }
+        if let Some(next) = successors.get(&current) {
+            queue.extend(next.iter().copied());
+        }
+    }
+
+    Ok(false)
+}
+
+/// Checks a whole set of `(blocking, blocked)` edges for cycles by repeatedly
+/// removing tasks that nothing blocks any more.
+pub fn dependency_graph_has_cycle(edges: impl IntoIterator<Item = (Uuid, Uuid)>) -> bool {
+    let mut successors: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut in_degree: HashMap<Uuid, usize> = HashMap::new();
+    for (blocking, blocked) in edges {
+        successors.entry(blocking).or_default().push(blocked);
+        in_degree.entry(blocking).or_default();
+        *in_degree.entry(blocked).or_default() += 1;
+    }
+
+    let mut ready: VecDeque<Uuid> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut removed = 0;
+    while let Some(current) = ready.pop_front() {
+        removed += 1;
+        for next in successors.get(&current).into_iter().flatten() {
+            let degree = in_degree.entry(*next).or_default();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(*next);
+            }
+        }
+    }
+
+    removed < in_degree.len()
+}
+
+pub async fn list_task_dependencies(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<TaskDependenciesResponse> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+
+    let dependencies = TaskDependency::find()
+        .filter(
+            Condition::any()
+                .add(task_dependency::Column::BlockedTaskId.eq(task.id))
+                .add(task_dependency::Column::BlockingTaskId.eq(task.id)),
+        )
+        .order_by_asc(task_dependency::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let (blocked_by, blocking): (Vec<_>, Vec<_>) = dependencies
+        .into_iter()
+        .partition(|dependency| dependency.blocked_task_id == task.id);
+
+    Ok(TaskDependenciesResponse {
+        blocked_by: blocked_by.into_iter().map(TaskDependencyResponse::from).collect(),
+        blocking: blocking.into_iter().map(TaskDependencyResponse::from).collect(),
+    })
+}
+
+/// Records that `task_id` is blocked by `request.blocking_task_id`.
+pub async fn create_task_dependency(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: CreateTaskDependencyRequest,
+) -> AppResult<TaskDependencyResponse> {
+    if request.blocking_task_id == task_id {
+        return Err(AppError::BadRequest(
+            "A task cannot depend on itself".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+
+    let (blocked, topic) = find_owned_task(&txn, user_id, task_id).await?;
+    let workspace_id = topic.workspace_id;
+    let (blocking, blocking_topic) =
+        find_owned_task(&txn, user_id, request.blocking_task_id).await?;
+
+    if blocking_topic.workspace_id != workspace_id {
+        return Err(AppError::BadRequest(
+            "Dependencies must stay within one workspace".to_string(),
+        ));
+    }
+
+    // Serialise dependency edits per workspace so two concurrent inserts
+    // cannot close a cycle between them.
+    Workspace::find_by_id(workspace_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?;
+
+    let existing = TaskDependency::find()
+        .filter(task_dependency::Column::BlockingTaskId.eq(blocking.id))
+        .filter(task_dependency::Column::BlockedTaskId.eq(blocked.id))
+        .one(&txn)
+        .await?;
+
+    if existing.is_some() {
+        return Err(AppError::Conflict("Dependency already exists".to_string()));
+    }
+
+    if would_create_cycle(&txn, workspace_id, blocking.id, blocked.id).await? {
+        return Err(AppError::Conflict(
+            "Dependency would create a cycle".to_string(),
+        ));
+    }
+
+    let dependency = task_dependency::ActiveModel {
+        blocking_task_id: Set(blocking.id),
+        blocked_task_id: Set(blocked.id),
+        dependency_type: Set(request
+            .dependency_type
+            .unwrap_or(TaskDependencyType::FinishToStart)),
+        ..Default::default()
+    };
+
+    let dependency = dependency.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Dependency,
+            dependency.id,
+            ActivityAction::Created,
+        )
+        .on_task(blocked.id)
+        .with_changes(field_changes(None, Some(&dependency))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(TaskDependencyResponse::from(dependency))
+}
+
+pub async fn delete_task_dependency(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    dependency_id: Uuid,
+) -> AppResult<bool> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    let workspace_id = topic.workspace_id;
+
+    let dependency = TaskDependency::find_by_id(dependency_id)
+        .filter(
+            Condition::any()
+                .add(task_dependency::Column::BlockedTaskId.eq(task.id))
+                .add(task_dependency::Column::BlockingTaskId.eq(task.id)),
+        )
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dependency not found".to_string()))?;
+
+    let txn = db.begin().await?;
+    let result = TaskDependency::delete_by_id(dependency.id)
+        .exec(&txn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Dependency not found".to_string()));
+    }
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Dependency,
+            dependency.id,
+            ActivityAction::Deleted,
+        )
+        .on_task(dependency.blocked_task_id)
+        .with_changes(field_changes(Some(&dependency), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(true)
+}
+
+/// Fills `blocked_by` and `blocking` on the given responses. Dependencies on
+/// trashed tasks are left out.
+pub async fn attach_task_dependencies<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<Uuid> = tasks
+        .iter()
+        .map(|t| t.id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let dependencies = TaskDependency::find()
+        .filter(
+            Condition::any()
+                .add(task_dependency::Column::BlockedTaskId.is_in(ids.clone()))
+                .add(task_dependency::Column::BlockingTaskId.is_in(ids)),
+        )
+        .order_by_asc(task_dependency::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+
+    let related_ids: HashSet<Uuid> = dependencies
+        .iter()
+        .flat_map(|d| [d.blocking_task_id, d.blocked_task_id])
+        .collect();
+    let live: HashSet<Uuid> = Task::find()
+        .filter(task::Column::Id.is_in(related_ids))
+        .filter(task::Column::DeletedAt.is_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+
+    let mut blocked_by: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut blocking: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for dependency in dependencies {
+        if !live.contains(&dependency.blocking_task_id) || !live.contains(&dependency.blocked_task_id)
+        {
+            continue;
+        }
+        blocked_by
+            .entry(dependency.blocked_task_id)
+            .or_default()
+            .push(dependency.blocking_task_id);
+        blocking
+            .entry(dependency.blocking_task_id)
+            .or_default()
+            .push(dependency.blocked_task_id);
+    }
+
+    for task in tasks.iter_mut() {
+        task.blocked_by = blocked_by.get(&task.id).cloned().unwrap_or_default();
+        task.blocking = blocking.get(&task.id).cloned().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Refuses to complete a task while any of its blockers is unfinished. For
+/// start-to-start and start-to-finish dependencies the blocker only has to
+/// have started.
+pub async fn check_task_blockers<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<()> {
+    let dependencies = TaskDependency::find()
+        .filter(task_dependency::Column::BlockedTaskId.eq(task_id))
+        .all(db)
+        .await?;
+
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+
+    let blockers: HashMap<Uuid, (task::Model, Option<TaskStatusCategory>)> = Task::find()
+        .filter(
+            task::Column::Id.is_in(
+                dependencies
+                    .iter()
+                    .map(|d| d.blocking_task_id)
+                    .collect::<Vec<_>>(),
+            ),
+        )
+        .filter(task::Column::DeletedAt.is_null())
+        .find_also_related(TaskStatus)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(task, status)| (task.id, (task, status.map(|s| s.category))))
+        .collect();
+
+    let unfinished = dependencies
+        .iter()
+        .filter(|dependency| {
+            let Some((blocker, category)) = blockers.get(&dependency.blocking_task_id) else {
+                return false;
+            };
+            if blocker.completed {
+                return false;
+            }
+            dependency.dependency_type.requires_blocker_done()
+                || !matches!(category, Some(TaskStatusCategory::Doing))
+        })
+        .count();
+
+    if unfinished > 0 {
+        return Err(AppError::Conflict(format!(
+            "Task is blocked by {} unfinished task(s); set force to complete it anyway",
+            unfinished
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: usize) -> Vec<Uuid> {
+        (0..count).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn empty_graph_has_no_cycle() {
+        assert!(!dependency_graph_has_cycle(Vec::new()));
+    }
+
+    #[test]
+    fn acyclic_chain() {
+        let t = ids(4);
+        assert!(!dependency_graph_has_cycle([
+            (t[0], t[1]),
+            (t[1], t[2]),
+            (t[2], t[3]),
+        ]));
+    }
+
+    #[test]
+    fn two_cycle() {
+        let t = ids(2);
+        assert!(dependency_graph_has_cycle([(t[0], t[1]), (t[1], t[0])]));
+    }
+
+    #[test]
+    fn longer_cycle() {
+        let t = ids(5);
+        assert!(dependency_graph_has_cycle([
+            (t[0], t[1]),
+            (t[1], t[2]),
+            (t[2], t[3]),
+            (t[3], t[4]),
+            (t[4], t[1]),
+        ]));
+    }
+
+    #[test]
+    fn diamond_is_acyclic() {
+        let t = ids(4);
+        assert!(!dependency_graph_has_cycle([
+            (t[0], t[1]),
+            (t[0], t[2]),
+            (t[1], t[3]),
+            (t[2], t[3]),
+        ]));
+    }
+
+    #[test]
+    fn cycle_beside_an_acyclic_part() {
+        let t = ids(5);
+        assert!(dependency_graph_has_cycle([
+            (t[0], t[1]),
+            (t[2], t[3]),
+            (t[3], t[4]),
+            (t[4], t[2]),
+        ]));
+    }
+}
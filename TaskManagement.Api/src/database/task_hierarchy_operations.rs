@@ -0,0 +1,406 @@
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::database::{
+    attach_task_details, check_task_quota, field_changes, first_task_status_in_category,
+    get_substep_by_id, invalidate_substeps_cache, invalidate_tasks_cache, last_task_rank,
+    record_activity, NewActivity, TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    task::{self, Entity as Task, TaskPriority},
+    task_status::TaskStatusCategory,
+    task_substep::Entity as TaskSubstep,
+    topic::{self, Entity as Topic},
+    workspace,
+};
+use crate::rank::rank_after;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Upper bound on nesting, so a corrupted parent chain cannot loop forever.
+pub const MAX_TASK_DEPTH: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTaskParentRequest {
+    /// `null` moves the task back to the top level.
+    pub parent_task_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub total: u64,
+    pub completed: u64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskTreeNode {
+    pub task: TaskResponse,
+    /// Completion of every live task below this one.
+    pub progress: TaskProgress,
+    pub children: Vec<TaskTreeNode>,
+}
+
+pub(crate) async fn find_owned_task<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<(task::Model, topic::Model)> {
+    let (task, topic) = Task::find_by_id(task_id)
+        .find_also_related(Topic)
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+
+    let topic = topic.ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+    Ok((task, topic))
+}
+
+/// Loads every descendant of the given task, level by level. With
+/// `live_only` trashed tasks (and everything below them) are skipped.
+pub async fn find_task_descendants<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    live_only: bool,
+) -> AppResult<Vec<task::Model>> {
+    let mut descendants = Vec::new();
+    let mut seen = HashSet::from([task_id]);
+    let mut frontier = vec![task_id];
+
+    for _ in 0..MAX_TASK_DEPTH {
+        if frontier.is_empty() {
+            break;
+        }
+
+        let mut query = Task::find().filter(task::Column::ParentTaskId.is_in(frontier));
+        if live_only {
+            query = query.filter(task::Column::DeletedAt.is_null());
+        }
+        let children = query
+            .order_by_asc(task::Column::Rank)
+            .order_by_asc(task::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        frontier = children
+            .iter()
+            .filter(|child| seen.insert(child.id))
+            .map(|child| child.id)
+            .collect();
+        descendants.extend(children);
+    }
+
+    Ok(descendants)
+}
+
+/// Ensures `parent_task_id` can take `task_id` as a child: same workspace,
+/// not the task itself or one of its descendants, and within the depth cap
+/// together with the task's own subtasks.
+pub async fn check_task_parent<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    task_id: Option<Uuid>,
+    parent_task_id: Uuid,
+) -> AppResult<task::Model> {
+    let (parent, parent_topic) = Task::find_by_id(parent_task_id)
+        .find_also_related(Topic)
+        .filter(task::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Parent task not found".to_string()))?;
+
+    if parent_topic.map(|t| t.workspace_id) != Some(workspace_id) {
+        return Err(AppError::BadRequest(
+            "Parent task must be in the same workspace".to_string(),
+        ));
+    }
+
+    if Some(parent.id) == task_id {
+        return Err(AppError::BadRequest("A task cannot be its own parent".to_string()));
+    }
+
+    // Walk up from the new parent; meeting the task itself means a loop.
+    let mut depth = 1;
+    let mut current = parent.parent_task_id;
+    while let Some(ancestor_id) = current {
+        if Some(ancestor_id) == task_id {
+            return Err(AppError::BadRequest(
+                "A task cannot be moved under one of its own subtasks".to_string(),
+            ));
+        }
+        depth += 1;
+        if depth >= MAX_TASK_DEPTH {
+            return Err(AppError::BadRequest(format!(
+                "Subtasks cannot be nested more than {} levels deep",
+                MAX_TASK_DEPTH
+            )));
+        }
+        current = Task::find_by_id(ancestor_id)
+            .one(db)
+            .await?
+            .and_then(|ancestor| ancestor.parent_task_id);
+    }
+
+    // A moved task brings its whole subtree down with it
+    let levels = match task_id {
+        Some(task_id) => subtree_levels(db, task_id).await?,
+        None => 1,
+    };
+    if depth + levels > MAX_TASK_DEPTH {
+        return Err(AppError::BadRequest(format!(
+            "Subtasks cannot be nested more than {} levels deep",
+            MAX_TASK_DEPTH
+        )));
+    }
+
+    Ok(parent)
+}
+
+/// How many levels a task and its subtasks span, counting the task itself.
+/// Trashed subtasks count too, since they can be restored.
+async fn subtree_levels<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<usize> {
+    let mut levels = 1;
+    let mut seen = HashSet::from([task_id]);
+    let mut frontier = vec![task_id];
+
+    while levels <= MAX_TASK_DEPTH {
+        let children = Task::find()
+            .select_only()
+            .column(task::Column::Id)
+            .filter(task::Column::ParentTaskId.is_in(frontier))
+            .into_tuple::<Uuid>()
+            .all(db)
+            .await?;
+
+        frontier = children.into_iter().filter(|id| seen.insert(*id)).collect();
+        if frontier.is_empty() {
+            break;
+        }
+        levels += 1;
+    }
+
+    Ok(levels)
+}
+
+pub async fn set_task_parent(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: SetTaskParentRequest,
+) -> AppResult<TaskResponse> {
+    let txn = db.begin().await?;
+
+    let (task, topic) = find_owned_task(&txn, user_id, task_id).await?;
+
+    // Serialise parent changes per workspace so two concurrent moves cannot
+    // each pass the loop check and close a cycle between them.
+    workspace::Entity::find_by_id(topic.workspace_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?;
+
+    if let Some(parent_task_id) = request.parent_task_id {
+        check_task_parent(&txn, topic.workspace_id, Some(task.id), parent_task_id).await?;
+    }
+
+    let before = task.clone();
+    let mut task: task::ActiveModel = task.into();
+    task.parent_task_id = Set(request.parent_task_id);
+    task.updated_at = Set(chrono::Utc::now());
+
+    let task = task.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&task))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
+/// Turns a checklist item into a child task of the task it belongs to. The
+/// new task keeps the substep's completion state and the substep is removed.
+pub async fn promote_substep_to_task(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
+    user_id: Uuid,
+    substep_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let substep = get_substep_by_id(db, user_id, substep_id).await?;
+    let (parent, topic) = find_owned_task(db, user_id, substep.task_id).await?;
+    let substep_changes = field_changes(Some(&substep), None);
+
+    let txn = db.begin().await?;
+
+    check_task_quota(&txn, config, user_id, topic.workspace_id, 1).await?;
+    check_task_parent(&txn, topic.workspace_id, None, parent.id).await?;
+
+    let category = if substep.completed {
+        TaskStatusCategory::Done
+    } else {
+        TaskStatusCategory::Todo
+    };
+    let status = first_task_status_in_category(&txn, topic.workspace_id, category).await?;
+    let rank = rank_after(
+        last_task_rank(&txn, topic.workspace_id, None)
+            .await?
+            .as_deref()
+            .unwrap_or(""),
+    );
+
+    let task = task::ActiveModel {
+        topic_id: Set(parent.topic_id),
+        parent_task_id: Set(Some(parent.id)),
+        title: Set(substep.description),
+        description: Set(None),
+        start_time: Set(None),
+        end_time: Set(None),
+        task_type_id: Set(parent.task_type_id),
+        status_id: Set(status.map(|s| s.id)),
+        rank: Set(rank),
+        color: Set(parent.color),
+        priority: Set(TaskPriority::None),
+        completed: Set(substep.completed),
+        due_date: Set(None),
+        recurrence_type: Set(None),
+        recurrence_interval: Set(None),
+        recurrence_days: Set(None),
+        recurrence_end_date: Set(None),
+        ..Default::default()
+    };
+    let task = task.insert(&txn).await?;
+
+    TaskSubstep::delete_by_id(substep_id).exec(&txn).await?;
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Substep,
+            substep_id,
+            ActivityAction::Deleted,
+        )
+        .on_task(parent.id)
+        .with_changes(substep_changes),
+    )
+    .await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&task))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    invalidate_substeps_cache(cache, user_id, parent.id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::substep(
+            ChangeAction::Deleted,
+            user_id,
+            topic.workspace_id,
+            parent.id,
+            substep_id,
+        ),
+    )
+    .await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Created, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    Ok(TaskResponse::from(task))
+}
+
+pub async fn get_task_subtree(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<TaskTreeNode> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+    let descendants = find_task_descendants(db, task.id, true).await?;
+
+    let mut responses: Vec<TaskResponse> = std::iter::once(task)
+        .chain(descendants)
+        .map(TaskResponse::from)
+        .collect();
+    attach_task_details(db, &mut responses).await?;
+
+    let mut children: HashMap<Uuid, Vec<TaskResponse>> = HashMap::new();
+    let mut responses = responses.into_iter();
+    let root = responses
+        .next()
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+    for response in responses {
+        if let Some(parent_id) = response.parent_task_id {
+            children.entry(parent_id).or_default().push(response);
+        }
+    }
+
+    Ok(build_tree_node(root, &mut children))
+}
+
+fn build_tree_node(
+    task: TaskResponse,
+    children: &mut HashMap<Uuid, Vec<TaskResponse>>,
+) -> TaskTreeNode {
+    let nodes: Vec<TaskTreeNode> = children
+        .remove(&task.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| build_tree_node(child, children))
+        .collect();
+
+    let mut progress = TaskProgress::default();
+    for node in &nodes {
+        progress.total += 1 + node.progress.total;
+        progress.completed += u64::from(node.task.completed) + node.progress.completed;
+    }
+    if progress.total > 0 {
+        progress.percent = progress.completed as f64 * 100.0 / progress.total as f64;
+    }
+
+    TaskTreeNode {
+        task,
+        progress,
+        children: nodes,
+    }
+}
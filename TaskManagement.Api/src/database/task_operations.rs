@@ -1,15 +1,35 @@
 use crate::cache::{keys, Cache};
-use crate::database::resolve_workspace_id;
+use crate::config::Config;
+use crate::database::{
+    attach_task_assignees, attach_task_custom_fields, attach_task_dependencies, attach_task_effort,
+    attach_task_labels, attach_task_time_tracked, attach_task_type_names, check_task_blockers,
+    check_task_parent, check_task_quota, check_workspace_assignees, check_workspace_labels,
+    custom_field_filter_condition, custom_field_sort_expr, enqueue_webhook_event, field_change,
+    field_changes, find_task_descendants, find_workspace_task_status,
+    first_task_status_in_category, issue_undo_token, notify_task_assignees, record_activity,
+    replace_task_assignees, replace_task_labels, resolve_task_status_change, resolve_task_type,
+    resolve_task_type_filter, resolve_workspace_id, set_custom_field_values, task_assignee_filter,
+    task_label_filter, validate_effort_minutes, AssigneeFilter, CustomFieldFilter,
+    CustomFieldValueResponse, EffortSummary, LabelMatch, LabelResponse, NewActivity, UndoStep,
+    UndoTokenResponse,
+};
 use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
 use crate::models::{
-    task::{self, Entity as Task, TaskType},
+    activity_log::{ActivityAction, ActivityEntity},
+    task::{self, Entity as Task, TaskPriority},
     task_exception::{self, Entity as TaskException},
     topic::{self, Entity as Topic},
+    task_status::TaskStatusCategory,
+    webhook::WebhookEvent,
     workspace::{self, Entity as Workspace},
 };
+use crate::rank::{rank_after, rank_between, rank_sequence, MAX_RANK_LEN};
 use chrono::{Datelike, Timelike};
+use sea_orm::sea_query::Expr;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -38,6 +58,10 @@ pub enum SortField {
     EndTime,
     Title,
     DueDate,
+    Rank,
+    Priority,
+    /// The value of a custom field of the workspace.
+    CustomField(Uuid),
 }
 
 impl std::str::FromStr for SortField {
@@ -51,7 +75,14 @@ impl std::str::FromStr for SortField {
             "end_time" | "endtime" => Ok(SortField::EndTime),
             "title" => Ok(SortField::Title),
             "due_date" | "duedate" => Ok(SortField::DueDate),
-            _ => Err(format!("Invalid sort field: {}", s)),
+            "rank" | "position" => Ok(SortField::Rank),
+            "priority" => Ok(SortField::Priority),
+            other => other
+                .strip_prefix("cf:")
+                .or_else(|| other.strip_prefix("custom_field:"))
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .map(SortField::CustomField)
+                .ok_or_else(|| format!("Invalid sort field: {}", s)),
         }
     }
 }
@@ -60,8 +91,19 @@ impl std::str::FromStr for SortField {
 pub struct TaskFilterOptions {
     pub start_date: Option<chrono::DateTime<chrono::Utc>>,
     pub end_date: Option<chrono::DateTime<chrono::Utc>>,
-    pub task_types: Option<Vec<TaskType>>,
+    /// Task type IDs or names.
+    pub task_types: Option<Vec<String>>,
     pub completed: Option<bool>,
+    pub status_ids: Option<Vec<Uuid>>,
+    pub parent_task_id: Option<Uuid>,
+    pub top_level_only: bool,
+    pub labels: Option<Vec<String>>,
+    pub label_match: LabelMatch,
+    pub custom_fields: Vec<CustomFieldFilter>,
+    pub assignee: Option<AssigneeFilter>,
+    pub priorities: Option<Vec<TaskPriority>>,
+    /// Matches tasks at or above `TaskPriority::URGENT` (or below it when
+    /// false).
     pub urgent: Option<bool>,
     pub title_contains: Option<String>,
     pub sort_field: SortField,
@@ -69,61 +111,127 @@ pub struct TaskFilterOptions {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
     pub workspace_id: Option<Uuid>,
+    pub topic_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTaskRequest {
     pub topic_id: Uuid,
+    pub parent_task_id: Option<Uuid>,
     pub title: String,
     pub description: Option<String>,
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
-    pub task_type: TaskType,
-    pub color: String,
-    pub urgent: bool,
+    pub task_type_id: Option<Uuid>,
+    /// Type name, accepted in place of `task_type_id` for older clients.
+    pub task_type: Option<String>,
+    pub status_id: Option<Uuid>,
+    pub label_ids: Option<Vec<Uuid>>,
+    /// Users to assign; they must be members of the workspace.
+    pub assignee_ids: Option<Vec<Uuid>>,
+    /// Values keyed by custom field ID.
+    pub custom_fields: Option<HashMap<Uuid, serde_json::Value>>,
+    /// Defaults to the colour of the task type.
+    pub color: Option<String>,
+    pub priority: Option<TaskPriority>,
+    /// Accepted from clients that predate priorities; `true` maps to
+    /// `TaskPriority::URGENT` when no priority is given.
+    pub urgent: Option<bool>,
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub estimate_minutes: Option<i32>,
+    /// Defaults to `estimate_minutes`.
+    pub remaining_minutes: Option<i32>,
     pub recurrence_type: Option<String>,
     pub recurrence_interval: Option<i32>,
     pub recurrence_days: Option<Vec<i32>>,
     pub recurrence_end_date: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateTaskRequest {
     pub title: Option<String>,
     pub description: Option<String>,
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
-    pub task_type: Option<TaskType>,
+    pub task_type_id: Option<Uuid>,
+    pub task_type: Option<String>,
+    pub status_id: Option<Uuid>,
     pub color: Option<String>,
+    pub priority: Option<TaskPriority>,
+    /// Ignored when `priority` is given.
     pub urgent: Option<bool>,
     pub completed: Option<bool>,
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub estimate_minutes: Option<i32>,
+    pub remaining_minutes: Option<i32>,
     pub recurrence_type: Option<String>,
     pub recurrence_interval: Option<i32>,
     pub recurrence_days: Option<Vec<i32>>,
     pub recurrence_end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub instance_date: Option<chrono::NaiveDate>,
+    /// Complete the task even if it still has unfinished blockers.
+    pub force: Option<bool>,
+}
+
+/// Moves a task next to a sibling and/or into another status or topic.
+/// `before_id` places it directly before that task, `after_id` directly
+/// after it. With neither, a task that changes status or topic goes to the
+/// end of the list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MoveTaskRequest {
+    pub before_id: Option<Uuid>,
+    pub after_id: Option<Uuid>,
+    pub status_id: Option<Uuid>,
+    pub topic_id: Option<Uuid>,
+    pub force: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TaskResponse {
     pub id: Uuid,
     pub topic_id: Uuid,
+    pub parent_task_id: Option<Uuid>,
     pub title: String,
     pub description: Option<String>,
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
-    pub task_type: TaskType,
+    pub task_type_id: Option<Uuid>,
+    /// Lowercased name of the task type, kept for clients that predate
+    /// per-workspace task types.
+    #[serde(default)]
+    pub task_type: Option<String>,
+    pub status_id: Option<Uuid>,
+    pub rank: String,
     pub color: String,
+    pub priority: TaskPriority,
+    /// Derived from `priority`, kept for clients that predate it.
     pub urgent: bool,
     pub completed: bool,
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub estimate_minutes: Option<i32>,
+    pub remaining_minutes: Option<i32>,
     pub recurrence_type: Option<String>,
     pub recurrence_interval: Option<i32>,
     pub recurrence_days: Option<Vec<i32>>,
     pub recurrence_end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub instance_date: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub blocked_by: Vec<Uuid>,
+    #[serde(default)]
+    pub blocking: Vec<Uuid>,
+    #[serde(default)]
+    pub labels: Vec<LabelResponse>,
+    #[serde(default)]
+    pub assignee_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub custom_fields: Vec<CustomFieldValueResponse>,
+    /// Total of the task's finished time entries.
+    #[serde(default)]
+    pub tracked_seconds: i64,
+    /// Rollup over the task and its subtasks.
+    #[serde(default)]
+    pub effort: EffortSummary,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -133,20 +241,35 @@ impl From<task::Model> for TaskResponse {
         Self {
             id: task.id,
             topic_id: task.topic_id,
+            parent_task_id: task.parent_task_id,
             title: task.title,
             description: task.description,
             start_time: task.start_time,
             end_time: task.end_time,
-            task_type: task.task_type,
+            task_type_id: task.task_type_id,
+            task_type: None,
+            status_id: task.status_id,
+            rank: task.rank,
             color: task.color,
-            urgent: task.urgent,
+            priority: task.priority,
+            urgent: task.priority.is_urgent(),
             completed: task.completed,
             due_date: task.due_date,
+            estimate_minutes: task.estimate_minutes,
+            remaining_minutes: task.remaining_minutes,
             recurrence_type: task.recurrence_type,
             recurrence_interval: task.recurrence_interval,
             recurrence_days: task.recurrence_days,
             recurrence_end_date: task.recurrence_end_date,
             instance_date: None,
+            blocked_by: Vec::new(),
+            blocking: Vec::new(),
+            labels: Vec::new(),
+            assignee_ids: Vec::new(),
+            custom_fields: Vec::new(),
+            tracked_seconds: 0,
+            effort: EffortSummary::default(),
+            deleted_at: task.deleted_at,
             created_at: task.created_at,
             updated_at: task.updated_at,
         }
@@ -156,28 +279,83 @@ impl From<task::Model> for TaskResponse {
 pub async fn create_task(
     db: &DatabaseConnection,
     cache: &Cache,
+    config: &Config,
     user_id: Uuid,
     request: CreateTaskRequest,
 ) -> AppResult<TaskResponse> {
+    validate_effort_minutes(request.estimate_minutes, request.remaining_minutes)?;
+
     // Verify that the topic belongs to the user
-    let _topic = Topic::find_by_id(request.topic_id)
+    let topic = Topic::find_by_id(request.topic_id)
         .inner_join(Workspace)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
+    if let Some(parent_task_id) = request.parent_task_id {
+        check_task_parent(db, topic.workspace_id, None, parent_task_id).await?;
+    }
+
+    if let Some(label_ids) = &request.label_ids {
+        check_workspace_labels(db, topic.workspace_id, label_ids).await?;
+    }
+
+    if let Some(assignee_ids) = &request.assignee_ids {
+        check_workspace_assignees(db, topic.workspace_id, assignee_ids).await?;
+    }
+
+    let task_type = resolve_task_type(
+        db,
+        topic.workspace_id,
+        request.task_type_id,
+        request.task_type.as_deref(),
+    )
+    .await?;
+
+    let color = request
+        .color
+        .or_else(|| task_type.as_ref().map(|t| t.color.clone()))
+        .ok_or_else(|| AppError::Validation("Task color is required".to_string()))?;
+
+    // Without an explicit end, the task lasts the type's default duration
+    let end_time = request.end_time.or_else(|| {
+        let minutes = task_type.as_ref()?.default_duration_minutes?;
+        Some(request.start_time? + chrono::Duration::minutes(minutes as i64))
+    });
+
+    let status = match request.status_id {
+        Some(status_id) => Some(find_workspace_task_status(db, topic.workspace_id, status_id).await?),
+        None => {
+            first_task_status_in_category(db, topic.workspace_id, TaskStatusCategory::Todo).await?
+        }
+    };
+
+    let rank = match last_task_rank(db, topic.workspace_id, None).await? {
+        Some(last) => rank_after(&last),
+        None => rank_after(""),
+    };
+
     let task = task::ActiveModel {
         topic_id: Set(request.topic_id),
+        parent_task_id: Set(request.parent_task_id),
         title: Set(request.title),
         description: Set(request.description),
         start_time: Set(request.start_time),
-        end_time: Set(request.end_time),
-        task_type: Set(request.task_type),
-        color: Set(request.color),
-        urgent: Set(request.urgent),
-        completed: Set(false),
+        end_time: Set(end_time),
+        task_type_id: Set(task_type.map(|t| t.id)),
+        status_id: Set(status.as_ref().map(|s| s.id)),
+        rank: Set(rank),
+        color: Set(color),
+        priority: Set(request.priority.unwrap_or_else(|| {
+            TaskPriority::from_urgent(TaskPriority::default(), request.urgent.unwrap_or(false))
+        })),
+        completed: Set(status.as_ref().is_some_and(|s| s.category.is_done())),
         due_date: Set(request.due_date),
+        estimate_minutes: Set(request.estimate_minutes),
+        remaining_minutes: Set(request.remaining_minutes.or(request.estimate_minutes)),
         recurrence_type: Set(request.recurrence_type),
         recurrence_interval: Set(request.recurrence_interval),
         recurrence_days: Set(request.recurrence_days),
@@ -185,12 +363,56 @@ pub async fn create_task(
         ..Default::default()
     };
 
-    let task = task.insert(db).await?;
+    let txn = db.begin().await?;
+    check_task_quota(&txn, config, user_id, topic.workspace_id, 1).await?;
+    let task = task.insert(&txn).await?;
+
+    if let Some(label_ids) = &request.label_ids {
+        replace_task_labels(&txn, task.id, label_ids).await?;
+    }
+
+    if let Some(assignee_ids) = &request.assignee_ids {
+        let added = replace_task_assignees(&txn, task.id, assignee_ids).await?;
+        notify_task_assignees(&txn, user_id, &task, topic.workspace_id, &added).await?;
+    }
+
+    if let Some(values) = &request.custom_fields {
+        set_custom_field_values(&txn, topic.workspace_id, task.id, values).await?;
+    }
+
+    enqueue_webhook_event(
+        &txn,
+        topic.workspace_id,
+        WebhookEvent::TaskCreated,
+        serde_json::json!({ "task": &task }),
+    )
+    .await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&task))),
+    )
+    .await?;
+
+    txn.commit().await?;
 
     // Invalidate cache for this user's tasks
     invalidate_tasks_cache(cache, user_id).await;
-
-    Ok(TaskResponse::from(task))
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Created, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
 }
 
 pub async fn get_tasks_by_user(
@@ -226,7 +448,10 @@ pub async fn get_tasks_by_user(
         .inner_join(Topic)
         .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
         .filter(workspace::Column::OwnerId.eq(user_id))
-        .filter(topic::Column::WorkspaceId.eq(resolved_workspace_id));
+        .filter(topic::Column::WorkspaceId.eq(resolved_workspace_id))
+        .filter(topic::Column::ArchivedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null());
 
     if let Some(start) = start_date {
         query = query.filter(task::Column::StartTime.gte(start));
@@ -238,7 +463,9 @@ pub async fn get_tasks_by_user(
 
     let tasks = query.order_by_asc(task::Column::StartTime).all(db).await?;
 
-    let task_responses: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
+    let mut task_responses: Vec<TaskResponse> =
+        tasks.into_iter().map(TaskResponse::from).collect();
+    attach_task_details(db, &mut task_responses).await?;
 
     // Cache the result
     if let Err(e) = cache.set(&cache_key, &task_responses).await {
@@ -258,17 +485,22 @@ pub async fn get_tasks_by_topic(
     let _topic = Topic::find_by_id(topic_id)
         .inner_join(Workspace)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
     let tasks = Task::find()
         .filter(task::Column::TopicId.eq(topic_id))
+        .filter(task::Column::DeletedAt.is_null())
         .order_by_asc(task::Column::StartTime)
         .all(db)
         .await?;
 
-    Ok(tasks.into_iter().map(TaskResponse::from).collect())
+    let mut responses: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
+    attach_task_details(db, &mut responses).await?;
+    Ok(responses)
 }
 
 pub async fn get_tasks_by_workspace(
@@ -278,11 +510,16 @@ pub async fn get_tasks_by_workspace(
     let tasks = Task::find()
         .inner_join(Topic)
         .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .filter(topic::Column::ArchivedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
         .order_by_asc(task::Column::StartTime)
         .all(db)
         .await?;
 
-    Ok(tasks.into_iter().map(TaskResponse::from).collect())
+    let mut responses: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
+    attach_task_details(db, &mut responses).await?;
+    Ok(responses)
 }
 
 pub async fn get_task_by_id(
@@ -294,27 +531,43 @@ pub async fn get_task_by_id(
         .inner_join(Topic)
         .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
 
-    Ok(TaskResponse::from(task))
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
 }
 
 pub async fn update_task(
     db: &DatabaseConnection,
+    cache: &Cache,
     user_id: Uuid,
     task_id: Uuid,
     request: UpdateTaskRequest,
 ) -> AppResult<TaskResponse> {
+    validate_effort_minutes(request.estimate_minutes, request.remaining_minutes)?;
+
     let task = Task::find_by_id(task_id)
         .inner_join(Topic)
         .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
 
+    let topic = Topic::find_by_id(task.topic_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+
     // If instance_date is provided, we are updating a specific occurrence
     if let Some(instance_date) = request.instance_date {
         if let Some(completed) = request.completed {
@@ -324,12 +577,16 @@ pub async fn update_task(
                 .filter(task_exception::Column::OriginalDate.eq(instance_date))
                 .one(db)
                 .await?;
+            let was_completed = exception
+                .as_ref()
+                .map_or(task.completed, |exception| exception.is_completed);
 
+            let txn = db.begin().await?;
             if let Some(exception) = exception {
                 let mut exception: task_exception::ActiveModel = exception.into();
                 exception.is_completed = Set(completed);
                 exception.updated_at = Set(chrono::Utc::now());
-                exception.update(db).await?;
+                exception.update(&txn).await?;
             } else {
                 let exception = task_exception::ActiveModel {
                     id: Set(Uuid::new_v4()),
@@ -339,8 +596,34 @@ pub async fn update_task(
                     created_at: Set(chrono::Utc::now()),
                     updated_at: Set(chrono::Utc::now()),
                 };
-                exception.insert(db).await?;
+                exception.insert(&txn).await?;
+            }
+
+            if completed != was_completed {
+                let state = |completed: bool| {
+                    serde_json::json!({
+                        "instance_date": instance_date,
+                        "completed": completed,
+                    })
+                };
+                record_activity(
+                    &txn,
+                    NewActivity::new(
+                        topic.workspace_id,
+                        user_id,
+                        ActivityEntity::Task,
+                        task_id,
+                        ActivityAction::Updated,
+                    )
+                    .with_changes(field_change(
+                        "instance_completed",
+                        state(was_completed),
+                        state(completed),
+                    )),
+                )
+                .await?;
             }
+            txn.commit().await?;
         }
 
         // Return the updated task representation for that instance
@@ -349,9 +632,60 @@ pub async fn update_task(
         if let Some(completed) = request.completed {
             response.completed = completed;
         }
+        attach_task_details(db, std::slice::from_mut(&mut response)).await?;
         return Ok(response);
     }
 
+    let txn = db.begin().await?;
+    let task = apply_task_update(&txn, user_id, topic.workspace_id, task, request).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
+/// Applies an update to the whole series (not a single occurrence) of a
+/// task within the caller's transaction: resolves the status and type,
+/// enforces blockers, queues the webhooks and logs the change. Callers
+/// validate the effort fields and handle `instance_date` themselves.
+pub(crate) async fn apply_task_update<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task: task::Model,
+    request: UpdateTaskRequest,
+) -> AppResult<task::Model> {
+    let status = resolve_task_status_change(db, &task, request.status_id, request.completed).await?;
+
+    let task_type = if request.task_type_id.is_some() || request.task_type.is_some() {
+        resolve_task_type(
+            db,
+            workspace_id,
+            request.task_type_id,
+            request.task_type.as_deref(),
+        )
+        .await?
+    } else {
+        None
+    };
+
+    if let Some(status) = &status {
+        if status.category.is_done() && !task.completed && !request.force.unwrap_or(false) {
+            check_task_blockers(db, task.id).await?;
+        }
+    }
+
+    let current_priority = task.priority;
+    let was_completed = task.completed;
+    let before = task.clone();
     let mut task: task::ActiveModel = task.into();
 
     if let Some(title) = request.title {
@@ -370,26 +704,41 @@ pub async fn update_task(
         task.end_time = Set(Some(end_time));
     }
 
-    if let Some(task_type) = request.task_type {
-        task.task_type = Set(task_type);
+    if let Some(task_type) = task_type {
+        task.task_type_id = Set(Some(task_type.id));
     }
 
     if let Some(color) = request.color {
         task.color = Set(color);
     }
 
-    if let Some(urgent) = request.urgent {
-        task.urgent = Set(urgent);
+    let priority = request.priority.or_else(|| {
+        request
+            .urgent
+            .map(|urgent| TaskPriority::from_urgent(current_priority, urgent))
+    });
+    if let Some(priority) = priority {
+        task.priority = Set(priority);
     }
 
-    if let Some(completed) = request.completed {
-        task.completed = Set(completed);
+    // `completed` always follows the category of the task's status
+    if let Some(status) = status {
+        task.status_id = Set(Some(status.id));
+        task.completed = Set(status.category.is_done());
     }
 
     if let Some(due_date) = request.due_date {
         task.due_date = Set(Some(due_date));
     }
 
+    if let Some(estimate_minutes) = request.estimate_minutes {
+        task.estimate_minutes = Set(Some(estimate_minutes));
+    }
+
+    if let Some(remaining_minutes) = request.remaining_minutes {
+        task.remaining_minutes = Set(Some(remaining_minutes));
+    }
+
     if let Some(recurrence_type) = request.recurrence_type {
         task.recurrence_type = Set(Some(recurrence_type));
     }
@@ -407,76 +756,481 @@ pub async fn update_task(
     }
 
     let task = task.update(db).await?;
-    Ok(TaskResponse::from(task))
+
+    let data = serde_json::json!({ "task": &task });
+    enqueue_webhook_event(db, workspace_id, WebhookEvent::TaskUpdated, data.clone()).await?;
+    if task.completed && !was_completed {
+        enqueue_webhook_event(db, workspace_id, WebhookEvent::TaskCompleted, data).await?;
+    }
+    record_activity(
+        db,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&task))),
+    )
+    .await?;
+
+    Ok(task)
 }
 
-pub async fn delete_task(db: &DatabaseConnection, user_id: Uuid, task_id: Uuid) -> AppResult<bool> {
+/// Moves a task and its subtasks to the trash. The returned token undoes
+/// the deletion within the undo window.
+pub async fn delete_task(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<UndoTokenResponse> {
     let task = Task::find_by_id(task_id)
         .inner_join(Topic)
         .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
 
-    Task::delete_by_id(task.id).exec(db).await?;
-    Ok(true)
+    let topic = Topic::find_by_id(task.topic_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+
+    let now = chrono::Utc::now();
+    let txn = db.begin().await?;
+    let (task, trashed_ids) = trash_task(&txn, user_id, topic.workspace_id, task, now).await?;
+    let undo = issue_undo_token(
+        &txn,
+        config,
+        user_id,
+        topic.workspace_id,
+        vec![UndoStep::RestoreTasks {
+            task_ids: trashed_ids,
+            deleted_at: now,
+        }],
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Deleted, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    Ok(undo)
 }
 
-pub async fn get_tasks_by_user_filtered(
+/// Moves a task and its subtasks to the trash within the caller's
+/// transaction. Subtasks go with their parent, stamped with the same time
+/// so that restoring the parent brings exactly them back. Returns the
+/// trashed task and the IDs of everything trashed, the task's first.
+pub(crate) async fn trash_task<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task: task::Model,
+    now: chrono::DateTime<chrono::Utc>,
+) -> AppResult<(task::Model, Vec<Uuid>)> {
+    let descendant_ids: Vec<Uuid> = find_task_descendants(db, task.id, true)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+
+    if !descendant_ids.is_empty() {
+        Task::update_many()
+            .col_expr(task::Column::DeletedAt, Expr::value(now))
+            .filter(task::Column::Id.is_in(descendant_ids.clone()))
+            .exec(db)
+            .await?;
+    }
+
+    let mut task: task::ActiveModel = task.into();
+    task.deleted_at = Set(Some(now));
+    let task = task.update(db).await?;
+
+    enqueue_webhook_event(
+        db,
+        workspace_id,
+        WebhookEvent::TaskDeleted,
+        serde_json::json!({ "task": &task }),
+    )
+    .await?;
+
+    let trashed_ids: Vec<Uuid> = std::iter::once(task.id).chain(descendant_ids).collect();
+    for id in &trashed_ids {
+        record_activity(
+            db,
+            NewActivity::new(
+                workspace_id,
+                user_id,
+                ActivityEntity::Task,
+                *id,
+                ActivityAction::Deleted,
+            ),
+        )
+        .await?;
+    }
+
+    Ok((task, trashed_ids))
+}
+
+pub async fn restore_task(
     db: &DatabaseConnection,
     cache: &Cache,
+    config: &Config,
     user_id: Uuid,
-    options: TaskFilterOptions,
-) -> AppResult<Vec<TaskResponse>> {
-    let resolved_workspace_id = resolve_workspace_id(db, user_id, options.workspace_id).await?;
-    // Generate cache key based on filter options
-    let cache_suffix = generate_filter_cache_suffix(&options);
-    let cache_key = cache.generate_cache_key(keys::TASKS, &user_id, cache_suffix.as_deref());
+    task_id: Uuid,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = Task::find_by_id(task_id)
+        .filter(task::Column::DeletedAt.is_not_null())
+        .find_also_related(Topic)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found in trash".to_string()))?;
 
-    // Try to get from cache first (only if no limit/offset for simplicity)
-    if options.limit.is_none() && options.offset.is_none() {
-        if let Some(cached_tasks) = cache.get::<Vec<TaskResponse>>(&cache_key).await? {
-            return Ok(cached_tasks);
+    let topic = topic.ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+
+    let workspace = Workspace::find_by_id(topic.workspace_id)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found in trash".to_string()))?;
+
+    if topic.deleted_at.is_some() || workspace.deleted_at.is_some() {
+        return Err(AppError::BadRequest(
+            "Restore the task's topic and workspace first".to_string(),
+        ));
+    }
+
+    if let Some(parent_task_id) = task.parent_task_id {
+        let parent_deleted = Task::find_by_id(parent_task_id)
+            .one(db)
+            .await?
+            .is_some_and(|parent| parent.deleted_at.is_some());
+        if parent_deleted {
+            return Err(AppError::BadRequest(
+                "Restore the parent task first".to_string(),
+            ));
         }
     }
 
-    // Build the query
-    let mut query = Task::find()
-        .inner_join(Topic)
+    let txn = db.begin().await?;
+
+    // Bring back the subtasks that were trashed along with this task.
+    let restored_ids: Vec<Uuid> = find_task_descendants(&txn, task.id, false)
+        .await?
+        .into_iter()
+        .filter(|t| t.deleted_at == task.deleted_at)
+        .map(|t| t.id)
+        .collect();
+
+    if !restored_ids.is_empty() {
+        Task::update_many()
+            .col_expr(
+                task::Column::DeletedAt,
+                Expr::value(Option::<chrono::DateTime<chrono::Utc>>::None),
+            )
+            .filter(task::Column::Id.is_in(restored_ids.clone()))
+            .exec(&txn)
+            .await?;
+    }
+
+    let mut task: task::ActiveModel = task.into();
+    task.deleted_at = Set(None);
+    task.updated_at = Set(chrono::Utc::now());
+
+    let task = task.update(&txn).await?;
+    check_task_quota(&txn, config, user_id, topic.workspace_id, 0).await?;
+    for id in std::iter::once(task.id).chain(restored_ids) {
+        record_activity(
+            &txn,
+            NewActivity::new(
+                topic.workspace_id,
+                user_id,
+                ActivityEntity::Task,
+                id,
+                ActivityAction::Restored,
+            ),
+        )
+        .await?;
+    }
+    txn.commit().await?;
+
+    // A restored task reappears in lists just like a new one
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Created, user_id, topic.workspace_id, &task),
+    )
+    .await;
+
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
+pub async fn move_task(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: MoveTaskRequest,
+) -> AppResult<TaskResponse> {
+    let (task, topic) = Task::find_by_id(task_id)
+        .find_also_related(Topic)
         .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
         .filter(workspace::Column::OwnerId.eq(user_id))
-        .filter(topic::Column::WorkspaceId.eq(resolved_workspace_id));
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task not found".to_string()))?;
+    let topic = topic.ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+    let workspace_id = topic.workspace_id;
 
-    // Apply date filters
-    if let Some(start) = options.start_date {
-        query = query.filter(task::Column::StartTime.gte(start));
+    if request.before_id == Some(task.id) || request.after_id == Some(task.id) {
+        return Err(AppError::BadRequest(
+            "A task cannot be moved relative to itself".to_string(),
+        ));
     }
 
-    if let Some(end) = options.end_date {
-        query = query.filter(task::Column::EndTime.lte(end));
+    let txn = db.begin().await?;
+    let task = apply_task_move(&txn, user_id, workspace_id, task, &request).await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::task(ChangeAction::Updated, user_id, workspace_id, &task),
+    )
+    .await;
+
+    let mut response = TaskResponse::from(task);
+    attach_task_details(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
+/// Moves a task within the caller's transaction, queues the webhooks and
+/// logs the move. The anchors must not name the task itself.
+pub(crate) async fn apply_task_move<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task: task::Model,
+    request: &MoveTaskRequest,
+) -> AppResult<task::Model> {
+    let target_topic_id = match request.topic_id {
+        Some(topic_id) if topic_id != task.topic_id => {
+            let target = Topic::find_by_id(topic_id)
+                .filter(topic::Column::WorkspaceId.eq(workspace_id))
+                .filter(topic::Column::DeletedAt.is_null())
+                .one(db)
+                .await?
+                .ok_or_else(|| {
+                    AppError::BadRequest("Target topic must be in the same workspace".to_string())
+                })?;
+            Some(target.id)
+        }
+        _ => None,
+    };
+
+    let status = resolve_task_status_change(db, &task, request.status_id, None).await?;
+
+    if let Some(status) = &status {
+        if status.category.is_done() && !task.completed && !request.force.unwrap_or(false) {
+            check_task_blockers(db, task.id).await?;
+        }
     }
 
-    // Apply task type filters
-    if let Some(task_types) = options.task_types {
-        query = query.filter(task::Column::TaskType.is_in(task_types));
+    let anchored = request.before_id.is_some() || request.after_id.is_some();
+    let relocated = target_topic_id.is_some()
+        || status.as_ref().is_some_and(|s| Some(s.id) != task.status_id);
+
+    let rank = if anchored || relocated {
+        let mut rank =
+            task_rank_between(db, workspace_id, task.id, request.after_id, request.before_id)
+                .await?;
+        if rank.is_none() {
+            rebalance_task_ranks(db, workspace_id).await?;
+            rank = task_rank_between(db, workspace_id, task.id, request.after_id, request.before_id)
+                .await?;
+        }
+        Some(rank.ok_or_else(|| {
+            AppError::Internal("Could not compute a rank for the task".to_string())
+        })?)
+    } else {
+        None
+    };
+
+    let before = task.clone();
+    let mut task: task::ActiveModel = task.into();
+
+    if let Some(rank) = rank {
+        task.rank = Set(rank);
     }
 
-    // Apply completion filter
-    if let Some(completed) = options.completed {
-        query = query.filter(task::Column::Completed.eq(completed));
+    if let Some(topic_id) = target_topic_id {
+        task.topic_id = Set(topic_id);
     }
 
-    // Apply urgency filter
-    if let Some(urgent) = options.urgent {
-        query = query.filter(task::Column::Urgent.eq(urgent));
+    if let Some(status) = status {
+        task.status_id = Set(Some(status.id));
+        task.completed = Set(status.category.is_done());
     }
 
-    // Apply title filter (partial match)
-    if let Some(title) = &options.title_contains {
-        query = query.filter(task::Column::Title.contains(title));
+    task.updated_at = Set(chrono::Utc::now());
+    let task = task.update(db).await?;
+
+    let data = serde_json::json!({ "task": &task });
+    enqueue_webhook_event(db, workspace_id, WebhookEvent::TaskUpdated, data.clone()).await?;
+    if task.completed && !before.completed {
+        enqueue_webhook_event(db, workspace_id, WebhookEvent::TaskCompleted, data).await?;
+    }
+    record_activity(
+        db,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Task,
+            task.id,
+            ActivityAction::Moved,
+        )
+        .with_changes(field_changes(Some(&before), Some(&task))),
+    )
+    .await?;
+
+    Ok(task)
+}
+
+/// Highest rank in the workspace, optionally ignoring one task.
+pub(crate) async fn last_task_rank<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    exclude: Option<Uuid>,
+) -> AppResult<Option<String>> {
+    let mut query = Task::find()
+        .inner_join(Topic)
+        .filter(topic::Column::WorkspaceId.eq(workspace_id));
+
+    if let Some(exclude) = exclude {
+        query = query.filter(task::Column::Id.ne(exclude));
+    }
+
+    let last = query.order_by_desc(task::Column::Rank).one(db).await?;
+    Ok(last.map(|t| t.rank))
+}
+
+/// Computes a rank between the anchors within the workspace, or `None` when
+/// the neighbours leave no room and the workspace needs rebalancing.
+async fn task_rank_between<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    task_id: Uuid,
+    after_id: Option<Uuid>,
+    before_id: Option<Uuid>,
+) -> AppResult<Option<String>> {
+    let siblings = || {
+        Task::find()
+            .inner_join(Topic)
+            .filter(topic::Column::WorkspaceId.eq(workspace_id))
+            .filter(task::Column::Id.ne(task_id))
+    };
+
+    let anchor = |id: Uuid| async move {
+        siblings()
+            .filter(task::Column::Id.eq(id))
+            .one(db)
+            .await?
+            .map(|t| t.rank)
+            .ok_or_else(|| AppError::BadRequest("Sibling task not found in workspace".to_string()))
+    };
+
+    let (lower, upper) = match (after_id, before_id) {
+        (Some(after_id), Some(before_id)) => {
+            (Some(anchor(after_id).await?), Some(anchor(before_id).await?))
+        }
+        (Some(after_id), None) => {
+            let lower = anchor(after_id).await?;
+            let upper = siblings()
+                .filter(task::Column::Rank.gt(lower.clone()))
+                .order_by_asc(task::Column::Rank)
+                .one(db)
+                .await?
+                .map(|t| t.rank);
+            (Some(lower), upper)
+        }
+        (None, Some(before_id)) => {
+            let upper = anchor(before_id).await?;
+            let lower = siblings()
+                .filter(task::Column::Rank.lt(upper.clone()))
+                .order_by_desc(task::Column::Rank)
+                .one(db)
+                .await?
+                .map(|t| t.rank);
+            (lower, Some(upper))
+        }
+        (None, None) => (last_task_rank(db, workspace_id, Some(task_id)).await?, None),
+    };
+
+    Ok(rank_between(lower.as_deref(), upper.as_deref()).filter(|rank| rank.len() <= MAX_RANK_LEN))
+}
+
+/// Rewrites every rank in the workspace with evenly spaced keys, keeping the
+/// current order. Only needed when neighbouring keys collide or grow long.
+async fn rebalance_task_ranks<C: ConnectionTrait>(db: &C, workspace_id: Uuid) -> AppResult<()> {
+    let tasks = Task::find()
+        .inner_join(Topic)
+        .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(task::Column::Rank)
+        .order_by_asc(task::Column::CreatedAt)
+        .order_by_asc(task::Column::Id)
+        .all(db)
+        .await?;
+
+    let ranks = rank_sequence(tasks.len());
+    for (task, rank) in tasks.into_iter().zip(ranks) {
+        Task::update_many()
+            .col_expr(task::Column::Rank, Expr::value(rank))
+            .filter(task::Column::Id.eq(task.id))
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_tasks_by_user_filtered(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    options: TaskFilterOptions,
+) -> AppResult<Vec<TaskResponse>> {
+    let resolved_workspace_id = resolve_workspace_id(db, user_id, options.workspace_id).await?;
+    // Generate cache key based on filter options
+    let cache_suffix = generate_filter_cache_suffix(&options);
+    let cache_key = cache.generate_cache_key(keys::TASKS, &user_id, cache_suffix.as_deref());
+
+    // Try to get from cache first (only if no limit/offset for simplicity)
+    if options.limit.is_none() && options.offset.is_none() {
+        if let Some(cached_tasks) = cache.get::<Vec<TaskResponse>>(&cache_key).await? {
+            return Ok(cached_tasks);
+        }
     }
 
+    let mut query = filtered_task_query(db, user_id, resolved_workspace_id, &options).await?;
+
     // Apply sorting
     query = match (&options.sort_field, &options.sort_order) {
         (SortField::CreatedAt, SortOrder::Asc) => query.order_by_asc(task::Column::CreatedAt),
@@ -491,6 +1245,17 @@ pub async fn get_tasks_by_user_filtered(
         (SortField::Title, SortOrder::Desc) => query.order_by_desc(task::Column::Title),
         (SortField::DueDate, SortOrder::Asc) => query.order_by_asc(task::Column::DueDate),
         (SortField::DueDate, SortOrder::Desc) => query.order_by_desc(task::Column::DueDate),
+        (SortField::Rank, SortOrder::Asc) => query.order_by_asc(task::Column::Rank),
+        (SortField::Rank, SortOrder::Desc) => query.order_by_desc(task::Column::Rank),
+        (SortField::Priority, SortOrder::Asc) => query.order_by_asc(task::Column::Priority),
+        (SortField::Priority, SortOrder::Desc) => query.order_by_desc(task::Column::Priority),
+        (SortField::CustomField(field_id), order) => {
+            let expr = custom_field_sort_expr(db, resolved_workspace_id, *field_id).await?;
+            match order {
+                SortOrder::Asc => query.order_by(expr, Order::Asc),
+                SortOrder::Desc => query.order_by(expr, Order::Desc),
+            }
+        }
     };
 
     // Apply pagination
@@ -547,12 +1312,14 @@ pub async fn get_tasks_by_user_filtered(
         all_task_responses
     };
 
-    let final_responses = if let Some(limit) = options.limit {
+    let mut final_responses = if let Some(limit) = options.limit {
         final_responses.into_iter().take(limit).collect::<Vec<_>>()
     } else {
         final_responses
     };
 
+    attach_task_details(db, &mut final_responses).await?;
+
     // Cache the result (only if no pagination)
     if options.limit.is_none() && options.offset.is_none() {
         if let Err(e) = cache.set(&cache_key, &final_responses).await {
@@ -563,6 +1330,101 @@ pub async fn get_tasks_by_user_filtered(
     Ok(final_responses)
 }
 
+/// The live tasks of a workspace matching the filter options, without
+/// sorting or pagination.
+pub(crate) async fn filtered_task_query<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    options: &TaskFilterOptions,
+) -> AppResult<Select<Task>> {
+    let mut query = Task::find()
+        .inner_join(Topic)
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .filter(topic::Column::ArchivedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null());
+
+    if let Some(topic_id) = options.topic_id {
+        query = query.filter(task::Column::TopicId.eq(topic_id));
+    }
+
+    // Apply date filters
+    if let Some(start) = options.start_date {
+        query = query.filter(task::Column::StartTime.gte(start));
+    }
+
+    if let Some(end) = options.end_date {
+        query = query.filter(task::Column::EndTime.lte(end));
+    }
+
+    // Apply task type filters
+    if let Some(task_types) = &options.task_types {
+        let task_type_ids =
+            resolve_task_type_filter(db, workspace_id, task_types).await?;
+        query = query.filter(task::Column::TaskTypeId.is_in(task_type_ids));
+    }
+
+    // Apply completion filter
+    if let Some(completed) = options.completed {
+        query = query.filter(task::Column::Completed.eq(completed));
+    }
+
+    // Apply hierarchy filters
+    if let Some(parent_task_id) = options.parent_task_id {
+        query = query.filter(task::Column::ParentTaskId.eq(parent_task_id));
+    }
+
+    if options.top_level_only {
+        query = query.filter(task::Column::ParentTaskId.is_null());
+    }
+
+    // Apply label filter
+    if let Some(labels) = &options.labels {
+        let condition = task_label_filter(db, workspace_id, labels, options.label_match).await?;
+        query = query.filter(condition);
+    }
+
+    // Apply assignee filter
+    if let Some(assignee) = options.assignee {
+        query = query.filter(task_assignee_filter(assignee, user_id));
+    }
+
+    // Apply custom field filters
+    if !options.custom_fields.is_empty() {
+        let condition =
+            custom_field_filter_condition(db, workspace_id, &options.custom_fields).await?;
+        query = query.filter(condition);
+    }
+
+    // Apply status filter (Kanban columns)
+    if let Some(status_ids) = options.status_ids.clone() {
+        query = query.filter(task::Column::StatusId.is_in(status_ids));
+    }
+
+    // Apply priority filters
+    if let Some(priorities) = options.priorities.clone() {
+        query = query.filter(task::Column::Priority.is_in(priorities));
+    }
+
+    if let Some(urgent) = options.urgent {
+        query = if urgent {
+            query.filter(task::Column::Priority.gte(TaskPriority::URGENT))
+        } else {
+            query.filter(task::Column::Priority.lt(TaskPriority::URGENT))
+        };
+    }
+
+    // Apply title filter (partial match)
+    if let Some(title) = &options.title_contains {
+        query = query.filter(task::Column::Title.contains(title));
+    }
+
+    Ok(query)
+}
+
 fn expand_task(
     task: task::Model,
     start_range: chrono::DateTime<chrono::Utc>,
@@ -689,6 +1551,10 @@ fn sort_task_responses(tasks: &mut Vec<TaskResponse>, field: &SortField, order:
             SortField::EndTime => a.end_time.cmp(&b.end_time),
             SortField::Title => a.title.cmp(&b.title),
             SortField::DueDate => a.due_date.cmp(&b.due_date),
+            SortField::Rank => a.rank.cmp(&b.rank),
+            SortField::Priority => a.priority.cmp(&b.priority),
+            // Already ordered by the database; the stable sort keeps it
+            SortField::CustomField(_) => std::cmp::Ordering::Equal,
         };
         match order {
             SortOrder::Asc => res,
@@ -713,14 +1579,43 @@ fn generate_filter_cache_suffix(options: &TaskFilterOptions) -> Option<String> {
     }
 
     if let Some(types) = &options.task_types {
-        let type_str: Vec<String> = types.iter().map(|t| format!("{:?}", t)).collect();
-        parts.push(format!("types:{}", type_str.join(",")));
+        parts.push(format!("types:{}", types.join(",")));
     }
 
     if let Some(completed) = options.completed {
         parts.push(format!("completed:{}", completed));
     }
 
+    if let Some(parent_task_id) = options.parent_task_id {
+        parts.push(format!("parent:{}", parent_task_id));
+    }
+
+    if options.top_level_only {
+        parts.push("top_level".to_string());
+    }
+
+    if let Some(labels) = &options.labels {
+        parts.push(format!("labels:{:?}:{}", options.label_match, labels.join(",")));
+    }
+
+    if let Some(assignee) = &options.assignee {
+        parts.push(format!("assignee:{:?}", assignee));
+    }
+
+    for filter in &options.custom_fields {
+        parts.push(format!("cf:{}:{:?}:{}", filter.field_id, filter.op, filter.value));
+    }
+
+    if let Some(status_ids) = &options.status_ids {
+        let status_str: Vec<String> = status_ids.iter().map(|id| id.to_string()).collect();
+        parts.push(format!("statuses:{}", status_str.join(",")));
+    }
+
+    if let Some(priorities) = &options.priorities {
+        let priority_str: Vec<String> = priorities.iter().map(|p| format!("{:?}", p)).collect();
+        parts.push(format!("priorities:{}", priority_str.join(",")));
+    }
+
     if let Some(urgent) = options.urgent {
         parts.push(format!("urgent:{}", urgent));
     }
@@ -741,8 +1636,24 @@ fn generate_filter_cache_suffix(options: &TaskFilterOptions) -> Option<String> {
     }
 }
 
+/// Fills the related data of task responses (task type, dependencies,
+/// labels, assignees and custom field values) with one query per relation, however many tasks
+/// there are.
+pub async fn attach_task_details<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    attach_task_type_names(db, tasks).await?;
+    attach_task_dependencies(db, tasks).await?;
+    attach_task_labels(db, tasks).await?;
+    attach_task_assignees(db, tasks).await?;
+    attach_task_custom_fields(db, tasks).await?;
+    attach_task_time_tracked(db, tasks).await?;
+    attach_task_effort(db, tasks).await
+}
+
 // Cache helper functions
-async fn invalidate_tasks_cache(cache: &Cache, user_id: Uuid) {
+pub(crate) async fn invalidate_tasks_cache(cache: &Cache, user_id: Uuid) {
     // This is a simple implementation - delete all possible cache keys for this user
     // In production, you might want to keep track of cache keys or use pattern matching
     let base_key = cache.generate_cache_key(keys::TASKS, &user_id, None);
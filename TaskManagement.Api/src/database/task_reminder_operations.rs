@@ -0,0 +1,409 @@
+use crate::database::{
+    field_changes, find_owned_task, notify, record_activity, NewActivity, NewNotification,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    notification::NotificationKind,
+    task,
+    task_reminder::{self, Entity as TaskReminder, ReminderAnchor},
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Reminders can be set up to a year ahead of their anchor.
+const MAX_REMINDER_OFFSET_MINUTES: i32 = 365 * 24 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReminderRequest {
+    pub anchor: ReminderAnchor,
+    /// Minutes before the anchor time; 0 fires at the anchor time itself.
+    pub offset_minutes: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReminderRequest {
+    pub anchor: Option<ReminderAnchor>,
+    pub offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReminderResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub anchor: ReminderAnchor,
+    pub offset_minutes: i32,
+    /// When the reminder fires, or `None` while the task has no time for
+    /// its anchor.
+    pub remind_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub fired_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn anchor_time(
+    task: &task::Model,
+    anchor: ReminderAnchor,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    match anchor {
+        ReminderAnchor::StartTime => task.start_time,
+        ReminderAnchor::DueDate => task.due_date,
+    }
+}
+
+fn reminder_response(reminder: task_reminder::Model, task: &task::Model) -> ReminderResponse {
+    let anchor_at = anchor_time(task, reminder.anchor);
+    let remind_at =
+        anchor_at.map(|at| at - chrono::Duration::minutes(reminder.offset_minutes as i64));
+    // A reminder that fired for an earlier anchor time is armed again
+    let fired_at = reminder
+        .fired_at
+        .filter(|_| reminder.fired_for == anchor_at);
+
+    ReminderResponse {
+        id: reminder.id,
+        task_id: reminder.task_id,
+        user_id: reminder.user_id,
+        anchor: reminder.anchor,
+        offset_minutes: reminder.offset_minutes,
+        remind_at,
+        fired_at,
+        created_at: reminder.created_at,
+        updated_at: reminder.updated_at,
+    }
+}
+
+fn validate_offset(offset_minutes: i32) -> AppResult<()> {
+    if !(0..=MAX_REMINDER_OFFSET_MINUTES).contains(&offset_minutes) {
+        return Err(AppError::Validation(format!(
+            "Reminder offset must be between 0 and {} minutes",
+            MAX_REMINDER_OFFSET_MINUTES
+        )));
+    }
+
+    Ok(())
+}
+
+async fn check_duplicate_reminder<C: ConnectionTrait>(
+    db: &C,
+    task_id: Uuid,
+    user_id: Uuid,
+    anchor: ReminderAnchor,
+    offset_minutes: i32,
+    exclude_id: Option<Uuid>,
+) -> AppResult<()> {
+    let mut query = TaskReminder::find()
+        .filter(task_reminder::Column::TaskId.eq(task_id))
+        .filter(task_reminder::Column::UserId.eq(user_id))
+        .filter(task_reminder::Column::Anchor.eq(anchor))
+        .filter(task_reminder::Column::OffsetMinutes.eq(offset_minutes));
+
+    if let Some(id) = exclude_id {
+        query = query.filter(task_reminder::Column::Id.ne(id));
+    }
+
+    if query.count(db).await? > 0 {
+        return Err(AppError::Conflict(
+            "An identical reminder already exists".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Loads a reminder of the user on a live task of one of their workspaces,
+/// along with the task and the ID of the workspace.
+async fn find_own_reminder<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    reminder_id: Uuid,
+) -> AppResult<(task_reminder::Model, task::Model, Uuid)> {
+    let reminder = TaskReminder::find_by_id(reminder_id)
+        .filter(task_reminder::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Reminder not found".to_string()))?;
+
+    let (task, topic) = find_owned_task(db, user_id, reminder.task_id)
+        .await
+        .map_err(|_| AppError::NotFound("Reminder not found".to_string()))?;
+
+    Ok((reminder, task, topic.workspace_id))
+}
+
+pub async fn get_reminders_by_task(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<Vec<ReminderResponse>> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+
+    let reminders = TaskReminder::find()
+        .filter(task_reminder::Column::TaskId.eq(task.id))
+        .filter(task_reminder::Column::UserId.eq(user_id))
+        .order_by_asc(task_reminder::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(reminders
+        .into_iter()
+        .map(|reminder| reminder_response(reminder, &task))
+        .collect())
+}
+
+pub async fn create_reminder(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: CreateReminderRequest,
+) -> AppResult<ReminderResponse> {
+    validate_offset(request.offset_minutes)?;
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+    check_duplicate_reminder(
+        db,
+        task.id,
+        user_id,
+        request.anchor,
+        request.offset_minutes,
+        None,
+    )
+    .await?;
+
+    let reminder = task_reminder::ActiveModel {
+        task_id: Set(task.id),
+        user_id: Set(user_id),
+        anchor: Set(request.anchor),
+        offset_minutes: Set(request.offset_minutes),
+        fired_for: Set(None),
+        fired_at: Set(None),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let reminder = reminder.insert(&txn).await?;
+    record_activity(
+        &txn,
+        reminder_activity(
+            topic.workspace_id,
+            user_id,
+            &reminder,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&reminder))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(reminder_response(reminder, &task))
+}
+
+fn reminder_activity(
+    workspace_id: Uuid,
+    user_id: Uuid,
+    reminder: &task_reminder::Model,
+    action: ActivityAction,
+) -> NewActivity {
+    NewActivity::new(
+        workspace_id,
+        user_id,
+        ActivityEntity::Reminder,
+        reminder.id,
+        action,
+    )
+    .on_task(reminder.task_id)
+}
+
+/// Changing a reminder arms it again, even if it already fired.
+pub async fn update_reminder(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    reminder_id: Uuid,
+    request: UpdateReminderRequest,
+) -> AppResult<ReminderResponse> {
+    let (reminder, task, workspace_id) = find_own_reminder(db, user_id, reminder_id).await?;
+
+    let anchor = request.anchor.unwrap_or(reminder.anchor);
+    let offset_minutes = request.offset_minutes.unwrap_or(reminder.offset_minutes);
+    validate_offset(offset_minutes)?;
+    check_duplicate_reminder(
+        db,
+        task.id,
+        user_id,
+        anchor,
+        offset_minutes,
+        Some(reminder.id),
+    )
+    .await?;
+
+    let before = reminder.clone();
+    let mut reminder: task_reminder::ActiveModel = reminder.into();
+    reminder.anchor = Set(anchor);
+    reminder.offset_minutes = Set(offset_minutes);
+    reminder.fired_for = Set(None);
+    reminder.fired_at = Set(None);
+    reminder.updated_at = Set(chrono::Utc::now());
+
+    let txn = db.begin().await?;
+    let reminder = reminder.update(&txn).await?;
+    record_activity(
+        &txn,
+        reminder_activity(workspace_id, user_id, &reminder, ActivityAction::Updated)
+            .with_changes(field_changes(Some(&before), Some(&reminder))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(reminder_response(reminder, &task))
+}
+
+pub async fn delete_reminder(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    reminder_id: Uuid,
+) -> AppResult<bool> {
+    let (reminder, _, workspace_id) = find_own_reminder(db, user_id, reminder_id).await?;
+
+    let txn = db.begin().await?;
+    TaskReminder::delete_by_id(reminder.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        reminder_activity(workspace_id, user_id, &reminder, ActivityAction::Deleted)
+            .with_changes(field_changes(Some(&reminder), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(true)
+}
+
+#[derive(Debug, FromQueryResult)]
+struct DueReminder {
+    id: Uuid,
+    task_id: Uuid,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    anchor: String,
+    offset_minutes: i32,
+    title: String,
+    anchor_time: chrono::DateTime<chrono::Utc>,
+}
+
+/// Reminders whose time has come and that have not fired for the current
+/// anchor time yet. Rows are locked with `SKIP LOCKED` so that concurrent
+/// schedulers on other replicas pick disjoint batches.
+const DUE_REMINDERS_SQL: &str = r#"
+SELECT r.id, r.task_id, w.id AS workspace_id, r.user_id, r.anchor::text AS anchor, r.offset_minutes,
+       t.title, a.anchor_time
+FROM task_reminders r
+JOIN tasks t ON t.id = r.task_id
+JOIN topics tp ON tp.id = t.topic_id
+JOIN workspaces w ON w.id = tp.workspace_id
+CROSS JOIN LATERAL (
+    SELECT CASE r.anchor
+        WHEN 'start_time' THEN t.start_time
+        ELSE t.due_date
+    END AS anchor_time
+) a
+WHERE a.anchor_time IS NOT NULL
+  AND a.anchor_time - make_interval(mins => r.offset_minutes) <= $1
+  AND r.fired_for IS DISTINCT FROM a.anchor_time
+  AND NOT t.completed
+  AND t.deleted_at IS NULL
+  AND tp.deleted_at IS NULL
+  AND tp.archived_at IS NULL
+  AND w.deleted_at IS NULL
+  AND w.archived_at IS NULL
+ORDER BY a.anchor_time - make_interval(mins => r.offset_minutes)
+LIMIT $2
+FOR UPDATE OF r SKIP LOCKED
+"#;
+
+fn describe_reminder(reminder: &DueReminder) -> String {
+    let what = if reminder.anchor == "start_time" {
+        "Starts"
+    } else {
+        "Due"
+    };
+
+    let minutes = reminder.offset_minutes as i64;
+    let when = if minutes == 0 {
+        "now".to_string()
+    } else if minutes % (24 * 60) == 0 {
+        plural(minutes / (24 * 60), "day")
+    } else if minutes % 60 == 0 {
+        plural(minutes / 60, "hour")
+    } else {
+        plural(minutes, "minute")
+    };
+
+    if minutes == 0 {
+        format!("{} {}", what, when)
+    } else {
+        format!("{} in {}", what, when)
+    }
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+/// Fires one batch of due reminders into their users' notification inboxes
+/// and returns how many fired.
+///
+/// Each reminder is marked as fired in the same transaction that holds its
+/// row lock and records its notification, so it fires exactly once across
+/// replicas.
+pub async fn fire_due_reminders(db: &DatabaseConnection, batch_size: u64) -> AppResult<u64> {
+    let now = chrono::Utc::now();
+    let txn = db.begin().await?;
+
+    let due = DueReminder::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        DUE_REMINDERS_SQL,
+        [now.into(), (batch_size as i64).into()],
+    ))
+    .all(&txn)
+    .await?;
+
+    if due.is_empty() {
+        txn.commit().await?;
+        return Ok(0);
+    }
+
+    notify(
+        &txn,
+        due.iter().map(|reminder| NewNotification {
+            user_id: reminder.user_id,
+            kind: NotificationKind::Reminder,
+            title: format!("Reminder: {}", reminder.title),
+            body: Some(describe_reminder(reminder)),
+            actor_id: None,
+            workspace_id: Some(reminder.workspace_id),
+            task_id: Some(reminder.task_id),
+        }),
+    )
+    .await?;
+
+    for reminder in &due {
+        TaskReminder::update_many()
+            .col_expr(
+                task_reminder::Column::FiredFor,
+                Expr::value(reminder.anchor_time),
+            )
+            .col_expr(task_reminder::Column::FiredAt, Expr::value(now))
+            .filter(task_reminder::Column::Id.eq(reminder.id))
+            .exec(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(due.len() as u64)
+}
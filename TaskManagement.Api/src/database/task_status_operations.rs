@@ -0,0 +1,554 @@
+use crate::database::{
+    field_change, field_changes, find_owned_workspace, record_activity, NewActivity,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    task::{self, Entity as Task},
+    task_status::{self, Entity as TaskStatus, TaskStatusCategory},
+    task_status_transition::{self, Entity as TaskStatusTransition},
+    topic::Entity as Topic,
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Columns every new workspace starts with: (name, category, color).
+const DEFAULT_TASK_STATUSES: [(&str, TaskStatusCategory, &str); 4] = [
+    ("Backlog", TaskStatusCategory::Todo, "#94a3b8"),
+    ("In Progress", TaskStatusCategory::Doing, "#3b82f6"),
+    ("Review", TaskStatusCategory::Doing, "#f59e0b"),
+    ("Done", TaskStatusCategory::Done, "#22c55e"),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTaskStatusRequest {
+    pub name: String,
+    pub category: TaskStatusCategory,
+    pub color: Option<String>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTaskStatusRequest {
+    pub name: Option<String>,
+    pub category: Option<TaskStatusCategory>,
+    pub color: Option<String>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskStatusResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub category: TaskStatusCategory,
+    pub color: Option<String>,
+    pub position: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<task_status::Model> for TaskStatusResponse {
+    fn from(status: task_status::Model) -> Self {
+        Self {
+            id: status.id,
+            workspace_id: status.workspace_id,
+            name: status.name,
+            category: status.category,
+            color: status.color,
+            position: status.position,
+            created_at: status.created_at,
+            updated_at: status.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskStatusTransitionRequest {
+    pub from_status_id: Uuid,
+    pub to_status_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTaskStatusTransitionsRequest {
+    pub transitions: Vec<TaskStatusTransitionRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskStatusTransitionResponse {
+    pub id: Uuid,
+    pub from_status_id: Uuid,
+    pub to_status_id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<task_status_transition::Model> for TaskStatusTransitionResponse {
+    fn from(transition: task_status_transition::Model) -> Self {
+        Self {
+            id: transition.id,
+            from_status_id: transition.from_status_id,
+            to_status_id: transition.to_status_id,
+            created_at: transition.created_at,
+        }
+    }
+}
+
+/// Looks up a status and makes sure it belongs to the given workspace.
+pub async fn find_workspace_task_status<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    status_id: Uuid,
+) -> AppResult<task_status::Model> {
+    TaskStatus::find_by_id(status_id)
+        .filter(task_status::Column::WorkspaceId.eq(workspace_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Status does not belong to this workspace".to_string()))
+}
+
+/// Returns the left-most status of a category, if the workspace has one.
+pub async fn first_task_status_in_category<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    category: TaskStatusCategory,
+) -> AppResult<Option<task_status::Model>> {
+    let status = TaskStatus::find()
+        .filter(task_status::Column::WorkspaceId.eq(workspace_id))
+        .filter(task_status::Column::Category.eq(category))
+        .order_by_asc(task_status::Column::Position)
+        .order_by_asc(task_status::Column::CreatedAt)
+        .one(db)
+        .await?;
+
+    Ok(status)
+}
+
+pub async fn seed_default_task_statuses<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<Vec<task_status::Model>> {
+    let now = chrono::Utc::now();
+    let statuses: Vec<task_status::Model> = DEFAULT_TASK_STATUSES
+        .iter()
+        .enumerate()
+        .map(|(position, (name, category, color))| task_status::Model {
+            id: Uuid::new_v4(),
+            workspace_id,
+            name: name.to_string(),
+            category: *category,
+            color: Some(color.to_string()),
+            position: position as i32,
+            created_at: now,
+            updated_at: now,
+        })
+        .collect();
+
+    TaskStatus::insert_many(
+        statuses
+            .iter()
+            .cloned()
+            .map(task_status::ActiveModel::from),
+    )
+    .exec(db)
+    .await?;
+
+    Ok(statuses)
+}
+
+/// Rejects a status change that the workspace's transition rules do not
+/// allow. A status with no outgoing transitions configured is unrestricted.
+pub async fn check_task_status_transition<C: ConnectionTrait>(
+    db: &C,
+    from_status_id: Option<Uuid>,
+    to_status_id: Uuid,
+) -> AppResult<()> {
+    let from_status_id = match from_status_id {
+        Some(id) if id != to_status_id => id,
+        _ => return Ok(()),
+    };
+
+    let allowed: Vec<Uuid> = TaskStatusTransition::find()
+        .filter(task_status_transition::Column::FromStatusId.eq(from_status_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| t.to_status_id)
+        .collect();
+
+    if allowed.is_empty() || allowed.contains(&to_status_id) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(
+            "This status change is not allowed by the workspace workflow".to_string(),
+        ))
+    }
+}
+
+/// Works out the status a task should move to for an update that sets
+/// `status_id` explicitly or only toggles the legacy `completed` flag.
+/// Returns `None` when the task's status should stay as it is.
+pub async fn resolve_task_status_change<C: ConnectionTrait>(
+    db: &C,
+    task: &task::Model,
+    status_id: Option<Uuid>,
+    completed: Option<bool>,
+) -> AppResult<Option<task_status::Model>> {
+    if status_id.is_none() && completed.is_none() {
+        return Ok(None);
+    }
+
+    let topic = Topic::find_by_id(task.topic_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+
+    let target = if let Some(status_id) = status_id {
+        find_workspace_task_status(db, topic.workspace_id, status_id).await?
+    } else {
+        let completed = completed.unwrap_or_default();
+        let current = match task.status_id {
+            Some(id) => TaskStatus::find_by_id(id).one(db).await?,
+            None => None,
+        };
+
+        if current
+            .as_ref()
+            .is_some_and(|status| status.category.is_done() == completed)
+        {
+            return Ok(None);
+        }
+
+        let category = if completed {
+            TaskStatusCategory::Done
+        } else {
+            TaskStatusCategory::Todo
+        };
+
+        first_task_status_in_category(db, topic.workspace_id, category)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Workspace has no {:?} status to move the task to",
+                    category
+                ))
+            })?
+    };
+
+    check_task_status_transition(db, task.status_id, target.id).await?;
+    Ok(Some(target))
+}
+
+pub async fn list_task_statuses(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<TaskStatusResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    get_task_statuses_by_workspace(db, workspace.id).await
+}
+
+pub async fn get_task_statuses_by_workspace(
+    db: &DatabaseConnection,
+    workspace_id: Uuid,
+) -> AppResult<Vec<TaskStatusResponse>> {
+    let statuses = TaskStatus::find()
+        .filter(task_status::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(task_status::Column::Position)
+        .order_by_asc(task_status::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(statuses.into_iter().map(TaskStatusResponse::from).collect())
+}
+
+pub async fn create_task_status(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateTaskStatusRequest,
+) -> AppResult<TaskStatusResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    if request.name.trim().is_empty() {
+        return Err(AppError::Validation("Status name is required".to_string()));
+    }
+
+    let position = match request.position {
+        Some(position) => position,
+        None => TaskStatus::find()
+            .filter(task_status::Column::WorkspaceId.eq(workspace.id))
+            .order_by_desc(task_status::Column::Position)
+            .one(db)
+            .await?
+            .map_or(0, |last| last.position + 1),
+    };
+
+    let status = task_status::ActiveModel {
+        workspace_id: Set(workspace.id),
+        name: Set(request.name),
+        category: Set(request.category),
+        color: Set(request.color),
+        position: Set(position),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let status = status.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskStatus,
+            status.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&status))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(TaskStatusResponse::from(status))
+}
+
+pub async fn update_task_status(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    status_id: Uuid,
+    request: UpdateTaskStatusRequest,
+) -> AppResult<TaskStatusResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let status = TaskStatus::find_by_id(status_id)
+        .filter(task_status::Column::WorkspaceId.eq(workspace.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Status not found".to_string()))?;
+
+    if request.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+        return Err(AppError::Validation("Status name is required".to_string()));
+    }
+
+    let before = status.clone();
+    let txn = db.begin().await?;
+    let mut status: task_status::ActiveModel = status.into();
+
+    if let Some(name) = request.name {
+        status.name = Set(name);
+    }
+
+    if let Some(category) = request.category {
+        // Tasks in this column follow its category.
+        Task::update_many()
+            .col_expr(task::Column::Completed, Expr::value(category.is_done()))
+            .filter(task::Column::StatusId.eq(status_id))
+            .exec(&txn)
+            .await?;
+        status.category = Set(category);
+    }
+
+    if let Some(color) = request.color {
+        status.color = Set(Some(color));
+    }
+
+    if let Some(position) = request.position {
+        status.position = Set(position);
+    }
+
+    status.updated_at = Set(chrono::Utc::now());
+    let status = status.update(&txn).await?;
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskStatus,
+            status.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&status))),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(TaskStatusResponse::from(status))
+}
+
+/// Deletes a status. Tasks still using it must be moved somewhere first,
+/// either by the client or by passing `replacement_status_id`.
+pub async fn delete_task_status(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    status_id: Uuid,
+    replacement_status_id: Option<Uuid>,
+) -> AppResult<bool> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let status = TaskStatus::find_by_id(status_id)
+        .filter(task_status::Column::WorkspaceId.eq(workspace.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Status not found".to_string()))?;
+
+    let txn = db.begin().await?;
+
+    let in_use = Task::find()
+        .filter(task::Column::StatusId.eq(status.id))
+        .count(&txn)
+        .await?;
+
+    if in_use > 0 {
+        let replacement_id = replacement_status_id.ok_or_else(|| {
+            AppError::BadRequest(
+                "Status is still used by tasks; provide a replacement status".to_string(),
+            )
+        })?;
+        if replacement_id == status.id {
+            return Err(AppError::BadRequest(
+                "Replacement status must be a different status".to_string(),
+            ));
+        }
+        let replacement = find_workspace_task_status(&txn, workspace.id, replacement_id).await?;
+
+        Task::update_many()
+            .col_expr(task::Column::StatusId, Expr::value(replacement.id))
+            .col_expr(
+                task::Column::Completed,
+                Expr::value(replacement.category.is_done()),
+            )
+            .filter(task::Column::StatusId.eq(status.id))
+            .exec(&txn)
+            .await?;
+    }
+
+    TaskStatus::delete_by_id(status.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskStatus,
+            status.id,
+            ActivityAction::Deleted,
+        )
+        .with_changes(field_changes(Some(&status), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(true)
+}
+
+/// The workspace's transitions as sorted `(from, to)` pairs.
+async fn task_status_transition_pairs<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<Vec<(Uuid, Uuid)>> {
+    let mut pairs: Vec<(Uuid, Uuid)> = TaskStatusTransition::find()
+        .filter(task_status_transition::Column::WorkspaceId.eq(workspace_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| (t.from_status_id, t.to_status_id))
+        .collect();
+    pairs.sort();
+    Ok(pairs)
+}
+
+pub async fn list_task_status_transitions(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<TaskStatusTransitionResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let transitions = TaskStatusTransition::find()
+        .filter(task_status_transition::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(task_status_transition::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(transitions
+        .into_iter()
+        .map(TaskStatusTransitionResponse::from)
+        .collect())
+}
+
+/// Replaces the workspace's allowed transitions. An empty list removes all
+/// restrictions.
+pub async fn set_task_status_transitions(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: SetTaskStatusTransitionsRequest,
+) -> AppResult<Vec<TaskStatusTransitionResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let status_ids: HashSet<Uuid> = TaskStatus::find()
+        .filter(task_status::Column::WorkspaceId.eq(workspace.id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut transitions = Vec::new();
+    for transition in request.transitions {
+        if !status_ids.contains(&transition.from_status_id)
+            || !status_ids.contains(&transition.to_status_id)
+        {
+            return Err(AppError::BadRequest(
+                "Transition references a status outside this workspace".to_string(),
+            ));
+        }
+        if transition.from_status_id == transition.to_status_id || !seen.insert(transition) {
+            continue;
+        }
+        transitions.push(task_status_transition::ActiveModel {
+            workspace_id: Set(workspace.id),
+            from_status_id: Set(transition.from_status_id),
+            to_status_id: Set(transition.to_status_id),
+            ..Default::default()
+        });
+    }
+
+    let txn = db.begin().await?;
+    let before = task_status_transition_pairs(&txn, workspace.id).await?;
+
+    TaskStatusTransition::delete_many()
+        .filter(task_status_transition::Column::WorkspaceId.eq(workspace.id))
+        .exec(&txn)
+        .await?;
+
+    if !transitions.is_empty() {
+        TaskStatusTransition::insert_many(transitions)
+            .exec(&txn)
+            .await?;
+    }
+
+    let after = task_status_transition_pairs(&txn, workspace.id).await?;
+    if before != after {
+        record_activity(
+            &txn,
+            NewActivity::new(
+                workspace.id,
+                user_id,
+                ActivityEntity::Workspace,
+                workspace.id,
+                ActivityAction::Updated,
+            )
+            .with_changes(field_change("status_transitions", before, after)),
+        )
+        .await?;
+    }
+
+    txn.commit().await?;
+
+    list_task_status_transitions(db, user_id, workspace.id).await
+}
@@ -0,0 +1,398 @@
+use crate::cache::Cache;
+use crate::database::{
+    field_changes, find_owned_workspace, invalidate_tasks_cache, record_activity, NewActivity,
+    TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    task_type::{self, Entity as TaskType},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Types every new workspace starts with, matching the values of the former
+/// `task_type` enum: (name, icon, color, default duration in minutes).
+const DEFAULT_TASK_TYPES: [(&str, &str, &str, Option<i32>); 5] = [
+    ("Work", "briefcase", "#3b82f6", Some(60)),
+    ("Personal", "user", "#22c55e", Some(30)),
+    ("Meeting", "users", "#a855f7", Some(30)),
+    ("Deadline", "flag", "#ef4444", None),
+    ("Event", "calendar", "#f59e0b", Some(60)),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTaskTypeRequest {
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: String,
+    pub default_duration_minutes: Option<i32>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTaskTypeRequest {
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub default_duration_minutes: Option<i32>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskTypeResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: String,
+    pub default_duration_minutes: Option<i32>,
+    pub position: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<task_type::Model> for TaskTypeResponse {
+    fn from(task_type: task_type::Model) -> Self {
+        Self {
+            id: task_type.id,
+            workspace_id: task_type.workspace_id,
+            name: task_type.name,
+            icon: task_type.icon,
+            color: task_type.color,
+            default_duration_minutes: task_type.default_duration_minutes,
+            position: task_type.position,
+            created_at: task_type.created_at,
+            updated_at: task_type.updated_at,
+        }
+    }
+}
+
+/// Fills the derived `task_type` name on the given responses with a single
+/// query.
+pub async fn attach_task_type_names<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    let ids: HashSet<Uuid> = tasks.iter().filter_map(|t| t.task_type_id).collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let names: HashMap<Uuid, String> = TaskType::find()
+        .filter(task_type::Column::Id.is_in(ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|task_type| (task_type.id, task_type.name.to_lowercase()))
+        .collect();
+
+    for task in tasks.iter_mut() {
+        task.task_type = task.task_type_id.and_then(|id| names.get(&id).cloned());
+    }
+
+    Ok(())
+}
+
+async fn find_workspace_task_types<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<Vec<task_type::Model>> {
+    let task_types = TaskType::find()
+        .filter(task_type::Column::WorkspaceId.eq(workspace_id))
+        .order_by_asc(task_type::Column::Position)
+        .order_by_asc(task_type::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(task_types)
+}
+
+fn validate_task_type_fields(name: Option<&str>, duration: Option<i32>) -> AppResult<()> {
+    if name.is_some_and(|name| name.trim().is_empty()) {
+        return Err(AppError::Validation("Task type name is required".to_string()));
+    }
+
+    if duration.is_some_and(|minutes| minutes <= 0) {
+        return Err(AppError::Validation(
+            "Default duration must be a positive number of minutes".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn check_task_type_name_available<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    name: &str,
+    task_type_id: Option<Uuid>,
+) -> AppResult<()> {
+    let taken = find_workspace_task_types(db, workspace_id)
+        .await?
+        .into_iter()
+        .any(|t| Some(t.id) != task_type_id && t.name.to_lowercase() == name.to_lowercase());
+
+    if taken {
+        return Err(AppError::Conflict(format!(
+            "A task type named \"{}\" already exists in this workspace",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn seed_default_task_types<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+) -> AppResult<Vec<task_type::Model>> {
+    let now = chrono::Utc::now();
+    let task_types: Vec<task_type::Model> = DEFAULT_TASK_TYPES
+        .iter()
+        .enumerate()
+        .map(|(position, (name, icon, color, duration))| task_type::Model {
+            id: Uuid::new_v4(),
+            workspace_id,
+            name: name.to_string(),
+            icon: Some(icon.to_string()),
+            color: color.to_string(),
+            default_duration_minutes: *duration,
+            position: position as i32,
+            created_at: now,
+            updated_at: now,
+        })
+        .collect();
+
+    TaskType::insert_many(
+        task_types
+            .iter()
+            .cloned()
+            .map(task_type::ActiveModel::from),
+    )
+    .exec(db)
+    .await?;
+
+    Ok(task_types)
+}
+
+/// Looks up the type a task should get, either by ID or (for clients that
+/// still send the old enum values) by case-insensitive name.
+pub async fn resolve_task_type<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    task_type_id: Option<Uuid>,
+    name: Option<&str>,
+) -> AppResult<Option<task_type::Model>> {
+    if let Some(task_type_id) = task_type_id {
+        let task_type = TaskType::find_by_id(task_type_id)
+            .filter(task_type::Column::WorkspaceId.eq(workspace_id))
+            .one(db)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest("Task type does not belong to this workspace".to_string())
+            })?;
+        return Ok(Some(task_type));
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+
+    find_workspace_task_types(db, workspace_id)
+        .await?
+        .into_iter()
+        .find(|t| t.name.to_lowercase() == name.to_lowercase())
+        .map(Some)
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid task type: {}", name)))
+}
+
+/// Resolves the values of a `task_type=a,b` filter, given as type IDs or
+/// names, to the matching type IDs of the workspace. Unknown values are
+/// ignored.
+pub async fn resolve_task_type_filter<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    references: &[String],
+) -> AppResult<Vec<Uuid>> {
+    let task_types = find_workspace_task_types(db, workspace_id).await?;
+
+    Ok(task_types
+        .into_iter()
+        .filter(|t| {
+            references.iter().any(|reference| {
+                Uuid::parse_str(reference).is_ok_and(|id| id == t.id)
+                    || t.name.to_lowercase() == reference.to_lowercase()
+            })
+        })
+        .map(|t| t.id)
+        .collect())
+}
+
+pub async fn list_task_types(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<TaskTypeResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    get_task_types_by_workspace(db, workspace.id).await
+}
+
+pub async fn get_task_types_by_workspace(
+    db: &DatabaseConnection,
+    workspace_id: Uuid,
+) -> AppResult<Vec<TaskTypeResponse>> {
+    let task_types = find_workspace_task_types(db, workspace_id).await?;
+    Ok(task_types.into_iter().map(TaskTypeResponse::from).collect())
+}
+
+pub async fn create_task_type(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateTaskTypeRequest,
+) -> AppResult<TaskTypeResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    validate_task_type_fields(Some(&request.name), request.default_duration_minutes)?;
+    check_task_type_name_available(db, workspace.id, &request.name, None).await?;
+
+    let position = match request.position {
+        Some(position) => position,
+        None => TaskType::find()
+            .filter(task_type::Column::WorkspaceId.eq(workspace.id))
+            .order_by_desc(task_type::Column::Position)
+            .one(db)
+            .await?
+            .map_or(0, |last| last.position + 1),
+    };
+
+    let task_type = task_type::ActiveModel {
+        workspace_id: Set(workspace.id),
+        name: Set(request.name),
+        icon: Set(request.icon),
+        color: Set(request.color),
+        default_duration_minutes: Set(request.default_duration_minutes),
+        position: Set(position),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let task_type = task_type.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskType,
+            task_type.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&task_type))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(TaskTypeResponse::from(task_type))
+}
+
+pub async fn update_task_type(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task_type_id: Uuid,
+    request: UpdateTaskTypeRequest,
+) -> AppResult<TaskTypeResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let task_type = TaskType::find_by_id(task_type_id)
+        .filter(task_type::Column::WorkspaceId.eq(workspace.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task type not found".to_string()))?;
+
+    validate_task_type_fields(request.name.as_deref(), request.default_duration_minutes)?;
+    if let Some(name) = &request.name {
+        check_task_type_name_available(db, workspace.id, name, Some(task_type.id)).await?;
+    }
+
+    let before = task_type.clone();
+    let mut task_type: task_type::ActiveModel = task_type.into();
+
+    if let Some(name) = request.name {
+        task_type.name = Set(name);
+    }
+
+    if let Some(icon) = request.icon {
+        task_type.icon = Set(Some(icon));
+    }
+
+    if let Some(color) = request.color {
+        task_type.color = Set(color);
+    }
+
+    if let Some(duration) = request.default_duration_minutes {
+        task_type.default_duration_minutes = Set(Some(duration));
+    }
+
+    if let Some(position) = request.position {
+        task_type.position = Set(position);
+    }
+
+    task_type.updated_at = Set(chrono::Utc::now());
+    let txn = db.begin().await?;
+    let task_type = task_type.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskType,
+            task_type.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&task_type))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(TaskTypeResponse::from(task_type))
+}
+
+/// Deletes a task type. Tasks that used it are left without a type.
+pub async fn delete_task_type(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    task_type_id: Uuid,
+) -> AppResult<bool> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    let task_type = TaskType::find_by_id(task_type_id)
+        .filter(task_type::Column::WorkspaceId.eq(workspace.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Task type not found".to_string()))?;
+
+    let txn = db.begin().await?;
+    TaskType::delete_by_id(task_type.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::TaskType,
+            task_type.id,
+            ActivityAction::Deleted,
+        )
+        .with_changes(field_changes(Some(&task_type), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(true)
+}
@@ -0,0 +1,524 @@
+use crate::cache::Cache;
+use crate::database::{
+    field_changes, find_owned_task, find_owned_workspace, invalidate_tasks_cache, record_activity,
+    task_workspace_id, NewActivity, TaskResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    task::{self, Entity as Task},
+    task_type::{self, Entity as TaskType},
+    time_entry::{self, Entity as TimeEntry},
+    topic::{self, Entity as Topic},
+    user::{self, Entity as User},
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTimeEntryRequest {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub ended_at: chrono::DateTime<chrono::Utc>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StartTimerRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTimeEntryRequest {
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Setting this on a running entry stops it.
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeEntryResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `None` while the timer is running.
+    pub duration_seconds: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<time_entry::Model> for TimeEntryResponse {
+    fn from(entry: time_entry::Model) -> Self {
+        Self {
+            id: entry.id,
+            task_id: entry.task_id,
+            user_id: entry.user_id,
+            started_at: entry.started_at,
+            ended_at: entry.ended_at,
+            duration_seconds: entry
+                .ended_at
+                .map(|ended_at| (ended_at - entry.started_at).num_seconds()),
+            note: entry.note,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+/// Tracked time of one topic, task type or user in a report. `id` is `None`
+/// for the tasks without a task type.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeReportRow {
+    pub id: Option<Uuid>,
+    pub name: Option<String>,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeReportResponse {
+    pub workspace_id: Uuid,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub total_seconds: i64,
+    pub by_topic: Vec<TimeReportRow>,
+    pub by_task_type: Vec<TimeReportRow>,
+    pub by_user: Vec<TimeReportRow>,
+}
+
+fn validate_time_range(
+    started_at: chrono::DateTime<chrono::Utc>,
+    ended_at: chrono::DateTime<chrono::Utc>,
+) -> AppResult<()> {
+    if ended_at < started_at {
+        return Err(AppError::Validation(
+            "A time entry cannot end before it starts".to_string(),
+        ));
+    }
+
+    if ended_at > chrono::Utc::now() {
+        return Err(AppError::Validation(
+            "A time entry cannot end in the future".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Normalizes an optional note, treating a blank one as no note.
+fn clean_note(note: Option<String>) -> Option<String> {
+    note.map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty())
+}
+
+/// Loads a time entry of the user on a live task of one of their workspaces.
+/// Entries of other users can be seen through the task but not changed.
+/// Loads one of the user's own time entries, along with the ID of the
+/// workspace it is in.
+async fn find_own_time_entry<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    entry_id: Uuid,
+) -> AppResult<(time_entry::Model, Uuid)> {
+    let entry = TimeEntry::find_by_id(entry_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Time entry not found".to_string()))?;
+
+    let (_, topic) = find_owned_task(db, user_id, entry.task_id)
+        .await
+        .map_err(|_| AppError::NotFound("Time entry not found".to_string()))?;
+
+    if entry.user_id != user_id {
+        return Err(AppError::Forbidden(
+            "Only the user who tracked the time can change it".to_string(),
+        ));
+    }
+
+    Ok((entry, topic.workspace_id))
+}
+
+fn time_entry_activity(
+    workspace_id: Uuid,
+    user_id: Uuid,
+    entry: &time_entry::Model,
+    action: ActivityAction,
+) -> NewActivity {
+    NewActivity::new(
+        workspace_id,
+        user_id,
+        ActivityEntity::TimeEntry,
+        entry.id,
+        action,
+    )
+    .on_task(entry.task_id)
+}
+
+async fn find_running_entry<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+) -> AppResult<Option<time_entry::Model>> {
+    Ok(TimeEntry::find()
+        .filter(time_entry::Column::UserId.eq(user_id))
+        .filter(time_entry::Column::EndedAt.is_null())
+        .one(db)
+        .await?)
+}
+
+/// Fills `tracked_seconds` on the given responses with a single query.
+/// Running timers are not counted until they are stopped.
+pub async fn attach_task_time_tracked<C: ConnectionTrait>(
+    db: &C,
+    tasks: &mut [TaskResponse],
+) -> AppResult<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let ids: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let totals: HashMap<Uuid, i64> = TimeEntry::find()
+        .select_only()
+        .column(time_entry::Column::TaskId)
+        .column_as(
+            Expr::cust("CAST(SUM(EXTRACT(EPOCH FROM (ended_at - started_at))) AS BIGINT)"),
+            "seconds",
+        )
+        .filter(time_entry::Column::TaskId.is_in(ids))
+        .filter(time_entry::Column::EndedAt.is_not_null())
+        .group_by(time_entry::Column::TaskId)
+        .into_tuple::<(Uuid, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    for task in tasks.iter_mut() {
+        task.tracked_seconds = totals.get(&task.id).copied().unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+pub async fn get_time_entries_by_task(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> AppResult<Vec<TimeEntryResponse>> {
+    let (task, _) = find_owned_task(db, user_id, task_id).await?;
+
+    let entries = TimeEntry::find()
+        .filter(time_entry::Column::TaskId.eq(task.id))
+        .order_by_desc(time_entry::Column::StartedAt)
+        .all(db)
+        .await?;
+
+    Ok(entries.into_iter().map(TimeEntryResponse::from).collect())
+}
+
+/// Records time worked on a task after the fact.
+pub async fn create_time_entry(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: CreateTimeEntryRequest,
+) -> AppResult<TimeEntryResponse> {
+    validate_time_range(request.started_at, request.ended_at)?;
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+
+    let entry = time_entry::ActiveModel {
+        task_id: Set(task.id),
+        user_id: Set(user_id),
+        started_at: Set(request.started_at),
+        ended_at: Set(Some(request.ended_at)),
+        note: Set(clean_note(request.note)),
+        ..Default::default()
+    };
+    let txn = db.begin().await?;
+    let entry = entry.insert(&txn).await?;
+    record_activity(
+        &txn,
+        time_entry_activity(topic.workspace_id, user_id, &entry, ActivityAction::Created)
+            .with_changes(field_changes(None, Some(&entry))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(TimeEntryResponse::from(entry))
+}
+
+/// Starts a timer on a task. A user can only have one timer running; the
+/// partial unique index on `time_entries` backs this up against races.
+pub async fn start_timer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+    request: StartTimerRequest,
+) -> AppResult<TimeEntryResponse> {
+    let (task, topic) = find_owned_task(db, user_id, task_id).await?;
+
+    if let Some(running) = find_running_entry(db, user_id).await? {
+        return Err(AppError::Conflict(format!(
+            "A timer is already running on task {}",
+            running.task_id
+        )));
+    }
+
+    let entry = time_entry::ActiveModel {
+        task_id: Set(task.id),
+        user_id: Set(user_id),
+        started_at: Set(chrono::Utc::now()),
+        ended_at: Set(None),
+        note: Set(clean_note(request.note)),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let entry = match entry.insert(&txn).await {
+        Ok(entry) => entry,
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            return Err(AppError::Conflict("A timer is already running".to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    record_activity(
+        &txn,
+        time_entry_activity(topic.workspace_id, user_id, &entry, ActivityAction::Created)
+            .with_changes(field_changes(None, Some(&entry))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(TimeEntryResponse::from(entry))
+}
+
+pub async fn get_running_timer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> AppResult<Option<TimeEntryResponse>> {
+    let entry = find_running_entry(db, user_id).await?;
+    Ok(entry.map(TimeEntryResponse::from))
+}
+
+pub async fn stop_timer(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+) -> AppResult<TimeEntryResponse> {
+    let entry = find_running_entry(db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No timer is running".to_string()))?;
+
+    let workspace_id = task_workspace_id(db, entry.task_id).await?;
+
+    let now = chrono::Utc::now();
+    let before = entry.clone();
+    let mut entry: time_entry::ActiveModel = entry.into();
+    entry.ended_at = Set(Some(now));
+    entry.updated_at = Set(now);
+
+    let txn = db.begin().await?;
+    let entry = entry.update(&txn).await?;
+    record_activity(
+        &txn,
+        time_entry_activity(workspace_id, user_id, &entry, ActivityAction::Updated)
+            .with_changes(field_changes(Some(&before), Some(&entry))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(TimeEntryResponse::from(entry))
+}
+
+pub async fn update_time_entry(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    entry_id: Uuid,
+    request: UpdateTimeEntryRequest,
+) -> AppResult<TimeEntryResponse> {
+    let (entry, workspace_id) = find_own_time_entry(db, user_id, entry_id).await?;
+
+    let started_at = request.started_at.unwrap_or(entry.started_at);
+    let ended_at = request.ended_at.or(entry.ended_at);
+    match ended_at {
+        Some(ended_at) => validate_time_range(started_at, ended_at)?,
+        None if started_at > chrono::Utc::now() => {
+            return Err(AppError::Validation(
+                "A running timer cannot start in the future".to_string(),
+            ));
+        }
+        None => {}
+    }
+
+    let before = entry.clone();
+    let mut entry: time_entry::ActiveModel = entry.into();
+    entry.started_at = Set(started_at);
+    entry.ended_at = Set(ended_at);
+    if request.note.is_some() {
+        entry.note = Set(clean_note(request.note));
+    }
+    entry.updated_at = Set(chrono::Utc::now());
+
+    let txn = db.begin().await?;
+    let entry = entry.update(&txn).await?;
+    record_activity(
+        &txn,
+        time_entry_activity(workspace_id, user_id, &entry, ActivityAction::Updated)
+            .with_changes(field_changes(Some(&before), Some(&entry))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(TimeEntryResponse::from(entry))
+}
+
+pub async fn delete_time_entry(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    entry_id: Uuid,
+) -> AppResult<bool> {
+    let (entry, workspace_id) = find_own_time_entry(db, user_id, entry_id).await?;
+
+    let txn = db.begin().await?;
+    TimeEntry::delete_by_id(entry.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        time_entry_activity(workspace_id, user_id, &entry, ActivityAction::Deleted)
+            .with_changes(field_changes(Some(&entry), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    invalidate_tasks_cache(cache, user_id).await;
+
+    Ok(true)
+}
+
+fn report_rows(
+    totals: HashMap<Option<Uuid>, i64>,
+    names: &HashMap<Uuid, String>,
+) -> Vec<TimeReportRow> {
+    let mut rows: Vec<TimeReportRow> = totals
+        .into_iter()
+        .map(|(id, seconds)| TimeReportRow {
+            id,
+            name: id.and_then(|id| names.get(&id).cloned()),
+            seconds,
+        })
+        .collect();
+    rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.name.cmp(&b.name)));
+    rows
+}
+
+/// Aggregates the time tracked on a workspace's tasks between `from` and
+/// `to` by topic, task type and user. Entries overlapping the range only
+/// count with the part inside it; running timers count up to now.
+pub async fn get_time_report(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> AppResult<TimeReportResponse> {
+    if to <= from {
+        return Err(AppError::Validation(
+            "The end of the report range must be after its start".to_string(),
+        ));
+    }
+
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let entries = TimeEntry::find()
+        .select_only()
+        .column(time_entry::Column::UserId)
+        .column(task::Column::TopicId)
+        .column(task::Column::TaskTypeId)
+        .column(time_entry::Column::StartedAt)
+        .column(time_entry::Column::EndedAt)
+        .inner_join(Task)
+        .join(JoinType::InnerJoin, task::Relation::Topic.def())
+        .filter(topic::Column::WorkspaceId.eq(workspace.id))
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_null())
+        .filter(time_entry::Column::StartedAt.lt(to))
+        .filter(
+            Condition::any()
+                .add(time_entry::Column::EndedAt.is_null())
+                .add(time_entry::Column::EndedAt.gt(from)),
+        )
+        .into_tuple::<(
+            Uuid,
+            Uuid,
+            Option<Uuid>,
+            chrono::DateTime<chrono::Utc>,
+            Option<chrono::DateTime<chrono::Utc>>,
+        )>()
+        .all(db)
+        .await?;
+
+    let now = chrono::Utc::now();
+    let mut total_seconds = 0;
+    let mut by_topic: HashMap<Option<Uuid>, i64> = HashMap::new();
+    let mut by_task_type: HashMap<Option<Uuid>, i64> = HashMap::new();
+    let mut by_user: HashMap<Option<Uuid>, i64> = HashMap::new();
+
+    for (entry_user_id, topic_id, task_type_id, started_at, ended_at) in entries {
+        let start = started_at.max(from);
+        let end = ended_at.unwrap_or(now).min(to);
+        let seconds = (end - start).num_seconds();
+        if seconds <= 0 {
+            continue;
+        }
+
+        total_seconds += seconds;
+        *by_topic.entry(Some(topic_id)).or_default() += seconds;
+        *by_task_type.entry(task_type_id).or_default() += seconds;
+        *by_user.entry(Some(entry_user_id)).or_default() += seconds;
+    }
+
+    let topic_names: HashMap<Uuid, String> = Topic::find()
+        .filter(topic::Column::Id.is_in(by_topic.keys().flatten().copied()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|topic| (topic.id, topic.name))
+        .collect();
+
+    let task_type_names: HashMap<Uuid, String> = TaskType::find()
+        .filter(task_type::Column::Id.is_in(by_task_type.keys().flatten().copied()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|task_type| (task_type.id, task_type.name))
+        .collect();
+
+    let usernames: HashMap<Uuid, String> = User::find()
+        .filter(user::Column::Id.is_in(by_user.keys().flatten().copied()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.username))
+        .collect();
+
+    Ok(TimeReportResponse {
+        workspace_id: workspace.id,
+        from,
+        to,
+        total_seconds,
+        by_topic: report_rows(by_topic, &topic_names),
+        by_task_type: report_rows(by_task_type, &task_type_names),
+        by_user: report_rows(by_user, &usernames),
+    })
+}
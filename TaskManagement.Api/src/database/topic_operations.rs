@@ -1,6 +1,13 @@
-use crate::database::resolve_workspace_id;
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::database::{
+    attach_topic_effort, check_task_quota, check_topic_quota, field_changes, issue_undo_token,
+    record_activity, resolve_workspace_id, EffortSummary, NewActivity, UndoStep, UndoTokenResponse,
+};
 use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
 use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
     topic::{self, Entity as Topic},
     workspace::{self, Entity as Workspace},
 };
@@ -30,6 +37,11 @@ pub struct TopicResponse {
     pub name: String,
     pub description: Option<String>,
     pub color: String,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Totals over the topic's tasks.
+    #[serde(default)]
+    pub effort: EffortSummary,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -42,6 +54,9 @@ impl From<topic::Model> for TopicResponse {
             name: topic.name,
             description: topic.description,
             color: topic.color,
+            archived_at: topic.archived_at,
+            deleted_at: topic.deleted_at,
+            effort: EffortSummary::default(),
             created_at: topic.created_at,
             updated_at: topic.updated_at,
         }
@@ -50,6 +65,8 @@ impl From<topic::Model> for TopicResponse {
 
 pub async fn create_topic(
     db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
     user_id: Uuid,
     request: CreateTopicRequest,
 ) -> AppResult<TopicResponse> {
@@ -63,24 +80,64 @@ pub async fn create_topic(
         ..Default::default()
     };
 
-    let topic = topic.insert(db).await?;
+    let txn = db.begin().await?;
+    check_topic_quota(&txn, config, user_id, workspace_id, 1).await?;
+    let topic = topic.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Topic,
+            topic.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&topic))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::topic(ChangeAction::Created, user_id, &topic),
+    )
+    .await;
     Ok(TopicResponse::from(topic))
 }
 
+async fn topic_response<C: ConnectionTrait>(
+    db: &C,
+    topic: topic::Model,
+) -> AppResult<TopicResponse> {
+    let mut response = TopicResponse::from(topic);
+    attach_topic_effort(db, std::slice::from_mut(&mut response)).await?;
+    Ok(response)
+}
+
 pub async fn get_topics_by_user(
     db: &DatabaseConnection,
     user_id: Uuid,
     workspace_id: Option<Uuid>,
+    include_archived: bool,
 ) -> AppResult<Vec<TopicResponse>> {
     let resolved_workspace_id = resolve_workspace_id(db, user_id, workspace_id).await?;
 
-    let topics = Topic::find()
+    let mut query = Topic::find()
         .filter(topic::Column::WorkspaceId.eq(resolved_workspace_id))
+        .filter(topic::Column::DeletedAt.is_null());
+
+    if !include_archived {
+        query = query.filter(topic::Column::ArchivedAt.is_null());
+    }
+
+    let topics = query
         .order_by_asc(topic::Column::Name)
         .all(db)
         .await?;
 
-    Ok(topics.into_iter().map(TopicResponse::from).collect())
+    let mut responses: Vec<TopicResponse> = topics.into_iter().map(TopicResponse::from).collect();
+    attach_topic_effort(db, &mut responses).await?;
+    Ok(responses)
 }
 
 pub async fn get_topics_by_workspace(
@@ -89,11 +146,15 @@ pub async fn get_topics_by_workspace(
 ) -> AppResult<Vec<TopicResponse>> {
     let topics = Topic::find()
         .filter(topic::Column::WorkspaceId.eq(workspace_id))
+        .filter(topic::Column::ArchivedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .order_by_asc(topic::Column::Name)
         .all(db)
         .await?;
 
-    Ok(topics.into_iter().map(TopicResponse::from).collect())
+    let mut responses: Vec<TopicResponse> = topics.into_iter().map(TopicResponse::from).collect();
+    attach_topic_effort(db, &mut responses).await?;
+    Ok(responses)
 }
 
 pub async fn get_topic_by_id(
@@ -104,15 +165,18 @@ pub async fn get_topic_by_id(
     let topic = Topic::find_by_id(topic_id)
         .inner_join(Workspace)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
-    Ok(TopicResponse::from(topic))
+    topic_response(db, topic).await
 }
 
 pub async fn update_topic(
     db: &DatabaseConnection,
+    cache: &Cache,
     user_id: Uuid,
     topic_id: Uuid,
     request: UpdateTopicRequest,
@@ -120,10 +184,13 @@ pub async fn update_topic(
     let topic = Topic::find_by_id(topic_id)
         .inner_join(Workspace)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
+    let before = topic.clone();
     let mut topic: topic::ActiveModel = topic.into();
 
     if let Some(name) = request.name {
@@ -138,22 +205,187 @@ pub async fn update_topic(
         topic.color = Set(color);
     }
 
-    let topic = topic.update(db).await?;
-    Ok(TopicResponse::from(topic))
+    let txn = db.begin().await?;
+    let topic = topic.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Topic,
+            topic.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&topic))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::topic(ChangeAction::Updated, user_id, &topic),
+    )
+    .await;
+    topic_response(db, topic).await
 }
 
+/// Moves a topic to the trash. The returned token undoes the deletion
+/// within the undo window.
 pub async fn delete_topic(
     db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
     user_id: Uuid,
     topic_id: Uuid,
-) -> AppResult<bool> {
+) -> AppResult<UndoTokenResponse> {
     let topic = Topic::find_by_id(topic_id)
         .inner_join(Workspace)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
 
-    Topic::delete_by_id(topic.id).exec(db).await?;
-    Ok(true)
+    let now = chrono::Utc::now();
+    let mut topic: topic::ActiveModel = topic.into();
+    topic.deleted_at = Set(Some(now));
+
+    let txn = db.begin().await?;
+    let topic = topic.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Topic,
+            topic.id,
+            ActivityAction::Deleted,
+        ),
+    )
+    .await?;
+    let undo = issue_undo_token(
+        &txn,
+        config,
+        user_id,
+        topic.workspace_id,
+        vec![UndoStep::RestoreTopic {
+            topic_id: topic.id,
+            deleted_at: now,
+        }],
+    )
+    .await?;
+    txn.commit().await?;
+
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::topic(ChangeAction::Deleted, user_id, &topic),
+    )
+    .await;
+    Ok(undo)
+}
+
+pub async fn archive_topic(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    user_id: Uuid,
+    topic_id: Uuid,
+    archived: bool,
+) -> AppResult<TopicResponse> {
+    let topic = Topic::find_by_id(topic_id)
+        .inner_join(Workspace)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Topic not found".to_string()))?;
+
+    let mut topic: topic::ActiveModel = topic.into();
+    topic.archived_at = Set(archived.then(chrono::Utc::now));
+    topic.updated_at = Set(chrono::Utc::now());
+
+    let action = if archived {
+        ActivityAction::Archived
+    } else {
+        ActivityAction::Unarchived
+    };
+
+    let txn = db.begin().await?;
+    let topic = topic.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Topic,
+            topic.id,
+            action,
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::topic(ChangeAction::Updated, user_id, &topic),
+    )
+    .await;
+    topic_response(db, topic).await
+}
+
+pub async fn restore_topic(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
+    user_id: Uuid,
+    topic_id: Uuid,
+) -> AppResult<TopicResponse> {
+    let topic = Topic::find_by_id(topic_id)
+        .inner_join(Workspace)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(topic::Column::DeletedAt.is_not_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Topic not found in trash".to_string()))?;
+
+    let workspace = Workspace::find_by_id(topic.workspace_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+
+    if workspace.deleted_at.is_some() {
+        return Err(AppError::BadRequest(
+            "Restore the topic's workspace first".to_string(),
+        ));
+    }
+
+    let mut topic: topic::ActiveModel = topic.into();
+    topic.deleted_at = Set(None);
+    topic.updated_at = Set(chrono::Utc::now());
+
+    let txn = db.begin().await?;
+    let topic = topic.update(&txn).await?;
+    // The topic's tasks come back with it
+    check_topic_quota(&txn, config, user_id, topic.workspace_id, 0).await?;
+    check_task_quota(&txn, config, user_id, topic.workspace_id, 0).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            topic.workspace_id,
+            user_id,
+            ActivityEntity::Topic,
+            topic.id,
+            ActivityAction::Restored,
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    publish_workspace_event(
+        cache,
+        WorkspaceEvent::topic(ChangeAction::Created, user_id, &topic),
+    )
+    .await;
+    topic_response(db, topic).await
 }
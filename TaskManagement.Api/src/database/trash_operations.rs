@@ -0,0 +1,113 @@
+use crate::database::{TaskResponse, TopicResponse, WorkspaceResponse};
+use crate::errors::AppResult;
+use crate::models::{
+    attachment::{self, Entity as Attachment},
+    task::{self, Entity as Task},
+    topic::{self, Entity as Topic},
+    workspace::{self, Entity as Workspace},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashResponse {
+    pub workspaces: Vec<WorkspaceResponse>,
+    pub topics: Vec<TopicResponse>,
+    pub tasks: Vec<TaskResponse>,
+}
+
+#[derive(Debug)]
+pub struct PurgeSummary {
+    pub tasks: u64,
+    pub topics: u64,
+    pub workspaces: u64,
+    /// Storage keys of the attachments that went with the purged tasks. Their
+    /// blobs are not removed by the database cascade.
+    pub attachment_keys: Vec<String>,
+}
+
+/// Lists the items the user deleted directly. Children of a deleted topic or
+/// workspace are not listed separately; they come back with their parent.
+pub async fn get_trash_by_user(db: &DatabaseConnection, user_id: Uuid) -> AppResult<TrashResponse> {
+    let workspaces = Workspace::find()
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_not_null())
+        .order_by_desc(workspace::Column::DeletedAt)
+        .all(db)
+        .await?;
+
+    let topics = Topic::find()
+        .inner_join(Workspace)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_not_null())
+        .order_by_desc(topic::Column::DeletedAt)
+        .all(db)
+        .await?;
+
+    let tasks = Task::find()
+        .inner_join(Topic)
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(topic::Column::DeletedAt.is_null())
+        .filter(task::Column::DeletedAt.is_not_null())
+        .order_by_desc(task::Column::DeletedAt)
+        .all(db)
+        .await?;
+
+    Ok(TrashResponse {
+        workspaces: workspaces.into_iter().map(WorkspaceResponse::from).collect(),
+        topics: topics.into_iter().map(TopicResponse::from).collect(),
+        tasks: tasks.into_iter().map(TaskResponse::from).collect(),
+    })
+}
+
+/// Hard-deletes everything that has been in the trash for longer than
+/// `retention`. Rows underneath a purged topic or workspace go with it through
+/// the `ON DELETE CASCADE` chain.
+pub async fn purge_deleted_items(
+    db: &DatabaseConnection,
+    retention: chrono::Duration,
+) -> AppResult<PurgeSummary> {
+    let cutoff = chrono::Utc::now() - retention;
+
+    let attachment_keys = Attachment::find()
+        .select_only()
+        .column(attachment::Column::StorageKey)
+        .inner_join(Task)
+        .join(JoinType::InnerJoin, task::Relation::Topic.def())
+        .join(JoinType::InnerJoin, topic::Relation::Workspace.def())
+        .filter(
+            Condition::any()
+                .add(task::Column::DeletedAt.lt(cutoff))
+                .add(topic::Column::DeletedAt.lt(cutoff))
+                .add(workspace::Column::DeletedAt.lt(cutoff)),
+        )
+        .into_tuple::<String>()
+        .all(db)
+        .await?;
+
+    let tasks = Task::delete_many()
+        .filter(task::Column::DeletedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+
+    let topics = Topic::delete_many()
+        .filter(topic::Column::DeletedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+
+    let workspaces = Workspace::delete_many()
+        .filter(workspace::Column::DeletedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+
+    Ok(PurgeSummary {
+        tasks: tasks.rows_affected,
+        topics: topics.rows_affected,
+        workspaces: workspaces.rows_affected,
+        attachment_keys,
+    })
+}
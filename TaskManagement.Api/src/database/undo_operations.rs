@@ -0,0 +1,464 @@
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::database::{
+    change_task_labels, check_substep_quota, check_task_quota, check_topic_quota, field_changes,
+    find_owned_workspace, invalidate_substeps_cache, invalidate_tasks_cache, record_activity,
+    NewActivity, TaskLabelChange,
+};
+use crate::errors::{AppError, AppResult};
+use crate::events::{publish_workspace_event, ChangeAction, WorkspaceEvent};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    label::{self, Entity as Label},
+    task::{self, Entity as Task},
+    task_substep::{self, Entity as TaskSubstep},
+    topic::{self, Entity as Topic},
+    undo_token::{self, Entity as UndoToken},
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// One part of reversing a destructive operation. Trashed rows are only
+/// stamped with `deleted_at`, so their substeps, recurrence exceptions and
+/// other dependent rows are still in place and come back with them; rows
+/// that were removed outright are kept as a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoStep {
+    /// Takes tasks that were trashed together back out of the trash.
+    RestoreTasks {
+        task_ids: Vec<Uuid>,
+        deleted_at: chrono::DateTime<chrono::Utc>,
+    },
+    /// Takes a topic back out of the trash.
+    RestoreTopic {
+        topic_id: Uuid,
+        deleted_at: chrono::DateTime<chrono::Utc>,
+    },
+    /// Inserts deleted substeps again, with their original IDs and ranks.
+    ReinsertSubsteps { substeps: Vec<task_substep::Model> },
+    /// Puts changed tasks back the way they were, unless they have been
+    /// changed again since.
+    RevertTasks { tasks: Vec<TaskRevision> },
+    /// Gives tasks back their former labels, keyed by task ID.
+    RestoreTaskLabels { label_ids: HashMap<Uuid, Vec<Uuid>> },
+}
+
+/// A task before and after the operation to undo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRevision {
+    pub before: task::Model,
+    pub after: task::Model,
+}
+
+/// Returned by operations that can be undone.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoTokenResponse {
+    /// Redeem with `POST /api/undo/<token>`.
+    pub undo_token: String,
+    pub undo_expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UndoResponse {
+    pub restored_task_ids: Vec<Uuid>,
+    pub restored_topic_ids: Vec<Uuid>,
+    pub restored_substep_ids: Vec<Uuid>,
+    /// Tasks whose fields or labels were put back.
+    pub reverted_task_ids: Vec<Uuid>,
+}
+
+/// Rows brought back by an undo, for cache invalidation and events once
+/// it has committed.
+#[derive(Default)]
+struct UndoneRows {
+    tasks: Vec<task::Model>,
+    topics: Vec<topic::Model>,
+    substeps: Vec<task_substep::Model>,
+    reverted: Vec<task::Model>,
+}
+
+/// Stores the steps that reverse an operation and hands out a token for
+/// them, valid for the configured undo window. Call this inside the
+/// operation's transaction so the token only exists if the operation does.
+pub async fn issue_undo_token<C: ConnectionTrait>(
+    db: &C,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    steps: Vec<UndoStep>,
+) -> AppResult<UndoTokenResponse> {
+    let steps = serde_json::to_value(&steps)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize undo steps: {}", e)))?;
+    let expires_at =
+        chrono::Utc::now() + chrono::Duration::seconds(config.undo_window_seconds.max(1));
+
+    let undo = undo_token::ActiveModel {
+        token: Set(format!("undo_{}", Uuid::new_v4().simple())),
+        user_id: Set(user_id),
+        workspace_id: Set(workspace_id),
+        steps: Set(steps),
+        expires_at: Set(expires_at),
+        used_at: Set(None),
+        ..Default::default()
+    };
+    let undo = undo.insert(db).await?;
+
+    Ok(UndoTokenResponse {
+        undo_token: undo.token,
+        undo_expires_at: undo.expires_at,
+    })
+}
+
+/// Reverses the operation behind `token` in one transaction: either every
+/// step applies or nothing does. A token can be redeemed once, by the user
+/// it was issued to, before it expires.
+pub async fn undo_operation(
+    db: &DatabaseConnection,
+    cache: &Cache,
+    config: &Config,
+    user_id: Uuid,
+    token: &str,
+) -> AppResult<UndoResponse> {
+    let txn = db.begin().await?;
+
+    let undo = UndoToken::find()
+        .filter(undo_token::Column::Token.eq(token))
+        .filter(undo_token::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Undo token not found".to_string()))?;
+
+    if undo.used_at.is_some() {
+        return Err(AppError::Conflict(
+            "This operation has already been undone".to_string(),
+        ));
+    }
+    if undo.expires_at <= chrono::Utc::now() {
+        return Err(AppError::BadRequest("Undo token has expired".to_string()));
+    }
+
+    find_owned_workspace(&txn, user_id, undo.workspace_id).await?;
+
+    let steps: Vec<UndoStep> = serde_json::from_value(undo.steps.clone())
+        .map_err(|e| AppError::Internal(format!("Malformed undo steps: {}", e)))?;
+
+    let mut undone = UndoneRows::default();
+    // Later steps may depend on earlier ones having been applied, so they
+    // are reversed last to first
+    for step in steps.into_iter().rev() {
+        apply_undo_step(&txn, user_id, undo.workspace_id, step, &mut undone).await?;
+    }
+
+    let workspace_id = undo.workspace_id;
+    let substep_task_ids: HashSet<Uuid> = undone.substeps.iter().map(|s| s.task_id).collect();
+
+    // Rows brought back count towards the limits again
+    if !undone.topics.is_empty() {
+        check_topic_quota(&txn, config, user_id, workspace_id, 0).await?;
+    }
+    if !undone.tasks.is_empty() || !undone.topics.is_empty() {
+        check_task_quota(&txn, config, user_id, workspace_id, 0).await?;
+    }
+    for task_id in &substep_task_ids {
+        check_substep_quota(&txn, config, *task_id, 0).await?;
+    }
+
+    let mut undo: undo_token::ActiveModel = undo.into();
+    undo.used_at = Set(Some(chrono::Utc::now()));
+    undo.update(&txn).await?;
+
+    txn.commit().await?;
+
+    if !undone.tasks.is_empty() || !undone.topics.is_empty() || !undone.reverted.is_empty() {
+        invalidate_tasks_cache(cache, user_id).await;
+    }
+    for task_id in substep_task_ids {
+        invalidate_substeps_cache(cache, user_id, task_id).await;
+    }
+
+    // Restored rows reappear in lists just like new ones
+    for topic in &undone.topics {
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::topic(ChangeAction::Created, user_id, topic),
+        )
+        .await;
+    }
+    for task in &undone.tasks {
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::task(ChangeAction::Created, user_id, workspace_id, task),
+        )
+        .await;
+    }
+    for task in &undone.reverted {
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::task(ChangeAction::Updated, user_id, workspace_id, task),
+        )
+        .await;
+    }
+    for substep in &undone.substeps {
+        publish_workspace_event(
+            cache,
+            WorkspaceEvent::substep(
+                ChangeAction::Created,
+                user_id,
+                workspace_id,
+                substep.task_id,
+                substep.id,
+            ),
+        )
+        .await;
+    }
+
+    Ok(UndoResponse {
+        restored_task_ids: undone.tasks.iter().map(|t| t.id).collect(),
+        restored_topic_ids: undone.topics.iter().map(|t| t.id).collect(),
+        restored_substep_ids: undone.substeps.iter().map(|s| s.id).collect(),
+        reverted_task_ids: undone.reverted.iter().map(|t| t.id).collect(),
+    })
+}
+
+async fn apply_undo_step<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    step: UndoStep,
+    undone: &mut UndoneRows,
+) -> AppResult<()> {
+    match step {
+        UndoStep::RestoreTasks {
+            task_ids,
+            deleted_at,
+        } => {
+            // Tasks restored or purged since would be brought back twice or
+            // not at all, so the trash must still hold exactly this batch
+            let tasks = Task::find()
+                .filter(task::Column::Id.is_in(task_ids.clone()))
+                .filter(task::Column::DeletedAt.eq(deleted_at))
+                .all(db)
+                .await?;
+            if tasks.len() != task_ids.len() {
+                return Err(AppError::Conflict(
+                    "The deleted tasks have changed since; restore them from the trash instead"
+                        .to_string(),
+                ));
+            }
+
+            let topic_ids: HashSet<Uuid> = tasks.iter().map(|t| t.topic_id).collect();
+            let trashed_topics = Topic::find()
+                .filter(topic::Column::Id.is_in(topic_ids))
+                .filter(topic::Column::DeletedAt.is_not_null())
+                .count(db)
+                .await?;
+            if trashed_topics > 0 {
+                return Err(AppError::BadRequest(
+                    "Restore the task's topic first".to_string(),
+                ));
+            }
+
+            Task::update_many()
+                .col_expr(
+                    task::Column::DeletedAt,
+                    Expr::value(Option::<chrono::DateTime<chrono::Utc>>::None),
+                )
+                .filter(task::Column::Id.is_in(task_ids))
+                .exec(db)
+                .await?;
+
+            for mut task in tasks {
+                record_activity(
+                    db,
+                    NewActivity::new(
+                        workspace_id,
+                        user_id,
+                        ActivityEntity::Task,
+                        task.id,
+                        ActivityAction::Restored,
+                    ),
+                )
+                .await?;
+                task.deleted_at = None;
+                undone.tasks.push(task);
+            }
+        }
+        UndoStep::RestoreTopic {
+            topic_id,
+            deleted_at,
+        } => {
+            let topic = Topic::find_by_id(topic_id)
+                .filter(topic::Column::DeletedAt.eq(deleted_at))
+                .one(db)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Conflict(
+                        "The deleted topic has changed since; restore it from the trash instead"
+                            .to_string(),
+                    )
+                })?;
+
+            let mut topic: topic::ActiveModel = topic.into();
+            topic.deleted_at = Set(None);
+            topic.updated_at = Set(chrono::Utc::now());
+            let topic = topic.update(db).await?;
+
+            record_activity(
+                db,
+                NewActivity::new(
+                    workspace_id,
+                    user_id,
+                    ActivityEntity::Topic,
+                    topic.id,
+                    ActivityAction::Restored,
+                ),
+            )
+            .await?;
+            undone.topics.push(topic);
+        }
+        UndoStep::ReinsertSubsteps { substeps } => {
+            if substeps.is_empty() {
+                return Ok(());
+            }
+
+            let task_ids: HashSet<Uuid> = substeps.iter().map(|s| s.task_id).collect();
+            let existing_tasks = Task::find()
+                .filter(task::Column::Id.is_in(task_ids.iter().copied()))
+                .count(db)
+                .await?;
+            if existing_tasks != task_ids.len() as u64 {
+                return Err(AppError::Conflict(
+                    "The substep's task no longer exists".to_string(),
+                ));
+            }
+
+            let substep_ids: Vec<Uuid> = substeps.iter().map(|s| s.id).collect();
+            let reinserted = TaskSubstep::find()
+                .filter(task_substep::Column::Id.is_in(substep_ids))
+                .count(db)
+                .await?;
+            if reinserted > 0 {
+                return Err(AppError::Conflict(
+                    "The deleted substeps already exist again".to_string(),
+                ));
+            }
+
+            let rows: Vec<task_substep::ActiveModel> = substeps
+                .iter()
+                .cloned()
+                .map(task_substep::ActiveModel::from)
+                .collect();
+            TaskSubstep::insert_many(rows).exec(db).await?;
+
+            for substep in substeps {
+                record_activity(
+                    db,
+                    NewActivity::new(
+                        workspace_id,
+                        user_id,
+                        ActivityEntity::Substep,
+                        substep.id,
+                        ActivityAction::Restored,
+                    )
+                    .on_task(substep.task_id),
+                )
+                .await?;
+                undone.substeps.push(substep);
+            }
+        }
+        UndoStep::RevertTasks { tasks } => {
+            for TaskRevision { before, after } in tasks {
+                let current = live_task(db, before.id).await?;
+
+                // Rebalancing re-ranks whole workspaces without it being a
+                // change to the task, so the rank alone does not count
+                let unchanged = task::Model {
+                    rank: after.rank.clone(),
+                    ..current.clone()
+                } == after;
+                if !unchanged {
+                    return Err(AppError::Conflict(
+                        "A task has been changed since; the operation can no longer be undone"
+                            .to_string(),
+                    ));
+                }
+
+                // Every column goes back, not only the ones that changed
+                let mut task = task::ActiveModel::from(before).reset_all();
+                task.updated_at = Set(chrono::Utc::now());
+                let task = task.update(db).await?;
+
+                record_activity(
+                    db,
+                    NewActivity::new(
+                        workspace_id,
+                        user_id,
+                        ActivityEntity::Task,
+                        task.id,
+                        ActivityAction::Updated,
+                    )
+                    .with_changes(field_changes(Some(&current), Some(&task))),
+                )
+                .await?;
+                undone.reverted.push(task);
+            }
+        }
+        UndoStep::RestoreTaskLabels { label_ids } => {
+            for (task_id, label_ids) in label_ids {
+                let task = live_task(db, task_id).await?;
+
+                // Labels deleted in the meantime cannot come back
+                let remaining: Vec<Uuid> = Label::find()
+                    .filter(label::Column::WorkspaceId.eq(workspace_id))
+                    .filter(label::Column::Id.is_in(label_ids))
+                    .all(db)
+                    .await?
+                    .into_iter()
+                    .map(|label| label.id)
+                    .collect();
+
+                change_task_labels(
+                    db,
+                    workspace_id,
+                    user_id,
+                    task.id,
+                    TaskLabelChange::Replace(&remaining),
+                )
+                .await?;
+                undone.reverted.push(task);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A task an undo changes back, which must not have gone to the trash or
+/// been purged since.
+async fn live_task<C: ConnectionTrait>(db: &C, task_id: Uuid) -> AppResult<task::Model> {
+    Task::find_by_id(task_id)
+        .filter(task::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict(
+                "A changed task has been deleted since; it cannot be reverted".to_string(),
+            )
+        })
+}
+
+/// Deletes undo tokens whose window has passed; they can no longer be
+/// redeemed.
+pub async fn purge_expired_undo_tokens(db: &DatabaseConnection) -> AppResult<u64> {
+    let result = UndoToken::delete_many()
+        .filter(undo_token::Column::ExpiresAt.lt(chrono::Utc::now()))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,714 @@
+use crate::config::Config;
+use crate::database::{field_changes, find_owned_workspace, record_activity, NewActivity};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    webhook::{self, Entity as Webhook, WebhookEvent},
+    webhook_delivery::{self, Entity as WebhookDelivery, WebhookDeliveryStatus},
+};
+use crate::webhooks::{check_webhook_host, DeliveryOutcome, WebhookSender};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// The first retry waits this long; every further one waits twice as long
+/// as the one before, up to `RETRY_MAX_SECONDS`.
+const RETRY_BASE_SECONDS: i64 = 30;
+const RETRY_MAX_SECONDS: i64 = 6 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Event names such as `task.created`; at least one is required.
+    pub events: Vec<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+    /// Replaces the signing secret with a new one.
+    pub rotate_secret: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<webhook::Model> for WebhookResponse {
+    fn from(webhook: webhook::Model) -> Self {
+        Self {
+            id: webhook.id,
+            workspace_id: webhook.workspace_id,
+            url: webhook.url,
+            secret: webhook.secret,
+            events: webhook.events,
+            active: webhook.active,
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    /// When the next attempt is due; `None` once the delivery is settled.
+    pub next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<webhook_delivery::Model> for WebhookDeliveryResponse {
+    fn from(delivery: webhook_delivery::Model) -> Self {
+        Self {
+            id: delivery.id,
+            webhook_id: delivery.webhook_id,
+            event: delivery.event,
+            payload: serde_json::from_str(&delivery.payload)
+                .unwrap_or(serde_json::Value::String(delivery.payload)),
+            status: delivery.status,
+            attempts: delivery.attempts,
+            next_attempt_at: (delivery.status == WebhookDeliveryStatus::Pending)
+                .then_some(delivery.next_attempt_at),
+            last_attempt_at: delivery.last_attempt_at,
+            response_status: delivery.response_status,
+            last_error: delivery.last_error,
+            delivered_at: delivery.delivered_at,
+            created_at: delivery.created_at,
+        }
+    }
+}
+
+/// Loads a webhook of one of the user's workspaces.
+async fn find_own_webhook<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    webhook_id: Uuid,
+) -> AppResult<webhook::Model> {
+    let webhook = Webhook::find_by_id(webhook_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+    find_owned_workspace(db, user_id, webhook.workspace_id)
+        .await
+        .map_err(|_| AppError::NotFound("Webhook not found".to_string()))?;
+
+    Ok(webhook)
+}
+
+fn validate_webhook_url(config: &Config, url: &str) -> AppResult<()> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|_| AppError::Validation("Webhook URL is not a valid URL".to_string()))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(AppError::Validation(
+            "Webhook URL must be an http or https URL".to_string(),
+        ));
+    }
+
+    check_webhook_host(&parsed, config.webhook_allow_private_targets).map_err(AppError::Validation)
+}
+
+/// Checks the event names of a subscription and returns them without
+/// duplicates.
+fn parse_webhook_events(events: &[String]) -> AppResult<Vec<String>> {
+    if events.is_empty() {
+        return Err(AppError::Validation(
+            "A webhook must subscribe to at least one event".to_string(),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let mut parsed = Vec::new();
+    for name in events {
+        let event: WebhookEvent = name.trim().parse().map_err(AppError::Validation)?;
+        if seen.insert(event) {
+            parsed.push(event.as_str().to_string());
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn generate_webhook_secret() -> String {
+    format!(
+        "whsec_{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = (attempts - 1).clamp(0, 20) as u32;
+    let seconds = RETRY_BASE_SECONDS
+        .saturating_mul(1 << exponent)
+        .min(RETRY_MAX_SECONDS);
+    chrono::Duration::seconds(seconds)
+}
+
+pub async fn get_webhooks_by_workspace(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<WebhookResponse>> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let webhooks = Webhook::find()
+        .filter(webhook::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(webhook::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(webhooks.into_iter().map(WebhookResponse::from).collect())
+}
+
+pub async fn create_webhook(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateWebhookRequest,
+) -> AppResult<WebhookResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+    validate_webhook_url(config, &request.url)?;
+    let events = parse_webhook_events(&request.events)?;
+
+    let webhook = webhook::ActiveModel {
+        workspace_id: Set(workspace.id),
+        url: Set(request.url),
+        secret: Set(generate_webhook_secret()),
+        events: Set(events),
+        active: Set(request.active.unwrap_or(true)),
+        ..Default::default()
+    };
+    let txn = db.begin().await?;
+    let webhook = webhook.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Webhook,
+            webhook.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&webhook))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WebhookResponse::from(webhook))
+}
+
+pub async fn update_webhook(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    webhook_id: Uuid,
+    request: UpdateWebhookRequest,
+) -> AppResult<WebhookResponse> {
+    let webhook = find_own_webhook(db, user_id, webhook_id).await?;
+    let before = webhook.clone();
+    let mut webhook: webhook::ActiveModel = webhook.into();
+
+    if let Some(url) = request.url {
+        validate_webhook_url(config, &url)?;
+        webhook.url = Set(url);
+    }
+
+    if let Some(events) = request.events {
+        webhook.events = Set(parse_webhook_events(&events)?);
+    }
+
+    if let Some(active) = request.active {
+        webhook.active = Set(active);
+    }
+
+    if request.rotate_secret.unwrap_or(false) {
+        webhook.secret = Set(generate_webhook_secret());
+    }
+
+    webhook.updated_at = Set(chrono::Utc::now());
+    let txn = db.begin().await?;
+    let webhook = webhook.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            webhook.workspace_id,
+            user_id,
+            ActivityEntity::Webhook,
+            webhook.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&webhook))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WebhookResponse::from(webhook))
+}
+
+/// Deletes a webhook together with its delivery history.
+pub async fn delete_webhook(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    webhook_id: Uuid,
+) -> AppResult<bool> {
+    let webhook = find_own_webhook(db, user_id, webhook_id).await?;
+
+    let txn = db.begin().await?;
+    Webhook::delete_by_id(webhook.id).exec(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            webhook.workspace_id,
+            user_id,
+            ActivityEntity::Webhook,
+            webhook.id,
+            ActivityAction::Deleted,
+        )
+        .with_changes(field_changes(Some(&webhook), None)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(true)
+}
+
+/// Deliveries of a webhook, newest first.
+pub async fn get_webhook_deliveries(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    webhook_id: Uuid,
+    status: Option<WebhookDeliveryStatus>,
+    limit: u64,
+    offset: u64,
+) -> AppResult<Vec<WebhookDeliveryResponse>> {
+    let webhook = find_own_webhook(db, user_id, webhook_id).await?;
+
+    let mut query =
+        WebhookDelivery::find().filter(webhook_delivery::Column::WebhookId.eq(webhook.id));
+    if let Some(status) = status {
+        query = query.filter(webhook_delivery::Column::Status.eq(status));
+    }
+
+    let deliveries = query
+        .order_by_desc(webhook_delivery::Column::CreatedAt)
+        .order_by_desc(webhook_delivery::Column::Id)
+        .limit(limit)
+        .offset(offset)
+        .all(db)
+        .await?;
+
+    Ok(deliveries
+        .into_iter()
+        .map(WebhookDeliveryResponse::from)
+        .collect())
+}
+
+/// Queues a settled delivery, typically a dead one, for another round of
+/// attempts.
+pub async fn retry_webhook_delivery(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    webhook_id: Uuid,
+    delivery_id: Uuid,
+) -> AppResult<WebhookDeliveryResponse> {
+    let webhook = find_own_webhook(db, user_id, webhook_id).await?;
+
+    let delivery = WebhookDelivery::find_by_id(delivery_id)
+        .filter(webhook_delivery::Column::WebhookId.eq(webhook.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Delivery not found".to_string()))?;
+
+    if delivery.status == WebhookDeliveryStatus::Pending {
+        return Err(AppError::Conflict(
+            "Delivery is already pending".to_string(),
+        ));
+    }
+
+    let mut delivery: webhook_delivery::ActiveModel = delivery.into();
+    delivery.status = Set(WebhookDeliveryStatus::Pending);
+    delivery.attempts = Set(0);
+    delivery.next_attempt_at = Set(chrono::Utc::now());
+    let delivery = delivery.update(db).await?;
+
+    Ok(WebhookDeliveryResponse::from(delivery))
+}
+
+/// Queues a delivery of the event for every active webhook of the workspace
+/// that subscribes to it. Call it inside the transaction that makes the
+/// change, so an event is sent if and only if the change is committed.
+pub async fn enqueue_webhook_event<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: Uuid,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) -> AppResult<()> {
+    let webhooks = Webhook::find()
+        .filter(webhook::Column::WorkspaceId.eq(workspace_id))
+        .filter(webhook::Column::Active.eq(true))
+        .all(db)
+        .await?;
+
+    let now = chrono::Utc::now();
+    let rows: Vec<webhook_delivery::ActiveModel> = webhooks
+        .iter()
+        .filter(|webhook| webhook.events.iter().any(|e| e == event.as_str()))
+        .map(|webhook| {
+            let id = Uuid::new_v4();
+            let payload = serde_json::json!({
+                "id": id,
+                "event": event,
+                "workspace_id": workspace_id,
+                "occurred_at": now,
+                "data": data,
+            });
+
+            webhook_delivery::ActiveModel {
+                id: Set(id),
+                webhook_id: Set(webhook.id),
+                event: Set(event.as_str().to_string()),
+                payload: Set(payload.to_string()),
+                status: Set(WebhookDeliveryStatus::Pending),
+                attempts: Set(0),
+                next_attempt_at: Set(now),
+                last_attempt_at: Set(None),
+                response_status: Set(None),
+                last_error: Set(None),
+                delivered_at: Set(None),
+                created_at: Set(now),
+            }
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        WebhookDelivery::insert_many(rows).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ClaimedDelivery {
+    id: Uuid,
+}
+
+/// Claims due deliveries by pushing their next attempt past a lease. Rows
+/// are picked with `SKIP LOCKED`, so concurrent dispatchers claim disjoint
+/// batches, and a delivery whose dispatcher dies is picked up again once
+/// the lease runs out.
+const CLAIM_DELIVERIES_SQL: &str = r#"
+UPDATE webhook_deliveries d
+SET next_attempt_at = $2
+FROM (
+    SELECT id
+    FROM webhook_deliveries
+    WHERE status = 'pending'
+      AND next_attempt_at <= $1
+    ORDER BY next_attempt_at
+    LIMIT $3
+    FOR UPDATE SKIP LOCKED
+) due
+WHERE d.id = due.id
+RETURNING d.id
+"#;
+
+/// Attempts one batch of due deliveries and returns how many were
+/// attempted. Failed deliveries are retried with exponential backoff until
+/// `max_attempts` is reached, after which they are marked dead.
+pub async fn deliver_due_webhooks(
+    db: &DatabaseConnection,
+    sender: &WebhookSender,
+    max_attempts: i32,
+    batch_size: u64,
+) -> AppResult<u64> {
+    let now = chrono::Utc::now();
+    // Long enough for every delivery of the batch to time out in turn
+    let lease =
+        chrono::Duration::seconds(sender.timeout().as_secs() as i64 * batch_size as i64 + 60);
+
+    let claimed: Vec<Uuid> = ClaimedDelivery::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        CLAIM_DELIVERIES_SQL,
+        [now.into(), (now + lease).into(), (batch_size as i64).into()],
+    ))
+    .all(db)
+    .await?
+    .into_iter()
+    .map(|row| row.id)
+    .collect();
+
+    if claimed.is_empty() {
+        return Ok(0);
+    }
+
+    let deliveries = WebhookDelivery::find()
+        .filter(webhook_delivery::Column::Id.is_in(claimed.iter().copied()))
+        .find_also_related(Webhook)
+        .order_by_asc(webhook_delivery::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    for (delivery, webhook) in deliveries {
+        let Some(webhook) = webhook else { continue };
+
+        let outcome = if webhook.active {
+            sender.send(&webhook, &delivery).await
+        } else {
+            DeliveryOutcome::Failed {
+                status: None,
+                error: "Webhook is disabled".to_string(),
+            }
+        };
+
+        record_attempt(
+            delivery,
+            outcome,
+            chrono::Utc::now(),
+            max_attempts,
+            webhook.active,
+        )
+        .update(db)
+        .await?;
+    }
+
+    Ok(claimed.len() as u64)
+}
+
+/// Applies the outcome of one attempt to a delivery. A failed delivery is
+/// rescheduled with backoff, or marked dead once it has used up
+/// `max_attempts` or its webhook is disabled.
+fn record_attempt(
+    delivery: webhook_delivery::Model,
+    outcome: DeliveryOutcome,
+    attempted_at: chrono::DateTime<chrono::Utc>,
+    max_attempts: i32,
+    webhook_active: bool,
+) -> webhook_delivery::ActiveModel {
+    let attempts = delivery.attempts + 1;
+    let mut delivery: webhook_delivery::ActiveModel = delivery.into();
+    delivery.attempts = Set(attempts);
+    delivery.last_attempt_at = Set(Some(attempted_at));
+
+    match outcome {
+        DeliveryOutcome::Delivered { status } => {
+            delivery.status = Set(WebhookDeliveryStatus::Succeeded);
+            delivery.response_status = Set(Some(status as i32));
+            delivery.last_error = Set(None);
+            delivery.delivered_at = Set(Some(attempted_at));
+        }
+        DeliveryOutcome::Failed { status, error } => {
+            delivery.response_status = Set(status.map(i32::from));
+            delivery.last_error = Set(Some(error));
+            if attempts >= max_attempts || !webhook_active {
+                delivery.status = Set(WebhookDeliveryStatus::Dead);
+            } else {
+                delivery.next_attempt_at = Set(attempted_at + retry_delay(attempts));
+            }
+        }
+    }
+
+    delivery
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::{
+        sign_payload, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+    };
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    /// Accepts one request on a local port, answers it with `status` and
+    /// returns the raw request.
+    async fn spawn_receiver(status: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("http://{}/hook", listener.local_addr().expect("address"));
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut buffer).await.expect("read");
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+
+                let text = String::from_utf8_lossy(&request);
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            stream.write_all(response.as_bytes()).await.expect("write");
+            String::from_utf8(request).expect("UTF-8 request")
+        });
+
+        (url, handle)
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+        request.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    fn fixtures(url: String, attempts: i32) -> (webhook::Model, webhook_delivery::Model) {
+        let now = chrono::Utc::now();
+        let webhook = webhook::Model {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            url,
+            secret: "whsec_test".to_string(),
+            events: vec![WebhookEvent::TaskCreated.as_str().to_string()],
+            active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        let delivery = webhook_delivery::Model {
+            id: Uuid::new_v4(),
+            webhook_id: webhook.id,
+            event: WebhookEvent::TaskCreated.as_str().to_string(),
+            payload: r#"{"event":"task.created"}"#.to_string(),
+            status: WebhookDeliveryStatus::Pending,
+            attempts,
+            next_attempt_at: now,
+            last_attempt_at: None,
+            response_status: None,
+            last_error: None,
+            delivered_at: None,
+            created_at: now,
+        };
+        (webhook, delivery)
+    }
+
+    fn sender() -> WebhookSender {
+        WebhookSender::new(Duration::from_secs(5), true).expect("webhook client")
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1).num_seconds(), RETRY_BASE_SECONDS);
+        assert_eq!(retry_delay(2).num_seconds(), RETRY_BASE_SECONDS * 2);
+        assert_eq!(retry_delay(4).num_seconds(), RETRY_BASE_SECONDS * 8);
+        assert_eq!(retry_delay(0).num_seconds(), RETRY_BASE_SECONDS);
+        assert_eq!(retry_delay(30).num_seconds(), RETRY_MAX_SECONDS);
+        assert_eq!(retry_delay(i32::MAX).num_seconds(), RETRY_MAX_SECONDS);
+    }
+
+    #[tokio::test]
+    async fn delivery_is_signed() {
+        let (url, receiver) = spawn_receiver("204 No Content").await;
+        let (webhook, delivery) = fixtures(url, 0);
+
+        let outcome = sender().send(&webhook, &delivery).await;
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Delivered { status: 204 }
+        ));
+
+        let request = receiver.await.expect("receiver");
+        assert!(request.starts_with("POST /hook "));
+        assert_eq!(header(&request, EVENT_HEADER), Some("task.created"));
+        let delivery_id = delivery.id.to_string();
+        assert_eq!(
+            header(&request, DELIVERY_HEADER),
+            Some(delivery_id.as_str())
+        );
+        assert!(request.ends_with(&delivery.payload));
+
+        let timestamp: i64 = header(&request, TIMESTAMP_HEADER)
+            .and_then(|value| value.parse().ok())
+            .expect("timestamp header");
+        let signature = sign_payload(&webhook.secret, timestamp, &delivery.payload);
+        assert_eq!(header(&request, SIGNATURE_HEADER), Some(signature.as_str()));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_rescheduled() {
+        let (url, receiver) = spawn_receiver("500 Internal Server Error").await;
+        let (webhook, delivery) = fixtures(url, 0);
+
+        let outcome = sender().send(&webhook, &delivery).await;
+        receiver.await.expect("receiver");
+        let attempted_at = chrono::Utc::now();
+        let delivery = record_attempt(delivery, outcome, attempted_at, 3, true);
+
+        assert_eq!(delivery.status.as_ref(), &WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.attempts.as_ref(), &1);
+        assert_eq!(delivery.response_status.as_ref(), &Some(500));
+        assert_eq!(
+            delivery.next_attempt_at.as_ref(),
+            &(attempted_at + retry_delay(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn delivery_is_dead_after_max_attempts() {
+        let (url, receiver) = spawn_receiver("503 Service Unavailable").await;
+        let (webhook, delivery) = fixtures(url, 2);
+
+        let outcome = sender().send(&webhook, &delivery).await;
+        receiver.await.expect("receiver");
+        let delivery = record_attempt(delivery, outcome, chrono::Utc::now(), 3, true);
+
+        assert_eq!(delivery.status.as_ref(), &WebhookDeliveryStatus::Dead);
+        assert_eq!(delivery.attempts.as_ref(), &3);
+        assert_eq!(delivery.response_status.as_ref(), &Some(503));
+    }
+
+    #[test]
+    fn failure_of_disabled_webhook_is_dead() {
+        let (_, delivery) = fixtures("https://example.com/hook".to_string(), 0);
+        let outcome = DeliveryOutcome::Failed {
+            status: None,
+            error: "Webhook is disabled".to_string(),
+        };
+        let delivery = record_attempt(delivery, outcome, chrono::Utc::now(), 5, false);
+
+        assert_eq!(delivery.status.as_ref(), &WebhookDeliveryStatus::Dead);
+    }
+}
@@ -0,0 +1,1088 @@
+use crate::config::Config;
+use crate::database::{
+    build_custom_field_value, check_task_quota, check_topic_quota, custom_field_value_json,
+    dependency_graph_has_cycle, field_changes, find_owned_workspace, record_activity,
+    seed_default_task_statuses, seed_default_task_types, validate_custom_field_options,
+    validate_effort_minutes, workspace_member_ids, NewActivity, WorkspaceResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    custom_field::{self, CustomFieldType, Entity as CustomField},
+    label::{self, Entity as Label},
+    task::{self, Entity as Task, TaskPriority},
+    task_assignee::{self, Entity as TaskAssignee},
+    task_custom_field_value::{self, Entity as TaskCustomFieldValue},
+    task_dependency::{self, Entity as TaskDependency, TaskDependencyType},
+    task_exception::{self, Entity as TaskException},
+    task_label::{self, Entity as TaskLabel},
+    task_status::{self, Entity as TaskStatus, TaskStatusCategory},
+    task_status_transition::{self, Entity as TaskStatusTransition},
+    task_substep::{self, Entity as TaskSubstep},
+    task_type::{self, Entity as TaskType},
+    topic::{self, Entity as Topic},
+    workspace,
+};
+use crate::rank::{is_valid_rank, rank_sequence};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Version of the export document produced by `export_workspace`. Bump it
+/// whenever a field is removed or changes meaning.
+pub const WORKSPACE_EXPORT_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceExport {
+    pub version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub workspace: ExportedWorkspace,
+    // Statuses were added without a version bump; older documents omit them.
+    #[serde(default)]
+    pub statuses: Vec<ExportedTaskStatus>,
+    #[serde(default)]
+    pub status_transitions: Vec<ExportedTaskStatusTransition>,
+    #[serde(default)]
+    pub task_types: Vec<ExportedTaskType>,
+    #[serde(default)]
+    pub labels: Vec<ExportedLabel>,
+    #[serde(default)]
+    pub custom_fields: Vec<ExportedCustomField>,
+    pub topics: Vec<ExportedTopic>,
+    pub tasks: Vec<ExportedTask>,
+    #[serde(default)]
+    pub dependencies: Vec<ExportedTaskDependency>,
+    pub task_exceptions: Vec<ExportedTaskException>,
+    pub substeps: Vec<ExportedSubstep>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedWorkspace {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTaskStatus {
+    pub id: Uuid,
+    pub name: String,
+    pub category: TaskStatusCategory,
+    pub color: Option<String>,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTaskStatusTransition {
+    pub from_status_id: Uuid,
+    pub to_status_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTaskType {
+    pub id: Uuid,
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: String,
+    pub default_duration_minutes: Option<i32>,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedLabel {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedCustomField {
+    pub id: Uuid,
+    pub name: String,
+    pub field_type: CustomFieldType,
+    pub options: Option<Vec<String>>,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedCustomFieldValue {
+    pub field_id: Uuid,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTopic {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: String,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTask {
+    pub id: Uuid,
+    pub topic_id: Uuid,
+    #[serde(default)]
+    pub parent_task_id: Option<Uuid>,
+    pub title: String,
+    pub description: Option<String>,
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub task_type_id: Option<Uuid>,
+    /// Enum value (e.g. `"work"`) written by version 1 exports, from before
+    /// task types were per-workspace records; matched against type names on
+    /// import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_type: Option<String>,
+    #[serde(default)]
+    pub status_id: Option<Uuid>,
+    #[serde(default)]
+    pub rank: String,
+    #[serde(default)]
+    pub label_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub custom_field_values: Vec<ExportedCustomFieldValue>,
+    /// Only kept on import for users who are members of the new workspace.
+    #[serde(default)]
+    pub assignee_ids: Vec<Uuid>,
+    pub color: String,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    /// Still written for older importers; only read when `priority` is
+    /// missing.
+    #[serde(default)]
+    pub urgent: bool,
+    pub completed: bool,
+    pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub estimate_minutes: Option<i32>,
+    #[serde(default)]
+    pub remaining_minutes: Option<i32>,
+    pub recurrence_type: Option<String>,
+    pub recurrence_interval: Option<i32>,
+    pub recurrence_days: Option<Vec<i32>>,
+    pub recurrence_end_date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTaskDependency {
+    pub blocking_task_id: Uuid,
+    pub blocked_task_id: Uuid,
+    pub dependency_type: TaskDependencyType,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTaskException {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub original_date: chrono::NaiveDate,
+    pub is_completed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedSubstep {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub description: String,
+    pub completed: bool,
+    #[serde(default)]
+    pub rank: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportWorkspaceOptions {
+    pub name: Option<String>,
+}
+
+pub async fn export_workspace(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<WorkspaceExport> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let statuses = TaskStatus::find()
+        .filter(task_status::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(task_status::Column::Position)
+        .all(db)
+        .await?;
+
+    let status_transitions = TaskStatusTransition::find()
+        .filter(task_status_transition::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(task_status_transition::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let task_types = TaskType::find()
+        .filter(task_type::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(task_type::Column::Position)
+        .all(db)
+        .await?;
+
+    let labels = Label::find()
+        .filter(label::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(label::Column::Name)
+        .all(db)
+        .await?;
+
+    let custom_fields = CustomField::find()
+        .filter(custom_field::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(custom_field::Column::Position)
+        .order_by_asc(custom_field::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let topics = Topic::find()
+        .filter(topic::Column::WorkspaceId.eq(workspace.id))
+        .filter(topic::Column::DeletedAt.is_null())
+        .order_by_asc(topic::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let topic_ids: Vec<Uuid> = topics.iter().map(|t| t.id).collect();
+    let tasks = Task::find()
+        .filter(task::Column::TopicId.is_in(topic_ids))
+        .filter(task::Column::DeletedAt.is_null())
+        .order_by_asc(task::Column::Rank)
+        .order_by_asc(task::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let task_ids: Vec<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let exported_task_ids: HashSet<Uuid> = task_ids.iter().copied().collect();
+    let dependencies: Vec<task_dependency::Model> = TaskDependency::find()
+        .filter(task_dependency::Column::BlockedTaskId.is_in(task_ids.clone()))
+        .order_by_asc(task_dependency::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|dependency| exported_task_ids.contains(&dependency.blocking_task_id))
+        .collect();
+
+    let mut task_label_ids: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for task_label in TaskLabel::find()
+        .filter(task_label::Column::TaskId.is_in(task_ids.clone()))
+        .order_by_asc(task_label::Column::CreatedAt)
+        .all(db)
+        .await?
+    {
+        task_label_ids
+            .entry(task_label.task_id)
+            .or_default()
+            .push(task_label.label_id);
+    }
+
+    let mut task_assignee_ids: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for task_assignee in TaskAssignee::find()
+        .filter(task_assignee::Column::TaskId.is_in(task_ids.clone()))
+        .order_by_asc(task_assignee::Column::CreatedAt)
+        .all(db)
+        .await?
+    {
+        task_assignee_ids
+            .entry(task_assignee.task_id)
+            .or_default()
+            .push(task_assignee.user_id);
+    }
+
+    let mut task_custom_field_values: HashMap<Uuid, Vec<ExportedCustomFieldValue>> =
+        HashMap::new();
+    for value in TaskCustomFieldValue::find()
+        .filter(task_custom_field_value::Column::TaskId.is_in(task_ids.clone()))
+        .all(db)
+        .await?
+    {
+        task_custom_field_values
+            .entry(value.task_id)
+            .or_default()
+            .push(ExportedCustomFieldValue {
+                field_id: value.field_id,
+                value: custom_field_value_json(&value),
+            });
+    }
+
+    let task_exceptions = TaskException::find()
+        .filter(task_exception::Column::TaskId.is_in(task_ids.clone()))
+        .order_by_asc(task_exception::Column::OriginalDate)
+        .all(db)
+        .await?;
+
+    let substeps = TaskSubstep::find()
+        .filter(task_substep::Column::TaskId.is_in(task_ids))
+        .order_by_asc(task_substep::Column::Rank)
+        .order_by_asc(task_substep::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(WorkspaceExport {
+        version: WORKSPACE_EXPORT_VERSION,
+        exported_at: chrono::Utc::now(),
+        workspace: ExportedWorkspace {
+            id: workspace.id,
+            name: workspace.name,
+            description: workspace.description,
+        },
+        statuses: statuses
+            .into_iter()
+            .map(|status| ExportedTaskStatus {
+                id: status.id,
+                name: status.name,
+                category: status.category,
+                color: status.color,
+                position: status.position,
+            })
+            .collect(),
+        status_transitions: status_transitions
+            .into_iter()
+            .map(|transition| ExportedTaskStatusTransition {
+                from_status_id: transition.from_status_id,
+                to_status_id: transition.to_status_id,
+            })
+            .collect(),
+        task_types: task_types
+            .into_iter()
+            .map(|task_type| ExportedTaskType {
+                id: task_type.id,
+                name: task_type.name,
+                icon: task_type.icon,
+                color: task_type.color,
+                default_duration_minutes: task_type.default_duration_minutes,
+                position: task_type.position,
+            })
+            .collect(),
+        labels: labels
+            .into_iter()
+            .map(|label| ExportedLabel {
+                id: label.id,
+                name: label.name,
+                color: label.color,
+            })
+            .collect(),
+        custom_fields: custom_fields
+            .into_iter()
+            .map(|field| ExportedCustomField {
+                id: field.id,
+                name: field.name,
+                field_type: field.field_type,
+                options: field.options,
+                position: field.position,
+            })
+            .collect(),
+        topics: topics
+            .into_iter()
+            .map(|topic| ExportedTopic {
+                id: topic.id,
+                name: topic.name,
+                description: topic.description,
+                color: topic.color,
+                archived_at: topic.archived_at,
+            })
+            .collect(),
+        tasks: tasks
+            .into_iter()
+            .map(|task| ExportedTask {
+                id: task.id,
+                topic_id: task.topic_id,
+                parent_task_id: task.parent_task_id,
+                title: task.title,
+                description: task.description,
+                start_time: task.start_time,
+                end_time: task.end_time,
+                task_type_id: task.task_type_id,
+                task_type: None,
+                status_id: task.status_id,
+                label_ids: task_label_ids.remove(&task.id).unwrap_or_default(),
+                custom_field_values: task_custom_field_values
+                    .remove(&task.id)
+                    .unwrap_or_default(),
+                assignee_ids: task_assignee_ids.remove(&task.id).unwrap_or_default(),
+                rank: task.rank,
+                color: task.color,
+                priority: Some(task.priority),
+                urgent: task.priority.is_urgent(),
+                completed: task.completed,
+                due_date: task.due_date,
+                estimate_minutes: task.estimate_minutes,
+                remaining_minutes: task.remaining_minutes,
+                recurrence_type: task.recurrence_type,
+                recurrence_interval: task.recurrence_interval,
+                recurrence_days: task.recurrence_days,
+                recurrence_end_date: task.recurrence_end_date,
+            })
+            .collect(),
+        dependencies: dependencies
+            .into_iter()
+            .map(|dependency| ExportedTaskDependency {
+                blocking_task_id: dependency.blocking_task_id,
+                blocked_task_id: dependency.blocked_task_id,
+                dependency_type: dependency.dependency_type,
+            })
+            .collect(),
+        task_exceptions: task_exceptions
+            .into_iter()
+            .map(|exception| ExportedTaskException {
+                id: exception.id,
+                task_id: exception.task_id,
+                original_date: exception.original_date,
+                is_completed: exception.is_completed,
+            })
+            .collect(),
+        substeps: substeps
+            .into_iter()
+            .map(|substep| ExportedSubstep {
+                id: substep.id,
+                task_id: substep.task_id,
+                description: substep.description,
+                completed: substep.completed,
+                rank: substep.rank,
+            })
+            .collect(),
+    })
+}
+
+/// Checks the `version` field before anything else so that documents from a
+/// newer or unknown format are rejected with a clear message instead of a
+/// field-level deserialization error. Version 1 documents are still accepted;
+/// their tasks carry a `task_type` name instead of a `task_type_id`.
+pub fn parse_workspace_export(document: serde_json::Value) -> AppResult<WorkspaceExport> {
+    let version = document
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| AppError::Validation("Export document has no version".to_string()))?;
+
+    if !(1..=WORKSPACE_EXPORT_VERSION as u64).contains(&version) {
+        return Err(AppError::Validation(format!(
+            "Unsupported export version {} (expected 1 to {})",
+            version, WORKSPACE_EXPORT_VERSION
+        )));
+    }
+
+    let export: WorkspaceExport = serde_json::from_value(document)
+        .map_err(|e| AppError::Validation(format!("Invalid export document: {}", e)))?;
+
+    validate_workspace_export(&export)?;
+    Ok(export)
+}
+
+fn validate_workspace_export(export: &WorkspaceExport) -> AppResult<()> {
+    let mut status_ids = HashSet::new();
+    for status in &export.statuses {
+        if !status_ids.insert(status.id) {
+            return Err(AppError::Validation(format!("Duplicate status ID {}", status.id)));
+        }
+    }
+
+    for transition in &export.status_transitions {
+        if !status_ids.contains(&transition.from_status_id)
+            || !status_ids.contains(&transition.to_status_id)
+        {
+            return Err(AppError::Validation(
+                "Status transition references an unknown status".to_string(),
+            ));
+        }
+    }
+
+    let mut task_type_ids = HashSet::new();
+    for task_type in &export.task_types {
+        if !task_type_ids.insert(task_type.id) {
+            return Err(AppError::Validation(format!(
+                "Duplicate task type ID {}",
+                task_type.id
+            )));
+        }
+    }
+
+    let mut label_ids = HashSet::new();
+    let mut label_names = HashSet::new();
+    for label in &export.labels {
+        if !label_ids.insert(label.id) {
+            return Err(AppError::Validation(format!("Duplicate label ID {}", label.id)));
+        }
+        if !label_names.insert(label.name.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Duplicate label name \"{}\"",
+                label.name
+            )));
+        }
+    }
+
+    let mut custom_field_ids = HashSet::new();
+    let mut custom_field_names = HashSet::new();
+    for field in &export.custom_fields {
+        if !custom_field_ids.insert(field.id) {
+            return Err(AppError::Validation(format!(
+                "Duplicate custom field ID {}",
+                field.id
+            )));
+        }
+        if !custom_field_names.insert(field.name.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Duplicate custom field name \"{}\"",
+                field.name
+            )));
+        }
+        validate_custom_field_options(field.field_type, field.options.as_ref())?;
+    }
+
+    let mut topic_ids = HashSet::new();
+    for topic in &export.topics {
+        if !topic_ids.insert(topic.id) {
+            return Err(AppError::Validation(format!("Duplicate topic ID {}", topic.id)));
+        }
+    }
+
+    let mut task_ids = HashSet::new();
+    for task in &export.tasks {
+        if !task_ids.insert(task.id) {
+            return Err(AppError::Validation(format!("Duplicate task ID {}", task.id)));
+        }
+        if !topic_ids.contains(&task.topic_id) {
+            return Err(AppError::Validation(format!(
+                "Task {} references unknown topic {}",
+                task.id, task.topic_id
+            )));
+        }
+        if let Some(status_id) = task.status_id {
+            if !status_ids.contains(&status_id) {
+                return Err(AppError::Validation(format!(
+                    "Task {} references unknown status {}",
+                    task.id, status_id
+                )));
+            }
+        }
+        if let Some(task_type_id) = task.task_type_id {
+            if !task_type_ids.contains(&task_type_id) {
+                return Err(AppError::Validation(format!(
+                    "Task {} references unknown task type {}",
+                    task.id, task_type_id
+                )));
+            }
+        }
+        validate_effort_minutes(task.estimate_minutes, task.remaining_minutes)?;
+        if let Some(label_id) = task.label_ids.iter().find(|id| !label_ids.contains(id)) {
+            return Err(AppError::Validation(format!(
+                "Task {} references unknown label {}",
+                task.id, label_id
+            )));
+        }
+        if let Some(value) = task
+            .custom_field_values
+            .iter()
+            .find(|value| !custom_field_ids.contains(&value.field_id))
+        {
+            return Err(AppError::Validation(format!(
+                "Task {} references unknown custom field {}",
+                task.id, value.field_id
+            )));
+        }
+    }
+
+    for task in &export.tasks {
+        if let Some(parent_task_id) = task.parent_task_id {
+            if !task_ids.contains(&parent_task_id) {
+                return Err(AppError::Validation(format!(
+                    "Task {} references unknown parent task {}",
+                    task.id, parent_task_id
+                )));
+            }
+        }
+    }
+
+    if dependency_graph_has_cycle(
+        export
+            .tasks
+            .iter()
+            .filter_map(|t| t.parent_task_id.map(|parent| (parent, t.id))),
+    ) {
+        return Err(AppError::Validation(
+            "Task parents contain a cycle".to_string(),
+        ));
+    }
+
+    let mut dependency_pairs = HashSet::new();
+    for dependency in &export.dependencies {
+        if !task_ids.contains(&dependency.blocking_task_id)
+            || !task_ids.contains(&dependency.blocked_task_id)
+        {
+            return Err(AppError::Validation(
+                "Dependency references an unknown task".to_string(),
+            ));
+        }
+        if dependency.blocking_task_id == dependency.blocked_task_id {
+            return Err(AppError::Validation(format!(
+                "Task {} depends on itself",
+                dependency.blocked_task_id
+            )));
+        }
+        if !dependency_pairs.insert((dependency.blocking_task_id, dependency.blocked_task_id)) {
+            return Err(AppError::Validation(format!(
+                "Duplicate dependency of task {} on task {}",
+                dependency.blocked_task_id, dependency.blocking_task_id
+            )));
+        }
+    }
+
+    if dependency_graph_has_cycle(
+        export
+            .dependencies
+            .iter()
+            .map(|d| (d.blocking_task_id, d.blocked_task_id)),
+    ) {
+        return Err(AppError::Validation(
+            "Task dependencies contain a cycle".to_string(),
+        ));
+    }
+
+    for exception in &export.task_exceptions {
+        if !task_ids.contains(&exception.task_id) {
+            return Err(AppError::Validation(format!(
+                "Task exception {} references unknown task {}",
+                exception.id, exception.task_id
+            )));
+        }
+    }
+
+    for substep in &export.substeps {
+        if !task_ids.contains(&substep.task_id) {
+            return Err(AppError::Validation(format!(
+                "Substep {} references unknown task {}",
+                substep.id, substep.task_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Postgres accepts at most this many bind parameters in one statement.
+const MAX_BIND_PARAMS: usize = 65535;
+
+/// Inserts `rows` with as few multi-row statements as the bind parameter
+/// limit allows, so that copies of large workspaces do not fail on it.
+pub(crate) async fn insert_in_chunks<A, C>(
+    db: &C,
+    rows: impl IntoIterator<Item = A>,
+) -> AppResult<()>
+where
+    A: ActiveModelTrait,
+    C: ConnectionTrait,
+{
+    let columns = <<A::Entity as EntityTrait>::Column as Iterable>::iter().count();
+    let chunk_size = (MAX_BIND_PARAMS / columns).max(1);
+
+    let mut rows = rows.into_iter().peekable();
+    while rows.peek().is_some() {
+        let chunk: Vec<A> = rows.by_ref().take(chunk_size).collect();
+        <A::Entity as EntityTrait>::insert_many(chunk)
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn import_workspace(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    export: WorkspaceExport,
+    options: ImportWorkspaceOptions,
+) -> AppResult<WorkspaceResponse> {
+    if config.max_substeps_per_task > 0 {
+        let mut substeps_per_task: HashMap<Uuid, u64> = HashMap::new();
+        for substep in &export.substeps {
+            *substeps_per_task.entry(substep.task_id).or_default() += 1;
+        }
+        if substeps_per_task
+            .values()
+            .any(|count| *count > config.max_substeps_per_task)
+        {
+            return Err(AppError::QuotaExceeded(format!(
+                "Task substep limit of {} reached",
+                config.max_substeps_per_task
+            )));
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let txn = db.begin().await?;
+
+    let workspace = workspace::ActiveModel {
+        owner_id: Set(user_id),
+        name: Set(options.name.unwrap_or(export.workspace.name)),
+        description: Set(export.workspace.description),
+        ..Default::default()
+    };
+
+    let workspace = workspace.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&workspace))),
+    )
+    .await?;
+
+    // Documents without statuses get the default columns, and their tasks
+    // are placed by the completed flag.
+    let statuses: Vec<task_status::Model> = if export.statuses.is_empty() {
+        seed_default_task_statuses(&txn, workspace.id).await?
+    } else {
+        export
+            .statuses
+            .iter()
+            .map(|status| task_status::Model {
+                id: Uuid::new_v4(),
+                workspace_id: workspace.id,
+                name: status.name.clone(),
+                category: status.category,
+                color: status.color.clone(),
+                position: status.position,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect()
+    };
+    let status_ids: HashMap<Uuid, &task_status::Model> = export
+        .statuses
+        .iter()
+        .zip(statuses.iter())
+        .map(|(exported, status)| (exported.id, status))
+        .collect();
+
+    if !export.statuses.is_empty() {
+        TaskStatus::insert_many(statuses.iter().cloned().map(task_status::ActiveModel::from))
+            .exec(&txn)
+            .await?;
+    }
+
+    let mut transitions = Vec::new();
+    for transition in &export.status_transitions {
+        let from_status_id = status_ids[&transition.from_status_id].id;
+        let to_status_id = status_ids[&transition.to_status_id].id;
+        if from_status_id != to_status_id {
+            transitions.push(task_status_transition::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                workspace_id: Set(workspace.id),
+                from_status_id: Set(from_status_id),
+                to_status_id: Set(to_status_id),
+                created_at: Set(now),
+            });
+        }
+    }
+    if !transitions.is_empty() {
+        TaskStatusTransition::insert_many(transitions)
+            .exec(&txn)
+            .await?;
+    }
+
+    let status_for_completion = |completed: bool| {
+        let category = if completed {
+            TaskStatusCategory::Done
+        } else {
+            TaskStatusCategory::Todo
+        };
+        statuses
+            .iter()
+            .filter(|s| s.category == category)
+            .min_by_key(|s| s.position)
+    };
+
+    // Documents without task types get the defaults, which carry the names
+    // of the old enum values their tasks refer to.
+    let task_types: Vec<task_type::Model> = if export.task_types.is_empty() {
+        seed_default_task_types(&txn, workspace.id).await?
+    } else {
+        export
+            .task_types
+            .iter()
+            .map(|task_type| task_type::Model {
+                id: Uuid::new_v4(),
+                workspace_id: workspace.id,
+                name: task_type.name.clone(),
+                icon: task_type.icon.clone(),
+                color: task_type.color.clone(),
+                default_duration_minutes: task_type.default_duration_minutes,
+                position: task_type.position,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect()
+    };
+    let task_type_ids: HashMap<Uuid, Uuid> = export
+        .task_types
+        .iter()
+        .zip(task_types.iter())
+        .map(|(exported, task_type)| (exported.id, task_type.id))
+        .collect();
+
+    if !export.task_types.is_empty() {
+        TaskType::insert_many(
+            task_types
+                .iter()
+                .cloned()
+                .map(task_type::ActiveModel::from),
+        )
+        .exec(&txn)
+        .await?;
+    }
+
+    let task_type_for = |task: &ExportedTask| match (task.task_type_id, &task.task_type) {
+        (Some(id), _) => Some(task_type_ids[&id]),
+        (None, Some(name)) => task_types
+            .iter()
+            .find(|t| t.name.to_lowercase() == name.to_lowercase())
+            .map(|t| t.id),
+        (None, None) => None,
+    };
+
+    let label_ids: HashMap<Uuid, Uuid> = export
+        .labels
+        .iter()
+        .map(|label| (label.id, Uuid::new_v4()))
+        .collect();
+
+    if !export.labels.is_empty() {
+        let labels = export.labels.into_iter().map(|label| label::ActiveModel {
+            id: Set(label_ids[&label.id]),
+            workspace_id: Set(workspace.id),
+            name: Set(label.name),
+            color: Set(label.color),
+            created_at: Set(now),
+            updated_at: Set(now),
+        });
+        Label::insert_many(labels).exec(&txn).await?;
+    }
+
+    let custom_fields: HashMap<Uuid, custom_field::Model> = export
+        .custom_fields
+        .into_iter()
+        .map(|field| {
+            let model = custom_field::Model {
+                id: Uuid::new_v4(),
+                workspace_id: workspace.id,
+                name: field.name,
+                field_type: field.field_type,
+                options: field.options,
+                position: field.position,
+                created_at: now,
+                updated_at: now,
+            };
+            (field.id, model)
+        })
+        .collect();
+
+    if !custom_fields.is_empty() {
+        CustomField::insert_many(
+            custom_fields
+                .values()
+                .cloned()
+                .map(custom_field::ActiveModel::from),
+        )
+        .exec(&txn)
+        .await?;
+    }
+
+    let topic_ids: HashMap<Uuid, Uuid> = export
+        .topics
+        .iter()
+        .map(|topic| (topic.id, Uuid::new_v4()))
+        .collect();
+    let task_ids: HashMap<Uuid, Uuid> = export
+        .tasks
+        .iter()
+        .map(|task| (task.id, Uuid::new_v4()))
+        .collect();
+
+    if !export.topics.is_empty() {
+        let topics = export.topics.into_iter().map(|topic| topic::ActiveModel {
+            id: Set(topic_ids[&topic.id]),
+            workspace_id: Set(workspace.id),
+            name: Set(topic.name),
+            description: Set(topic.description),
+            color: Set(topic.color),
+            archived_at: Set(topic.archived_at),
+            deleted_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        });
+        insert_in_chunks(&txn, topics).await?;
+    }
+
+    let mut task_labels = Vec::new();
+    for task in &export.tasks {
+        let mut seen = HashSet::new();
+        for label_id in task.label_ids.iter().filter(|id| seen.insert(**id)) {
+            task_labels.push(task_label::ActiveModel {
+                task_id: Set(task_ids[&task.id]),
+                label_id: Set(label_ids[label_id]),
+                created_at: Set(now),
+            });
+        }
+    }
+
+    let members = workspace_member_ids(&txn, workspace.id).await?;
+    let mut task_assignees = Vec::new();
+    for task in &export.tasks {
+        let mut seen = HashSet::new();
+        for user_id in task
+            .assignee_ids
+            .iter()
+            .filter(|id| members.contains(id) && seen.insert(**id))
+        {
+            task_assignees.push(task_assignee::ActiveModel {
+                task_id: Set(task_ids[&task.id]),
+                user_id: Set(*user_id),
+                created_at: Set(now),
+            });
+        }
+    }
+
+    // Values are checked against their field here, as they are in the API
+    let mut custom_field_values = Vec::new();
+    for task in &export.tasks {
+        let mut seen = HashSet::new();
+        for exported in task
+            .custom_field_values
+            .iter()
+            .filter(|value| seen.insert(value.field_id))
+        {
+            let field = &custom_fields[&exported.field_id];
+            if let Some(value) =
+                build_custom_field_value(field, task_ids[&task.id], &exported.value, now)?
+            {
+                custom_field_values.push(task_custom_field_value::ActiveModel::from(value));
+            }
+        }
+    }
+
+    if !export.tasks.is_empty() {
+        // Older documents carry no ranks and edited ones may carry malformed
+        // keys; fall back to document order, which exports write by rank.
+        let mut ranks = if export.tasks.iter().any(|task| !is_valid_rank(&task.rank)) {
+            rank_sequence(export.tasks.len())
+        } else {
+            Vec::new()
+        }
+        .into_iter();
+        let tasks = export.tasks.into_iter().map(|task| {
+            let task_type_id = task_type_for(&task);
+            let rank = ranks.next().unwrap_or(task.rank);
+            let status = match task.status_id {
+                Some(status_id) => Some(status_ids[&status_id]),
+                None => status_for_completion(task.completed),
+            };
+            task::ActiveModel {
+                id: Set(task_ids[&task.id]),
+                topic_id: Set(topic_ids[&task.topic_id]),
+                parent_task_id: Set(task.parent_task_id.map(|id| task_ids[&id])),
+                title: Set(task.title),
+                description: Set(task.description),
+                start_time: Set(task.start_time),
+                end_time: Set(task.end_time),
+                task_type_id: Set(task_type_id),
+                status_id: Set(status.map(|s| s.id)),
+                rank: Set(rank),
+                color: Set(task.color),
+                priority: Set(task.priority.unwrap_or_else(|| {
+                    TaskPriority::from_urgent(TaskPriority::default(), task.urgent)
+                })),
+                completed: Set(status.map_or(task.completed, |s| s.category.is_done())),
+                due_date: Set(task.due_date),
+                estimate_minutes: Set(task.estimate_minutes),
+                remaining_minutes: Set(task.remaining_minutes),
+                recurrence_type: Set(task.recurrence_type),
+                recurrence_interval: Set(task.recurrence_interval),
+                recurrence_days: Set(task.recurrence_days),
+                recurrence_end_date: Set(task.recurrence_end_date),
+                deleted_at: Set(None),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+        });
+        insert_in_chunks(&txn, tasks).await?;
+    }
+
+    if !task_labels.is_empty() {
+        insert_in_chunks(&txn, task_labels).await?;
+    }
+
+    if !task_assignees.is_empty() {
+        insert_in_chunks(&txn, task_assignees).await?;
+    }
+
+    if !custom_field_values.is_empty() {
+        insert_in_chunks(&txn, custom_field_values).await?;
+    }
+
+    if !export.dependencies.is_empty() {
+        let dependencies = export
+            .dependencies
+            .into_iter()
+            .map(|dependency| task_dependency::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                blocking_task_id: Set(task_ids[&dependency.blocking_task_id]),
+                blocked_task_id: Set(task_ids[&dependency.blocked_task_id]),
+                dependency_type: Set(dependency.dependency_type),
+                created_at: Set(now),
+            })
+            .collect::<Vec<_>>();
+        insert_in_chunks(&txn, dependencies).await?;
+    }
+
+    if !export.task_exceptions.is_empty() {
+        let exceptions = export
+            .task_exceptions
+            .into_iter()
+            .map(|exception| task_exception::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                task_id: Set(task_ids[&exception.task_id]),
+                original_date: Set(exception.original_date),
+                is_completed: Set(exception.is_completed),
+                created_at: Set(now),
+                updated_at: Set(now),
+            });
+        insert_in_chunks(&txn, exceptions).await?;
+    }
+
+    if !export.substeps.is_empty() {
+        let mut ranks = if export
+            .substeps
+            .iter()
+            .any(|substep| !is_valid_rank(&substep.rank))
+        {
+            rank_sequence(export.substeps.len())
+        } else {
+            Vec::new()
+        }
+        .into_iter();
+        let substeps = export
+            .substeps
+            .into_iter()
+            .map(|substep| task_substep::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                task_id: Set(task_ids[&substep.task_id]),
+                description: Set(substep.description),
+                completed: Set(substep.completed),
+                rank: Set(ranks.next().unwrap_or(substep.rank)),
+                created_at: Set(now),
+                updated_at: Set(now),
+            });
+        insert_in_chunks(&txn, substeps).await?;
+    }
+
+    check_topic_quota(&txn, config, user_id, workspace.id, 0).await?;
+    check_task_quota(&txn, config, user_id, workspace.id, 0).await?;
+
+    txn.commit().await?;
+
+    Ok(WorkspaceResponse::from(workspace))
+}
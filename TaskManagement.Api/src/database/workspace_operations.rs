@@ -1,5 +1,16 @@
+use crate::config::Config;
+use crate::database::{
+    check_share_link_quota, check_task_quota, check_topic_quota, copy_workspace_contents,
+    field_changes, get_workspace_usage, get_workspace_usages, notify, record_activity,
+    seed_default_task_statuses, seed_default_task_types, NewActivity, NewNotification,
+    WorkspaceCopyOptions, WorkspaceUsage,
+};
 use crate::errors::{AppError, AppResult};
-use crate::models::{user, workspace, workspace_share_link};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    notification::NotificationKind,
+    user, workspace, workspace_share_link, workspace_transfer,
+};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -8,6 +19,8 @@ use uuid::Uuid;
 pub struct CreateWorkspaceRequest {
     pub name: String,
     pub description: Option<String>,
+    pub template_id: Option<Uuid>,
+    pub date_offset_days: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,10 +35,46 @@ pub struct WorkspaceResponse {
     pub owner_id: Uuid,
     pub name: String,
     pub description: Option<String>,
+    pub is_template: bool,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub usage: Option<WorkspaceUsage>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateWorkspaceTransferRequest {
+    pub recipient_email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceTransferResponse {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub from_user_id: Uuid,
+    pub to_user_id: Uuid,
+    pub accepted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub declined_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub cancelled_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<workspace_transfer::Model> for WorkspaceTransferResponse {
+    fn from(transfer: workspace_transfer::Model) -> Self {
+        Self {
+            id: transfer.id,
+            workspace_id: transfer.workspace_id,
+            from_user_id: transfer.from_user_id,
+            to_user_id: transfer.to_user_id,
+            accepted_at: transfer.accepted_at,
+            declined_at: transfer.declined_at,
+            cancelled_at: transfer.cancelled_at,
+            created_at: transfer.created_at,
+        }
+    }
+}
+
 impl From<workspace::Model> for WorkspaceResponse {
     fn from(workspace: workspace::Model) -> Self {
         Self {
@@ -33,6 +82,10 @@ impl From<workspace::Model> for WorkspaceResponse {
             owner_id: workspace.owner_id,
             name: workspace.name,
             description: workspace.description,
+            is_template: workspace.is_template,
+            archived_at: workspace.archived_at,
+            deleted_at: workspace.deleted_at,
+            usage: None,
             created_at: workspace.created_at,
             updated_at: workspace.updated_at,
         }
@@ -51,6 +104,8 @@ pub async fn create_default_workspace(
     };
 
     let workspace = workspace.insert(db).await?;
+    seed_default_task_statuses(db, workspace.id).await?;
+    seed_default_task_types(db, workspace.id).await?;
     Ok(workspace)
 }
 
@@ -66,6 +121,8 @@ pub async fn ensure_default_workspace(
     if let Some(workspace_id) = user_model.default_workspace_id {
         if let Some(existing) = workspace::Entity::find_by_id(workspace_id)
             .filter(workspace::Column::OwnerId.eq(user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .filter(workspace::Column::IsTemplate.eq(false))
             .one(db)
             .await?
         {
@@ -82,18 +139,28 @@ pub async fn ensure_default_workspace(
     Ok(workspace)
 }
 
+/// Loads a live workspace owned by the user.
+pub(crate) async fn find_owned_workspace<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<workspace::Model> {
+    workspace::Entity::find_by_id(workspace_id)
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))
+}
+
 pub async fn resolve_workspace_id(
     db: &DatabaseConnection,
     user_id: Uuid,
     requested: Option<Uuid>,
 ) -> AppResult<Uuid> {
     if let Some(workspace_id) = requested {
-        let exists = workspace::Entity::find_by_id(workspace_id)
-            .filter(workspace::Column::OwnerId.eq(user_id))
-            .one(db)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
-        return Ok(exists.id);
+        let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+        return Ok(workspace.id);
     }
 
     let workspace = ensure_default_workspace(db, user_id).await?;
@@ -102,9 +169,12 @@ pub async fn resolve_workspace_id(
 
 pub async fn create_workspace(
     db: &DatabaseConnection,
+    config: &Config,
     user_id: Uuid,
     request: CreateWorkspaceRequest,
 ) -> AppResult<WorkspaceResponse> {
+    let txn = db.begin().await?;
+
     let workspace = workspace::ActiveModel {
         owner_id: Set(user_id),
         name: Set(request.name),
@@ -112,35 +182,87 @@ pub async fn create_workspace(
         ..Default::default()
     };
 
-    let workspace = workspace.insert(db).await?;
+    let workspace = workspace.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&workspace))),
+    )
+    .await?;
+
+    if let Some(template_id) = request.template_id {
+        let template = workspace::Entity::find_by_id(template_id)
+            .filter(workspace::Column::OwnerId.eq(user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .filter(workspace::Column::IsTemplate.eq(true))
+            .one(&txn)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+        let options = WorkspaceCopyOptions {
+            date_offset: request.date_offset_days.map(chrono::Duration::days),
+            reset_completion: true,
+        };
+        copy_workspace_contents(&txn, template.id, workspace.id, &options).await?;
+        check_topic_quota(&txn, config, user_id, workspace.id, 0).await?;
+        check_task_quota(&txn, config, user_id, workspace.id, 0).await?;
+    } else {
+        seed_default_task_statuses(&txn, workspace.id).await?;
+        seed_default_task_types(&txn, workspace.id).await?;
+    }
 
     let user_model = user::Entity::find_by_id(user_id)
-        .one(db)
+        .one(&txn)
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
     if user_model.default_workspace_id.is_none() {
         let mut user_active: user::ActiveModel = user_model.into();
         user_active.default_workspace_id = Set(Some(workspace.id));
-        let _ = user_active.update(db).await?;
+        let _ = user_active.update(&txn).await?;
     }
 
+    txn.commit().await?;
+
     Ok(WorkspaceResponse::from(workspace))
 }
 
 pub async fn get_workspaces_by_user(
     db: &DatabaseConnection,
     user_id: Uuid,
+    include_archived: bool,
 ) -> AppResult<Vec<WorkspaceResponse>> {
-    let workspaces = workspace::Entity::find()
+    let mut query = workspace::Entity::find()
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(workspace::Column::IsTemplate.eq(false));
+
+    if !include_archived {
+        query = query.filter(workspace::Column::ArchivedAt.is_null());
+    }
+
+    let workspaces = query
         .order_by_asc(workspace::Column::Name)
         .all(db)
         .await?;
 
+    let workspace_ids: Vec<Uuid> = workspaces.iter().map(|w| w.id).collect();
+    let mut usages = get_workspace_usages(db, &workspace_ids).await?;
+
     Ok(workspaces
         .into_iter()
-        .map(WorkspaceResponse::from)
+        .map(|workspace| {
+            let usage = usages.remove(&workspace.id);
+            let mut response = WorkspaceResponse::from(workspace);
+            response.usage = usage;
+            response
+        })
         .collect())
 }
 
@@ -149,13 +271,12 @@ pub async fn get_workspace_by_id(
     user_id: Uuid,
     workspace_id: Uuid,
 ) -> AppResult<WorkspaceResponse> {
-    let workspace = workspace::Entity::find_by_id(workspace_id)
-        .filter(workspace::Column::OwnerId.eq(user_id))
-        .one(db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
 
-    Ok(WorkspaceResponse::from(workspace))
+    let usage = get_workspace_usage(db, workspace.id).await?;
+    let mut response = WorkspaceResponse::from(workspace);
+    response.usage = Some(usage);
+    Ok(response)
 }
 
 pub async fn update_workspace(
@@ -164,12 +285,9 @@ pub async fn update_workspace(
     workspace_id: Uuid,
     request: UpdateWorkspaceRequest,
 ) -> AppResult<WorkspaceResponse> {
-    let workspace = workspace::Entity::find_by_id(workspace_id)
-        .filter(workspace::Column::OwnerId.eq(user_id))
-        .one(db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
 
+    let before = workspace.clone();
     let mut workspace: workspace::ActiveModel = workspace.into();
 
     if let Some(name) = request.name {
@@ -180,7 +298,22 @@ pub async fn update_workspace(
         workspace.description = Set(Some(description));
     }
 
-    let workspace = workspace.update(db).await?;
+    let txn = db.begin().await?;
+    let workspace = workspace.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&workspace))),
+    )
+    .await?;
+    txn.commit().await?;
+
     Ok(WorkspaceResponse::from(workspace))
 }
 
@@ -189,16 +322,113 @@ pub async fn delete_workspace(
     user_id: Uuid,
     workspace_id: Uuid,
 ) -> AppResult<bool> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let mut workspace: workspace::ActiveModel = workspace.into();
+    workspace.deleted_at = Set(Some(chrono::Utc::now()));
+
+    let txn = db.begin().await?;
+    workspace.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace_id,
+            ActivityAction::Deleted,
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    reassign_default_workspace(db, user_id, workspace_id).await?;
+
+    Ok(true)
+}
+
+pub async fn archive_workspace(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    archived: bool,
+) -> AppResult<WorkspaceResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let mut workspace: workspace::ActiveModel = workspace.into();
+    workspace.archived_at = Set(archived.then(chrono::Utc::now));
+    workspace.updated_at = Set(chrono::Utc::now());
+
+    let action = if archived {
+        ActivityAction::Archived
+    } else {
+        ActivityAction::Unarchived
+    };
+
+    let txn = db.begin().await?;
+    let workspace = workspace.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            action,
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WorkspaceResponse::from(workspace))
+}
+
+pub async fn restore_workspace(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<WorkspaceResponse> {
     let workspace = workspace::Entity::find_by_id(workspace_id)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_not_null())
         .one(db)
         .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+        .ok_or_else(|| AppError::NotFound("Workspace not found in trash".to_string()))?;
 
-    workspace::Entity::delete_by_id(workspace.id)
-        .exec(db)
-        .await?;
+    let mut workspace: workspace::ActiveModel = workspace.into();
+    workspace.deleted_at = Set(None);
+    workspace.updated_at = Set(chrono::Utc::now());
+
+    let txn = db.begin().await?;
+    let workspace = workspace.update(&txn).await?;
+    // Its contents count towards the user's limits again
+    check_topic_quota(&txn, config, user_id, workspace.id, 0).await?;
+    check_task_quota(&txn, config, user_id, workspace.id, 0).await?;
+    check_share_link_quota(&txn, config, user_id, workspace.id, 0).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Restored,
+        ),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WorkspaceResponse::from(workspace))
+}
 
+/// Points `user_id`'s default workspace at their oldest remaining workspace
+/// if it currently refers to `workspace_id`, which they no longer own.
+async fn reassign_default_workspace<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<()> {
     let user_model = user::Entity::find_by_id(user_id)
         .one(db)
         .await?
@@ -207,6 +437,8 @@ pub async fn delete_workspace(
     if user_model.default_workspace_id == Some(workspace_id) {
         let remaining = workspace::Entity::find()
             .filter(workspace::Column::OwnerId.eq(user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .filter(workspace::Column::IsTemplate.eq(false))
             .order_by_asc(workspace::Column::CreatedAt)
             .one(db)
             .await?;
@@ -216,7 +448,282 @@ pub async fn delete_workspace(
         user_active.update(db).await?;
     }
 
-    Ok(true)
+    Ok(())
+}
+
+pub async fn create_workspace_transfer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CreateWorkspaceTransferRequest,
+) -> AppResult<WorkspaceTransferResponse> {
+    let workspace = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let recipient = user::Entity::find()
+        .filter(user::Column::Email.eq(&request.recipient_email))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Recipient not found".to_string()))?;
+
+    if recipient.id == user_id {
+        return Err(AppError::BadRequest(
+            "Cannot transfer a workspace to its current owner".to_string(),
+        ));
+    }
+
+    let pending = workspace_transfer::Entity::find()
+        .filter(workspace_transfer::Column::WorkspaceId.eq(workspace.id))
+        .filter(workspace_transfer::Column::AcceptedAt.is_null())
+        .filter(workspace_transfer::Column::DeclinedAt.is_null())
+        .filter(workspace_transfer::Column::CancelledAt.is_null())
+        .one(db)
+        .await?;
+
+    if pending.is_some() {
+        return Err(AppError::BadRequest(
+            "Workspace already has a pending transfer".to_string(),
+        ));
+    }
+
+    let transfer = workspace_transfer::ActiveModel {
+        workspace_id: Set(workspace.id),
+        from_user_id: Set(user_id),
+        to_user_id: Set(recipient.id),
+        accepted_at: Set(None),
+        declined_at: Set(None),
+        cancelled_at: Set(None),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+    let transfer = transfer.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Transfer,
+            transfer.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&transfer))),
+    )
+    .await?;
+    notify(
+        &txn,
+        [NewNotification {
+            user_id: recipient.id,
+            kind: NotificationKind::WorkspaceTransfer,
+            title: format!("You were offered the workspace {}", workspace.name),
+            body: None,
+            actor_id: Some(user_id),
+            workspace_id: Some(workspace.id),
+            task_id: None,
+        }],
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WorkspaceTransferResponse::from(transfer))
+}
+
+pub async fn list_workspace_transfers(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+) -> AppResult<Vec<WorkspaceTransferResponse>> {
+    find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let transfers = workspace_transfer::Entity::find()
+        .filter(workspace_transfer::Column::WorkspaceId.eq(workspace_id))
+        .order_by_desc(workspace_transfer::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(transfers
+        .into_iter()
+        .map(WorkspaceTransferResponse::from)
+        .collect())
+}
+
+pub async fn list_incoming_workspace_transfers(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> AppResult<Vec<WorkspaceTransferResponse>> {
+    let transfers = workspace_transfer::Entity::find()
+        .filter(workspace_transfer::Column::ToUserId.eq(user_id))
+        .filter(workspace_transfer::Column::AcceptedAt.is_null())
+        .filter(workspace_transfer::Column::DeclinedAt.is_null())
+        .filter(workspace_transfer::Column::CancelledAt.is_null())
+        .order_by_desc(workspace_transfer::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(transfers
+        .into_iter()
+        .map(WorkspaceTransferResponse::from)
+        .collect())
+}
+
+pub async fn cancel_workspace_transfer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    transfer_id: Uuid,
+) -> AppResult<WorkspaceTransferResponse> {
+    let transfer = workspace_transfer::Entity::find_by_id(transfer_id)
+        .filter(workspace_transfer::Column::WorkspaceId.eq(workspace_id))
+        .filter(workspace_transfer::Column::FromUserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+    if !transfer.is_pending() {
+        return Err(AppError::BadRequest("Transfer is no longer pending".to_string()));
+    }
+
+    let before = transfer.clone();
+    let mut transfer: workspace_transfer::ActiveModel = transfer.into();
+    transfer.cancelled_at = Set(Some(chrono::Utc::now()));
+
+    let txn = db.begin().await?;
+    let transfer = transfer.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            transfer.workspace_id,
+            user_id,
+            ActivityEntity::Transfer,
+            transfer.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&transfer))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WorkspaceTransferResponse::from(transfer))
+}
+
+pub async fn decline_workspace_transfer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    transfer_id: Uuid,
+) -> AppResult<WorkspaceTransferResponse> {
+    let transfer = workspace_transfer::Entity::find_by_id(transfer_id)
+        .filter(workspace_transfer::Column::ToUserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+    if !transfer.is_pending() {
+        return Err(AppError::BadRequest("Transfer is no longer pending".to_string()));
+    }
+
+    let before = transfer.clone();
+    let mut transfer: workspace_transfer::ActiveModel = transfer.into();
+    transfer.declined_at = Set(Some(chrono::Utc::now()));
+
+    let txn = db.begin().await?;
+    let transfer = transfer.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            transfer.workspace_id,
+            user_id,
+            ActivityEntity::Transfer,
+            transfer.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&transfer))),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(WorkspaceTransferResponse::from(transfer))
+}
+
+pub async fn accept_workspace_transfer(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    transfer_id: Uuid,
+) -> AppResult<WorkspaceResponse> {
+    let txn = db.begin().await?;
+
+    let transfer = workspace_transfer::Entity::find_by_id(transfer_id)
+        .filter(workspace_transfer::Column::ToUserId.eq(user_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+    if !transfer.is_pending() {
+        return Err(AppError::BadRequest("Transfer is no longer pending".to_string()));
+    }
+
+    // The nominating user must still own the workspace; otherwise the
+    // transfer is stale and accepting it would hijack someone else's data.
+    let workspace = workspace::Entity::find_by_id(transfer.workspace_id)
+        .filter(workspace::Column::OwnerId.eq(transfer.from_user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+
+    let previous_owner_id = transfer.from_user_id;
+    let workspace_before = workspace.clone();
+    let transfer_before = transfer.clone();
+
+    let mut workspace: workspace::ActiveModel = workspace.into();
+    workspace.owner_id = Set(user_id);
+    workspace.updated_at = Set(chrono::Utc::now());
+    let workspace = workspace.update(&txn).await?;
+
+    let mut transfer: workspace_transfer::ActiveModel = transfer.into();
+    transfer.accepted_at = Set(Some(chrono::Utc::now()));
+    let transfer = transfer.update(&txn).await?;
+
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Transfer,
+            transfer.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&transfer_before), Some(&transfer))),
+    )
+    .await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&workspace_before), Some(&workspace))),
+    )
+    .await?;
+
+    reassign_default_workspace(&txn, previous_owner_id, workspace.id).await?;
+
+    let recipient = user::Entity::find_by_id(user_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if recipient.default_workspace_id.is_none() {
+        let mut recipient: user::ActiveModel = recipient.into();
+        recipient.default_workspace_id = Set(Some(workspace.id));
+        recipient.update(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(WorkspaceResponse::from(workspace))
 }
 
 pub async fn user_owns_workspace(
@@ -226,6 +733,7 @@ pub async fn user_owns_workspace(
 ) -> AppResult<()> {
     let workspace = workspace::Entity::find_by_id(workspace_id)
         .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::Forbidden("Access denied".to_string()))?;
@@ -258,6 +766,7 @@ pub async fn get_workspace_by_share_token(
     }
 
     let workspace = workspace::Entity::find_by_id(share_link.workspace_id)
+        .filter(workspace::Column::DeletedAt.is_null())
         .one(db)
         .await?
         .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
@@ -1,5 +1,14 @@
+use crate::config::Config;
+use crate::database::{
+    check_share_link_quota, enqueue_webhook_event, field_changes, find_owned_workspace,
+    record_activity, NewActivity,
+};
 use crate::errors::{AppError, AppResult};
-use crate::models::{workspace, workspace_share_link};
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    webhook::WebhookEvent,
+    workspace_share_link,
+};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -34,17 +43,27 @@ impl From<workspace_share_link::Model> for WorkspaceShareLinkResponse {
     }
 }
 
+/// Webhook payloads leave out the token, which grants access on its own.
+fn share_link_event_data(link: &workspace_share_link::Model) -> serde_json::Value {
+    serde_json::json!({
+        "share_link": {
+            "id": link.id,
+            "created_by": link.created_by,
+            "expires_at": link.expires_at,
+            "revoked_at": link.revoked_at,
+            "created_at": link.created_at,
+        }
+    })
+}
+
 pub async fn create_workspace_share_link(
     db: &DatabaseConnection,
+    config: &Config,
     user_id: Uuid,
     workspace_id: Uuid,
     request: CreateWorkspaceShareLinkRequest,
 ) -> AppResult<WorkspaceShareLinkResponse> {
-    let _workspace = workspace::Entity::find_by_id(workspace_id)
-        .filter(workspace::Column::OwnerId.eq(user_id))
-        .one(db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+    find_owned_workspace(db, user_id, workspace_id).await?;
 
     let share_link = workspace_share_link::ActiveModel {
         workspace_id: Set(workspace_id),
@@ -55,7 +74,30 @@ pub async fn create_workspace_share_link(
         ..Default::default()
     };
 
-    let share_link = share_link.insert(db).await?;
+    let txn = db.begin().await?;
+    check_share_link_quota(&txn, config, user_id, workspace_id, 1).await?;
+    let share_link = share_link.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::ShareLink,
+            share_link.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&share_link))),
+    )
+    .await?;
+    enqueue_webhook_event(
+        &txn,
+        workspace_id,
+        WebhookEvent::ShareLinkCreated,
+        share_link_event_data(&share_link),
+    )
+    .await?;
+    txn.commit().await?;
+
     Ok(WorkspaceShareLinkResponse::from(share_link))
 }
 
@@ -64,11 +106,7 @@ pub async fn list_workspace_share_links(
     user_id: Uuid,
     workspace_id: Uuid,
 ) -> AppResult<Vec<WorkspaceShareLinkResponse>> {
-    let _workspace = workspace::Entity::find_by_id(workspace_id)
-        .filter(workspace::Column::OwnerId.eq(user_id))
-        .one(db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+    find_owned_workspace(db, user_id, workspace_id).await?;
 
     let links = workspace_share_link::Entity::find()
         .filter(workspace_share_link::Column::WorkspaceId.eq(workspace_id))
@@ -88,11 +126,7 @@ pub async fn revoke_workspace_share_link(
     workspace_id: Uuid,
     share_link_id: Uuid,
 ) -> AppResult<WorkspaceShareLinkResponse> {
-    let _workspace = workspace::Entity::find_by_id(workspace_id)
-        .filter(workspace::Column::OwnerId.eq(user_id))
-        .one(db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Workspace not found".to_string()))?;
+    find_owned_workspace(db, user_id, workspace_id).await?;
 
     let share_link = workspace_share_link::Entity::find_by_id(share_link_id)
         .filter(workspace_share_link::Column::WorkspaceId.eq(workspace_id))
@@ -100,9 +134,32 @@ pub async fn revoke_workspace_share_link(
         .await?
         .ok_or_else(|| AppError::NotFound("Share link not found".to_string()))?;
 
+    let before = share_link.clone();
     let mut share_link: workspace_share_link::ActiveModel = share_link.into();
     share_link.revoked_at = Set(Some(chrono::Utc::now()));
 
-    let share_link = share_link.update(db).await?;
+    let txn = db.begin().await?;
+    let share_link = share_link.update(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace_id,
+            user_id,
+            ActivityEntity::ShareLink,
+            share_link.id,
+            ActivityAction::Updated,
+        )
+        .with_changes(field_changes(Some(&before), Some(&share_link))),
+    )
+    .await?;
+    enqueue_webhook_event(
+        &txn,
+        workspace_id,
+        WebhookEvent::ShareLinkRevoked,
+        share_link_event_data(&share_link),
+    )
+    .await?;
+    txn.commit().await?;
+
     Ok(WorkspaceShareLinkResponse::from(share_link))
 }
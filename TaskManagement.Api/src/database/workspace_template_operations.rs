@@ -0,0 +1,461 @@
+use crate::config::Config;
+use crate::database::{
+    check_task_quota, check_topic_quota, field_changes, find_owned_workspace, insert_in_chunks,
+    record_activity, workspace_member_ids, NewActivity, WorkspaceResponse,
+};
+use crate::errors::AppResult;
+use crate::models::{
+    activity_log::{ActivityAction, ActivityEntity},
+    custom_field::{self, Entity as CustomField},
+    label::{self, Entity as Label},
+    task::{self, Entity as Task},
+    task_assignee::{self, Entity as TaskAssignee},
+    task_custom_field_value::{self, Entity as TaskCustomFieldValue},
+    task_dependency::{self, Entity as TaskDependency},
+    task_label::{self, Entity as TaskLabel},
+    task_status::{self, Entity as TaskStatus, TaskStatusCategory},
+    task_status_transition::{self, Entity as TaskStatusTransition},
+    task_substep::{self, Entity as TaskSubstep},
+    task_type::{self, Entity as TaskType},
+    topic::{self, Entity as Topic},
+    workspace::{self, Entity as Workspace},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneWorkspaceRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub date_offset_days: Option<i64>,
+    pub reset_completion: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveWorkspaceTemplateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceCopyOptions {
+    pub date_offset: Option<chrono::Duration>,
+    pub reset_completion: bool,
+}
+
+/// Copies the statuses, task types, labels, custom fields and live topics,
+/// tasks, dependencies, substeps and custom field values of one workspace into
+/// another, giving every row a fresh ID. Trashed rows are left behind, and
+/// so are assignees who are not members of the target workspace.
+pub async fn copy_workspace_contents<C: ConnectionTrait>(
+    db: &C,
+    source_workspace_id: Uuid,
+    target_workspace_id: Uuid,
+    options: &WorkspaceCopyOptions,
+) -> AppResult<()> {
+    let now = chrono::Utc::now();
+    let shift = |value: Option<chrono::DateTime<chrono::Utc>>| match options.date_offset {
+        Some(offset) => value.map(|v| v + offset),
+        None => value,
+    };
+
+    let statuses = TaskStatus::find()
+        .filter(task_status::Column::WorkspaceId.eq(source_workspace_id))
+        .order_by_asc(task_status::Column::Position)
+        .all(db)
+        .await?;
+
+    let mut status_ids = HashMap::new();
+    let mut new_statuses = Vec::with_capacity(statuses.len());
+    let mut initial_status_id = None;
+
+    for status in statuses {
+        let new_id = Uuid::new_v4();
+        status_ids.insert(status.id, new_id);
+        if initial_status_id.is_none() && status.category == TaskStatusCategory::Todo {
+            initial_status_id = Some(new_id);
+        }
+
+        let mut active: task_status::ActiveModel = status.into();
+        active.id = Set(new_id);
+        active.workspace_id = Set(target_workspace_id);
+        active.created_at = Set(now);
+        active.updated_at = Set(now);
+        new_statuses.push(active);
+    }
+
+    if !new_statuses.is_empty() {
+        TaskStatus::insert_many(new_statuses).exec(db).await?;
+
+        let transitions = TaskStatusTransition::find()
+            .filter(task_status_transition::Column::WorkspaceId.eq(source_workspace_id))
+            .all(db)
+            .await?;
+
+        if !transitions.is_empty() {
+            let new_transitions = transitions.into_iter().map(|transition| {
+                task_status_transition::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    workspace_id: Set(target_workspace_id),
+                    from_status_id: Set(status_ids[&transition.from_status_id]),
+                    to_status_id: Set(status_ids[&transition.to_status_id]),
+                    created_at: Set(now),
+                }
+            });
+            TaskStatusTransition::insert_many(new_transitions)
+                .exec(db)
+                .await?;
+        }
+    }
+
+    let task_types = TaskType::find()
+        .filter(task_type::Column::WorkspaceId.eq(source_workspace_id))
+        .all(db)
+        .await?;
+
+    let task_type_ids: HashMap<Uuid, Uuid> =
+        task_types.iter().map(|t| (t.id, Uuid::new_v4())).collect();
+
+    if !task_types.is_empty() {
+        let new_task_types = task_types.into_iter().map(|task_type| {
+            let new_id = task_type_ids[&task_type.id];
+            let mut active: task_type::ActiveModel = task_type.into();
+            active.id = Set(new_id);
+            active.workspace_id = Set(target_workspace_id);
+            active.created_at = Set(now);
+            active.updated_at = Set(now);
+            active
+        });
+        TaskType::insert_many(new_task_types).exec(db).await?;
+    }
+
+    let labels = Label::find()
+        .filter(label::Column::WorkspaceId.eq(source_workspace_id))
+        .all(db)
+        .await?;
+
+    let label_ids: HashMap<Uuid, Uuid> = labels.iter().map(|l| (l.id, Uuid::new_v4())).collect();
+
+    if !labels.is_empty() {
+        let new_labels = labels.into_iter().map(|label| {
+            let new_id = label_ids[&label.id];
+            let mut active: label::ActiveModel = label.into();
+            active.id = Set(new_id);
+            active.workspace_id = Set(target_workspace_id);
+            active.created_at = Set(now);
+            active.updated_at = Set(now);
+            active
+        });
+        Label::insert_many(new_labels).exec(db).await?;
+    }
+
+    let custom_fields = CustomField::find()
+        .filter(custom_field::Column::WorkspaceId.eq(source_workspace_id))
+        .all(db)
+        .await?;
+
+    let custom_field_ids: HashMap<Uuid, Uuid> =
+        custom_fields.iter().map(|f| (f.id, Uuid::new_v4())).collect();
+
+    if !custom_fields.is_empty() {
+        let new_custom_fields = custom_fields.into_iter().map(|field| {
+            let new_id = custom_field_ids[&field.id];
+            let mut active: custom_field::ActiveModel = field.into();
+            active.id = Set(new_id);
+            active.workspace_id = Set(target_workspace_id);
+            active.created_at = Set(now);
+            active.updated_at = Set(now);
+            active
+        });
+        CustomField::insert_many(new_custom_fields).exec(db).await?;
+    }
+
+    let topics = Topic::find()
+        .filter(topic::Column::WorkspaceId.eq(source_workspace_id))
+        .filter(topic::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+
+    if topics.is_empty() {
+        return Ok(());
+    }
+
+    let mut topic_ids = HashMap::new();
+    let mut new_topics = Vec::with_capacity(topics.len());
+
+    for topic in topics {
+        let new_id = Uuid::new_v4();
+        topic_ids.insert(topic.id, new_id);
+
+        let mut active: topic::ActiveModel = topic.into();
+        active.id = Set(new_id);
+        active.workspace_id = Set(target_workspace_id);
+        active.created_at = Set(now);
+        active.updated_at = Set(now);
+        new_topics.push(active);
+    }
+
+    insert_in_chunks(db, new_topics).await?;
+
+    let tasks = Task::find()
+        .filter(task::Column::TopicId.is_in(topic_ids.keys().copied().collect::<Vec<_>>()))
+        .filter(task::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    let task_ids: HashMap<Uuid, Uuid> = tasks.iter().map(|t| (t.id, Uuid::new_v4())).collect();
+    let mut new_tasks = Vec::with_capacity(tasks.len());
+
+    for task in tasks {
+        let new_id = task_ids[&task.id];
+        // Subtasks of a trashed parent become top-level in the copy.
+        let parent_task_id = task.parent_task_id.and_then(|id| task_ids.get(&id).copied());
+
+        let new_topic_id = topic_ids[&task.topic_id];
+        let start_time = shift(task.start_time);
+        let end_time = shift(task.end_time);
+        let due_date = shift(task.due_date);
+        let recurrence_end_date = shift(task.recurrence_end_date);
+        let status_id = task.status_id.and_then(|id| status_ids.get(&id).copied());
+        let task_type_id = task.task_type_id.and_then(|id| task_type_ids.get(&id).copied());
+
+        let mut active: task::ActiveModel = task.into();
+        active.id = Set(new_id);
+        active.topic_id = Set(new_topic_id);
+        active.parent_task_id = Set(parent_task_id);
+        active.start_time = Set(start_time);
+        active.end_time = Set(end_time);
+        active.due_date = Set(due_date);
+        active.recurrence_end_date = Set(recurrence_end_date);
+        active.status_id = Set(status_id);
+        active.task_type_id = Set(task_type_id);
+        if options.reset_completion {
+            active.status_id = Set(initial_status_id);
+            active.completed = Set(false);
+        }
+        active.created_at = Set(now);
+        active.updated_at = Set(now);
+        new_tasks.push(active);
+    }
+
+    insert_in_chunks(db, new_tasks).await?;
+
+    let dependencies = TaskDependency::find()
+        .filter(
+            task_dependency::Column::BlockedTaskId
+                .is_in(task_ids.keys().copied().collect::<Vec<_>>()),
+        )
+        .all(db)
+        .await?;
+
+    let new_dependencies: Vec<task_dependency::ActiveModel> = dependencies
+        .into_iter()
+        .filter_map(|dependency| {
+            let blocking_task_id = *task_ids.get(&dependency.blocking_task_id)?;
+            let blocked_task_id = task_ids[&dependency.blocked_task_id];
+            Some(task_dependency::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                blocking_task_id: Set(blocking_task_id),
+                blocked_task_id: Set(blocked_task_id),
+                dependency_type: Set(dependency.dependency_type),
+                created_at: Set(now),
+            })
+        })
+        .collect();
+
+    if !new_dependencies.is_empty() {
+        insert_in_chunks(db, new_dependencies).await?;
+    }
+
+    let task_labels = TaskLabel::find()
+        .filter(task_label::Column::TaskId.is_in(task_ids.keys().copied().collect::<Vec<_>>()))
+        .all(db)
+        .await?;
+
+    if !task_labels.is_empty() {
+        let new_task_labels = task_labels
+            .into_iter()
+            .map(|task_label| task_label::ActiveModel {
+                task_id: Set(task_ids[&task_label.task_id]),
+                label_id: Set(label_ids[&task_label.label_id]),
+                created_at: Set(now),
+            });
+        insert_in_chunks(db, new_task_labels).await?;
+    }
+
+    let members = workspace_member_ids(db, target_workspace_id).await?;
+    let new_task_assignees: Vec<task_assignee::ActiveModel> = TaskAssignee::find()
+        .filter(task_assignee::Column::TaskId.is_in(task_ids.keys().copied().collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|task_assignee| members.contains(&task_assignee.user_id))
+        .map(|task_assignee| task_assignee::ActiveModel {
+            task_id: Set(task_ids[&task_assignee.task_id]),
+            user_id: Set(task_assignee.user_id),
+            created_at: Set(now),
+        })
+        .collect();
+
+    if !new_task_assignees.is_empty() {
+        insert_in_chunks(db, new_task_assignees).await?;
+    }
+
+    let custom_field_values = TaskCustomFieldValue::find()
+        .filter(
+            task_custom_field_value::Column::TaskId
+                .is_in(task_ids.keys().copied().collect::<Vec<_>>()),
+        )
+        .all(db)
+        .await?;
+
+    if !custom_field_values.is_empty() {
+        let new_custom_field_values = custom_field_values.into_iter().map(|value| {
+            let task_id = task_ids[&value.task_id];
+            let field_id = custom_field_ids[&value.field_id];
+            let mut active: task_custom_field_value::ActiveModel = value.into();
+            active.task_id = Set(task_id);
+            active.field_id = Set(field_id);
+            active.created_at = Set(now);
+            active.updated_at = Set(now);
+            active
+        });
+        insert_in_chunks(db, new_custom_field_values).await?;
+    }
+
+    let substeps = TaskSubstep::find()
+        .filter(task_substep::Column::TaskId.is_in(task_ids.keys().copied().collect::<Vec<_>>()))
+        .order_by_asc(task_substep::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    if substeps.is_empty() {
+        return Ok(());
+    }
+
+    let new_substeps: Vec<task_substep::ActiveModel> = substeps
+        .into_iter()
+        .map(|substep| {
+            let new_task_id = task_ids[&substep.task_id];
+            let mut active: task_substep::ActiveModel = substep.into();
+            active.id = Set(Uuid::new_v4());
+            active.task_id = Set(new_task_id);
+            if options.reset_completion {
+                active.completed = Set(false);
+            }
+            active.created_at = Set(now);
+            active.updated_at = Set(now);
+            active
+        })
+        .collect();
+
+    insert_in_chunks(db, new_substeps).await?;
+
+    Ok(())
+}
+
+async fn duplicate_workspace(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    source_workspace_id: Uuid,
+    workspace: workspace::ActiveModel,
+    options: WorkspaceCopyOptions,
+) -> AppResult<WorkspaceResponse> {
+    let txn = db.begin().await?;
+
+    let workspace = workspace.insert(&txn).await?;
+    record_activity(
+        &txn,
+        NewActivity::new(
+            workspace.id,
+            user_id,
+            ActivityEntity::Workspace,
+            workspace.id,
+            ActivityAction::Created,
+        )
+        .with_changes(field_changes(None, Some(&workspace))),
+    )
+    .await?;
+
+    copy_workspace_contents(&txn, source_workspace_id, workspace.id, &options).await?;
+    check_topic_quota(&txn, config, user_id, workspace.id, 0).await?;
+    check_task_quota(&txn, config, user_id, workspace.id, 0).await?;
+
+    txn.commit().await?;
+
+    Ok(WorkspaceResponse::from(workspace))
+}
+
+pub async fn clone_workspace(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: CloneWorkspaceRequest,
+) -> AppResult<WorkspaceResponse> {
+    let source = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let workspace = workspace::ActiveModel {
+        owner_id: Set(user_id),
+        name: Set(request
+            .name
+            .unwrap_or_else(|| format!("{} (copy)", source.name))),
+        description: Set(request.description.or_else(|| source.description.clone())),
+        is_template: Set(source.is_template),
+        ..Default::default()
+    };
+
+    let options = WorkspaceCopyOptions {
+        date_offset: request.date_offset_days.map(chrono::Duration::days),
+        reset_completion: request.reset_completion.unwrap_or(false),
+    };
+
+    duplicate_workspace(db, config, user_id, source.id, workspace, options).await
+}
+
+pub async fn save_workspace_as_template(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+    workspace_id: Uuid,
+    request: SaveWorkspaceTemplateRequest,
+) -> AppResult<WorkspaceResponse> {
+    let source = find_owned_workspace(db, user_id, workspace_id).await?;
+
+    let workspace = workspace::ActiveModel {
+        owner_id: Set(user_id),
+        name: Set(request.name.unwrap_or_else(|| source.name.clone())),
+        description: Set(request.description.or_else(|| source.description.clone())),
+        is_template: Set(true),
+        ..Default::default()
+    };
+
+    let options = WorkspaceCopyOptions {
+        date_offset: None,
+        reset_completion: true,
+    };
+
+    duplicate_workspace(db, config, user_id, source.id, workspace, options).await
+}
+
+pub async fn get_workspace_templates_by_user(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> AppResult<Vec<WorkspaceResponse>> {
+    let templates = Workspace::find()
+        .filter(workspace::Column::OwnerId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(workspace::Column::IsTemplate.eq(true))
+        .order_by_asc(workspace::Column::Name)
+        .all(db)
+        .await?;
+
+    Ok(templates.into_iter().map(WorkspaceResponse::from).collect())
+}
@@ -27,6 +27,12 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
     
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+    
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
     #[error("Internal server error: {0}")]
     Internal(String),
 }
@@ -48,6 +54,8 @@ impl<'r> Responder<'r, 'static> for AppError {
             AppError::Unauthorized(_) => (rocket::http::Status::Unauthorized, "unauthorized", self.to_string()),
             AppError::Forbidden(_) => (rocket::http::Status::Forbidden, "forbidden", self.to_string()),
             AppError::BadRequest(_) => (rocket::http::Status::BadRequest, "bad_request", self.to_string()),
+            AppError::QuotaExceeded(_) => (rocket::http::Status::Conflict, "quota_exceeded", self.to_string()),
+            AppError::Conflict(_) => (rocket::http::Status::Conflict, "conflict", self.to_string()),
             AppError::Internal(_) => (rocket::http::Status::InternalServerError, "internal_error", self.to_string()),
         };
 
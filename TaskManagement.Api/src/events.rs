@@ -0,0 +1,178 @@
+use crate::cache::Cache;
+use crate::models::{task, topic};
+use rocket::futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Redis channel every API instance publishes its workspace changes to.
+pub const EVENTS_CHANNEL: &str = "workspace_events";
+
+/// How many events a slow stream may fall behind before it misses some.
+const EVENT_BUFFER: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangedEntity {
+    Task,
+    Topic,
+    Substep,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A change to a workspace's tasks, topics or substeps. Events only name
+/// what changed; clients fetch the current state through the API.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceEvent {
+    pub workspace_id: Uuid,
+    pub entity: ChangedEntity,
+    pub action: ChangeAction,
+    pub id: Uuid,
+    pub topic_id: Option<Uuid>,
+    /// The task a substep belongs to.
+    pub task_id: Option<Uuid>,
+    /// The user who made the change, so clients can skip their own.
+    pub actor_id: Uuid,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WorkspaceEvent {
+    pub fn task(
+        action: ChangeAction,
+        actor_id: Uuid,
+        workspace_id: Uuid,
+        task: &task::Model,
+    ) -> Self {
+        Self {
+            workspace_id,
+            entity: ChangedEntity::Task,
+            action,
+            id: task.id,
+            topic_id: Some(task.topic_id),
+            task_id: None,
+            actor_id,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn topic(action: ChangeAction, actor_id: Uuid, topic: &topic::Model) -> Self {
+        Self {
+            workspace_id: topic.workspace_id,
+            entity: ChangedEntity::Topic,
+            action,
+            id: topic.id,
+            topic_id: None,
+            task_id: None,
+            actor_id,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn substep(
+        action: ChangeAction,
+        actor_id: Uuid,
+        workspace_id: Uuid,
+        task_id: Uuid,
+        substep_id: Uuid,
+    ) -> Self {
+        Self {
+            workspace_id,
+            entity: ChangedEntity::Substep,
+            action,
+            id: substep_id,
+            topic_id: None,
+            task_id: Some(task_id),
+            actor_id,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+
+    /// The SSE event name, such as `task.updated`.
+    pub fn name(&self) -> String {
+        let entity = match self.entity {
+            ChangedEntity::Task => "task",
+            ChangedEntity::Topic => "topic",
+            ChangedEntity::Substep => "substep",
+        };
+        let action = match self.action {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+            ChangeAction::Deleted => "deleted",
+        };
+        format!("{}.{}", entity, action)
+    }
+}
+
+/// Announces a committed change to the event streams of every instance.
+/// Like cache invalidation this is best-effort: a failure is logged and
+/// does not fail the request.
+pub async fn publish_workspace_event(cache: &Cache, event: WorkspaceEvent) {
+    if let Err(e) = cache.publish(EVENTS_CHANNEL, &event).await {
+        eprintln!("Failed to publish workspace event: {}", e);
+    }
+}
+
+/// Fans the events received from Redis out to the open streams of this
+/// instance.
+pub struct EventHub {
+    sender: broadcast::Sender<WorkspaceEvent>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Relays the Redis channel into the hub for as long as the server
+    /// runs, resubscribing whenever the connection drops. One subscription
+    /// serves every stream of the instance.
+    pub fn spawn_relay(&self, cache: Cache) {
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match cache.subscribe(EVENTS_CHANNEL).await {
+                    Ok(pubsub) => {
+                        let mut messages = pubsub.into_on_message();
+                        while let Some(message) = messages.next().await {
+                            let event = message
+                                .get_payload::<String>()
+                                .ok()
+                                .and_then(|payload| serde_json::from_str(&payload).ok());
+                            match event {
+                                // Sending only fails while nobody is listening
+                                Some(event) => {
+                                    let _ = sender.send(event);
+                                }
+                                None => eprintln!("Ignoring malformed workspace event"),
+                            }
+                        }
+                        eprintln!("Lost the workspace event subscription, resubscribing");
+                    }
+                    Err(e) => eprintln!("Failed to subscribe to workspace events: {}", e),
+                }
+
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+    }
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::config::Config;
+use crate::database::{
+    deliver_due_webhooks, dispatch_pending_notifications, fire_due_reminders, purge_deleted_items,
+    purge_expired_undo_tokens,
+};
+use crate::notifications::NotificationChannel;
+use crate::storage::BlobStorage;
+use crate::webhooks::WebhookSender;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Periodically hard-deletes trashed workspaces, topics and tasks once they
+/// are older than the configured retention window, along with the blobs of
+/// their attachments. Expired undo tokens are dropped on the same schedule.
+pub fn spawn_trash_purge(db: DatabaseConnection, storage: Arc<dyn BlobStorage>, config: &Config) {
+    let retention = chrono::Duration::days(config.trash_retention_days);
+    let period = Duration::from_secs(config.trash_purge_interval_seconds.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match purge_deleted_items(&db, retention).await {
+                Ok(summary) => {
+                    if summary.tasks + summary.topics + summary.workspaces > 0 {
+                        println!(
+                            "Purged trash: {} workspaces, {} topics, {} tasks",
+                            summary.workspaces, summary.topics, summary.tasks
+                        );
+                    }
+
+                    for key in &summary.attachment_keys {
+                        if let Err(e) = storage.delete(key).await {
+                            eprintln!("Failed to delete attachment blob: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to purge trash: {}", e),
+            }
+
+            if let Err(e) = purge_expired_undo_tokens(&db).await {
+                eprintln!("Failed to purge expired undo tokens: {}", e);
+            }
+        }
+    });
+}
+
+/// Periodically turns due task reminders into notifications. Safe to run
+/// on every replica: each run claims its batch with `SKIP LOCKED`, so a
+/// reminder only fires once.
+pub fn spawn_reminder_scheduler(db: DatabaseConnection, config: &Config) {
+    let period = Duration::from_secs(config.reminder_poll_interval_seconds.max(1));
+    let batch_size = config.reminder_batch_size.max(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            // Keep going while full batches come back so a backlog drains
+            // without waiting for further ticks
+            loop {
+                match fire_due_reminders(&db, batch_size).await {
+                    Ok(fired) if fired == batch_size => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        eprintln!("Failed to fire reminders: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Periodically hands undelivered notifications to the notification
+/// channel. Like the reminder scheduler it claims rows with `SKIP LOCKED`
+/// and can run on every replica.
+pub fn spawn_notification_dispatcher(
+    db: DatabaseConnection,
+    channel: Arc<dyn NotificationChannel>,
+    config: &Config,
+) {
+    let period = Duration::from_secs(config.notification_dispatch_interval_seconds.max(1));
+    let batch_size = config.notification_batch_size.max(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            loop {
+                match dispatch_pending_notifications(&db, channel.as_ref(), batch_size).await {
+                    Ok(delivered) if delivered == batch_size => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        eprintln!("Failed to dispatch notifications: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Periodically attempts due webhook deliveries from the outbox. Like the
+/// other dispatchers it can run on every replica; see
+/// `deliver_due_webhooks` for how batches are claimed.
+pub fn spawn_webhook_dispatcher(db: DatabaseConnection, sender: WebhookSender, config: &Config) {
+    let period = Duration::from_secs(config.webhook_poll_interval_seconds.max(1));
+    let batch_size = config.webhook_batch_size.max(1);
+    let max_attempts = config.webhook_max_attempts.max(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            loop {
+                match deliver_due_webhooks(&db, &sender, max_attempts, batch_size).await {
+                    Ok(attempted) if attempted == batch_size => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        eprintln!("Failed to deliver webhooks: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
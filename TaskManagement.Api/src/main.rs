@@ -6,9 +6,15 @@ mod cache;
 mod config;
 mod database;
 mod errors;
+mod events;
+mod jobs;
 mod models;
+mod notifications;
+mod rank;
 mod routes;
 mod schemas;
+mod storage;
+mod webhooks;
 
 use cache::Cache;
 use config::Config;
@@ -41,6 +47,7 @@ fn ping() -> Json<PingResponse> {
 
 async fn setup_database(
     db: &DatabaseConnection,
+    cache: &Cache,
     config: &Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // For now, we'll handle schema creation manually
@@ -71,7 +78,8 @@ async fn setup_database(
                 color: "#673ab7".to_string(),
             };
 
-            let _default_topic = create_topic(db, demo_user.id, default_topic_request).await?;
+            let _default_topic =
+                create_topic(db, cache, config, demo_user.id, default_topic_request).await?;
             println!("Created default topic for demo user");
         }
     }
@@ -94,11 +102,33 @@ async fn rocket() -> _ {
     let cache = Cache::new(&config.redis_url, config.cache_ttl_seconds)
         .expect("Failed to initialize Redis cache");
 
+    // Initialize attachment storage
+    let storage =
+        storage::blob_storage_from_config(&config).expect("Failed to initialize attachment storage");
+
+    // Initialize the channel notifications are delivered through
+    let notifications = notifications::notification_channel_from_config(&config)
+        .expect("Failed to initialize notification channel");
+
+    // Initialize the client webhook deliveries are posted with
+    let webhooks = webhooks::WebhookSender::from_config(&config)
+        .expect("Failed to initialize webhook client");
+
+    // Relay workspace changes from every instance to this one's event streams
+    let events = events::EventHub::new();
+    events.spawn_relay(cache.clone());
+
     // Setup database (migrations and demo data)
-    setup_database(&db, &config)
+    setup_database(&db, &cache, &config)
         .await
         .expect("Failed to setup database");
 
+    // Start background jobs
+    jobs::spawn_trash_purge(db.clone(), storage.clone(), &config);
+    jobs::spawn_reminder_scheduler(db.clone(), &config);
+    jobs::spawn_notification_dispatcher(db.clone(), notifications.clone(), &config);
+    jobs::spawn_webhook_dispatcher(db.clone(), webhooks, &config);
+
     // Configure CORS
     let cors = CorsOptions::default()
         .allowed_origins(AllowedOrigins::some_exact(&[
@@ -128,6 +158,8 @@ async fn rocket() -> _ {
         .manage(db)
         .manage(config)
         .manage(cache)
+        .manage(storage)
+        .manage(events)
         .attach(cors)
         .mount("/", routes![index, ping])
         .mount(
@@ -146,7 +178,10 @@ async fn rocket() -> _ {
                 routes::get_topic,
                 routes::create_topic_route,
                 routes::update_topic_route,
-                routes::delete_topic_route
+                routes::delete_topic_route,
+                routes::archive_topic_route,
+                routes::unarchive_topic_route,
+                routes::restore_topic_route
             ],
         )
         .mount(
@@ -157,22 +192,87 @@ async fn rocket() -> _ {
                 routes::create_workspace_route,
                 routes::update_workspace_route,
                 routes::delete_workspace_route,
+                routes::archive_workspace_route,
+                routes::unarchive_workspace_route,
+                routes::restore_workspace_route,
+                routes::clone_workspace_route,
+                routes::save_workspace_as_template_route,
+                routes::get_workspace_templates,
+                routes::export_workspace_route,
+                routes::import_workspace_route,
                 routes::create_workspace_share_link_route,
                 routes::get_workspace_share_links,
-                routes::revoke_workspace_share_link_route
+                routes::revoke_workspace_share_link_route,
+                routes::create_workspace_transfer_route,
+                routes::get_workspace_transfers,
+                routes::cancel_workspace_transfer_route,
+                routes::get_incoming_workspace_transfers,
+                routes::accept_workspace_transfer_route,
+                routes::decline_workspace_transfer_route,
+                routes::get_task_statuses,
+                routes::create_task_status_route,
+                routes::update_task_status_route,
+                routes::delete_task_status_route,
+                routes::get_task_status_transitions,
+                routes::set_task_status_transitions_route,
+                routes::get_labels,
+                routes::create_label_route,
+                routes::update_label_route,
+                routes::delete_label_route,
+                routes::get_task_types,
+                routes::create_task_type_route,
+                routes::update_task_type_route,
+                routes::delete_task_type_route,
+                routes::get_custom_fields,
+                routes::create_custom_field_route,
+                routes::update_custom_field_route,
+                routes::delete_custom_field_route,
+                routes::get_time_report_route,
+                routes::get_webhooks,
+                routes::create_webhook_route,
+                routes::workspace_events,
+                routes::get_workspace_activity_route
             ],
         )
         .mount("/api/shared", routes![routes::get_shared_workspace])
+        .mount("/api/trash", routes![routes::get_trash])
+        .mount("/api/undo", routes![routes::undo_route])
         .mount(
             "/api/tasks",
             routes![
                 routes::get_tasks,
+                routes::get_my_tasks,
                 routes::get_task,
                 routes::create_task_route,
                 routes::update_task_route,
                 routes::delete_task_route,
+                routes::restore_task_route,
+                routes::move_task_route,
+                routes::bulk_tasks_route,
+                routes::get_task_dependencies,
+                routes::create_task_dependency_route,
+                routes::delete_task_dependency_route,
+                routes::get_task_subtree_route,
+                routes::set_task_parent_route,
+                routes::set_task_labels_route,
+                routes::add_task_label_route,
+                routes::remove_task_label_route,
+                routes::update_task_custom_fields_route,
+                routes::set_task_assignees_route,
+                routes::add_task_assignee_route,
+                routes::remove_task_assignee_route,
                 routes::get_task_substeps,
-                routes::create_task_substep
+                routes::create_task_substep,
+                routes::get_task_comments,
+                routes::create_task_comment,
+                routes::get_task_attachments,
+                routes::upload_task_attachment,
+                routes::get_task_time_entries,
+                routes::create_task_time_entry,
+                routes::start_task_timer,
+                routes::get_task_reminders,
+                routes::create_task_reminder,
+                routes::get_task_history_route
             ],
         )
         .mount(
@@ -180,7 +280,52 @@ async fn rocket() -> _ {
             routes![
                 routes::get_substep,
                 routes::update_substep_route,
-                routes::delete_substep_route
+                routes::delete_substep_route,
+                routes::move_substep_route,
+                routes::promote_substep_route
+            ],
+        )
+        .mount(
+            "/api/comments",
+            routes![routes::update_comment_route, routes::delete_comment_route],
+        )
+        .mount(
+            "/api/attachments",
+            routes![
+                routes::get_attachment,
+                routes::download_attachment,
+                routes::delete_attachment_route
+            ],
+        )
+        .mount(
+            "/api/reminders",
+            routes![routes::update_reminder_route, routes::delete_reminder_route],
+        )
+        .mount(
+            "/api/time-entries",
+            routes![
+                routes::get_running_timer_route,
+                routes::stop_timer_route,
+                routes::update_time_entry_route,
+                routes::delete_time_entry_route
+            ],
+        )
+        .mount(
+            "/api/notifications",
+            routes![
+                routes::get_notifications,
+                routes::mark_notification_read_route,
+                routes::mark_all_notifications_read_route,
+                routes::delete_notification_route
+            ],
+        )
+        .mount(
+            "/api/webhooks",
+            routes![
+                routes::update_webhook_route,
+                routes::delete_webhook_route,
+                routes::get_webhook_deliveries_route,
+                routes::retry_webhook_delivery_route
             ],
         )
 }
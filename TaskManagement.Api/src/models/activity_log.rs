@@ -0,0 +1,116 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "activity_entity")]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityEntity {
+    #[sea_orm(string_value = "workspace")]
+    Workspace,
+    #[sea_orm(string_value = "topic")]
+    Topic,
+    #[sea_orm(string_value = "task")]
+    Task,
+    #[sea_orm(string_value = "substep")]
+    Substep,
+    #[sea_orm(string_value = "comment")]
+    Comment,
+    #[sea_orm(string_value = "attachment")]
+    Attachment,
+    #[sea_orm(string_value = "time_entry")]
+    TimeEntry,
+    #[sea_orm(string_value = "reminder")]
+    Reminder,
+    #[sea_orm(string_value = "dependency")]
+    Dependency,
+    #[sea_orm(string_value = "label")]
+    Label,
+    #[sea_orm(string_value = "task_status")]
+    TaskStatus,
+    #[sea_orm(string_value = "task_type")]
+    TaskType,
+    #[sea_orm(string_value = "custom_field")]
+    CustomField,
+    #[sea_orm(string_value = "share_link")]
+    ShareLink,
+    #[sea_orm(string_value = "webhook")]
+    Webhook,
+    #[sea_orm(string_value = "transfer")]
+    Transfer,
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "activity_action")]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityAction {
+    #[sea_orm(string_value = "created")]
+    Created,
+    #[sea_orm(string_value = "updated")]
+    Updated,
+    /// Moved to the trash, or removed outright for entities without one.
+    #[sea_orm(string_value = "deleted")]
+    Deleted,
+    /// Brought back from the trash.
+    #[sea_orm(string_value = "restored")]
+    Restored,
+    #[sea_orm(string_value = "archived")]
+    Archived,
+    #[sea_orm(string_value = "unarchived")]
+    Unarchived,
+    /// Reordered or moved to another topic.
+    #[sea_orm(string_value = "moved")]
+    Moved,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "activity_log")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub workspace_id: Uuid,
+    /// Cleared if the user is deleted.
+    pub actor_id: Option<Uuid>,
+    pub entity: ActivityEntity,
+    pub entity_id: Uuid,
+    /// The task the entry belongs to in task history: the task itself or the
+    /// task owning a substep, comment, attachment and so on.
+    pub task_id: Option<Uuid>,
+    pub action: ActivityAction,
+    /// `{ "<field>": { "before": .., "after": .. } }` for every changed field.
+    #[sea_orm(column_type = "JsonBinary")]
+    pub changes: Json,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::workspace::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspace::Column::Id"
+    )]
+    Workspace,
+}
+
+impl Related<super::workspace::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::new_v4()),
+            created_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
@@ -1,7 +1,27 @@
+pub mod activity_log;
+pub mod attachment;
+pub mod custom_field;
+pub mod label;
+pub mod notification;
 pub mod task;
+pub mod task_assignee;
+pub mod task_comment;
+pub mod task_comment_mention;
+pub mod task_custom_field_value;
+pub mod task_dependency;
 pub mod task_exception;
+pub mod task_label;
+pub mod task_reminder;
+pub mod task_status;
+pub mod task_status_transition;
 pub mod task_substep;
+pub mod task_type;
+pub mod time_entry;
 pub mod topic;
+pub mod undo_token;
 pub mod user;
+pub mod webhook;
+pub mod webhook_delivery;
 pub mod workspace;
 pub mod workspace_share_link;
+pub mod workspace_transfer;
@@ -0,0 +1,69 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "notification_kind")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    #[sea_orm(string_value = "reminder")]
+    Reminder,
+    #[sea_orm(string_value = "assignment")]
+    Assignment,
+    #[sea_orm(string_value = "mention")]
+    Mention,
+    #[sea_orm(string_value = "workspace_transfer")]
+    WorkspaceTransfer,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "notifications")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    /// The recipient.
+    pub user_id: Uuid,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: Option<String>,
+    /// The user whose action produced the notification, if any.
+    pub actor_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub read_at: Option<DateTimeUtc>,
+    /// Set once the notification channel has taken the notification.
+    pub delivered_at: Option<DateTimeUtc>,
+    pub delivery_attempts: i32,
+
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::new_v4()),
+            delivery_attempts: Set(0),
+            created_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
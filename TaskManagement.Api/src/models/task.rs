@@ -2,33 +2,59 @@ use sea_orm::entity::prelude::*;
 use sea_orm::Set;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
-#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_type")]
+/// Triage priority of a task. Variants are declared from lowest to highest,
+/// matching the `task_priority` Postgres enum, so both `Ord` and SQL ordering
+/// follow the priority.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, DeriveActiveEnum,
+    Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_priority")]
 #[serde(rename_all = "lowercase")]
-pub enum TaskType {
-    #[sea_orm(string_value = "work")]
-    Work,
-    #[sea_orm(string_value = "personal")]
-    Personal,
-    #[sea_orm(string_value = "meeting")]
-    Meeting,
-    #[sea_orm(string_value = "deadline")]
-    Deadline,
-    #[sea_orm(string_value = "event")]
-    Event,
+pub enum TaskPriority {
+    #[default]
+    #[sea_orm(string_value = "none")]
+    None,
+    #[sea_orm(string_value = "low")]
+    Low,
+    #[sea_orm(string_value = "medium")]
+    Medium,
+    #[sea_orm(string_value = "high")]
+    High,
+    #[sea_orm(string_value = "critical")]
+    Critical,
 }
 
-impl std::str::FromStr for TaskType {
+impl TaskPriority {
+    /// Tasks at this priority or above are reported as `urgent` to clients
+    /// that predate priorities.
+    pub const URGENT: TaskPriority = TaskPriority::High;
+
+    pub fn is_urgent(&self) -> bool {
+        *self >= Self::URGENT
+    }
+
+    /// The priority to use when an older client only sends `urgent`.
+    pub fn from_urgent(current: TaskPriority, urgent: bool) -> TaskPriority {
+        match (urgent, current.is_urgent()) {
+            (true, false) => Self::URGENT,
+            (false, true) => TaskPriority::None,
+            _ => current,
+        }
+    }
+}
+
+impl std::str::FromStr for TaskPriority {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "work" => Ok(TaskType::Work),
-            "personal" => Ok(TaskType::Personal),
-            "meeting" => Ok(TaskType::Meeting),
-            "deadline" => Ok(TaskType::Deadline),
-            "event" => Ok(TaskType::Event),
-            _ => Err(format!("Invalid task type: {}", s)),
+            "none" => Ok(TaskPriority::None),
+            "low" => Ok(TaskPriority::Low),
+            "medium" => Ok(TaskPriority::Medium),
+            "high" => Ok(TaskPriority::High),
+            "critical" => Ok(TaskPriority::Critical),
+            _ => Err(format!("Invalid priority: {}", s)),
         }
     }
 }
@@ -40,15 +66,20 @@ pub struct Model {
     pub id: Uuid,
 
     pub topic_id: Uuid,
+    pub parent_task_id: Option<Uuid>,
     pub title: String,
     pub description: Option<String>,
     pub start_time: Option<DateTimeUtc>,
     pub end_time: Option<DateTimeUtc>,
-    pub task_type: TaskType,
+    pub task_type_id: Option<Uuid>,
+    pub status_id: Option<Uuid>,
+    pub rank: String,
     pub color: String,
-    pub urgent: bool,
+    pub priority: TaskPriority,
     pub completed: bool,
     pub due_date: Option<DateTimeUtc>,
+    pub estimate_minutes: Option<i32>,
+    pub remaining_minutes: Option<i32>,
 
     // Recurrence fields
     pub recurrence_type: Option<String>,
@@ -56,6 +87,8 @@ pub struct Model {
     pub recurrence_days: Option<Vec<i32>>,
     pub recurrence_end_date: Option<DateTimeUtc>,
 
+    pub deleted_at: Option<DateTimeUtc>,
+
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -70,6 +103,27 @@ pub enum Relation {
     )]
     Topic,
     
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentTaskId",
+        to = "Column::Id"
+    )]
+    Parent,
+
+    #[sea_orm(
+        belongs_to = "super::task_status::Entity",
+        from = "Column::StatusId",
+        to = "super::task_status::Column::Id"
+    )]
+    Status,
+
+    #[sea_orm(
+        belongs_to = "super::task_type::Entity",
+        from = "Column::TaskTypeId",
+        to = "super::task_type::Column::Id"
+    )]
+    TaskType,
+
     #[sea_orm(has_many = "super::task_substep::Entity")]
     Substeps,
 }
@@ -80,6 +134,18 @@ impl Related<super::topic::Entity> for Entity {
     }
 }
 
+impl Related<super::task_status::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Status.def()
+    }
+}
+
+impl Related<super::task_type::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskType.def()
+    }
+}
+
 impl Related<super::task_substep::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Substeps.def()
@@ -0,0 +1,67 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_dependency_type")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskDependencyType {
+    #[sea_orm(string_value = "finish_to_start")]
+    FinishToStart,
+    #[sea_orm(string_value = "start_to_start")]
+    StartToStart,
+    #[sea_orm(string_value = "finish_to_finish")]
+    FinishToFinish,
+    #[sea_orm(string_value = "start_to_finish")]
+    StartToFinish,
+}
+
+impl TaskDependencyType {
+    /// Whether the blocking task has to be finished (rather than merely
+    /// started) before the blocked task may be completed.
+    pub fn requires_blocker_done(&self) -> bool {
+        matches!(
+            self,
+            TaskDependencyType::FinishToStart | TaskDependencyType::FinishToFinish
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "task_dependencies")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub blocking_task_id: Uuid,
+    pub blocked_task_id: Uuid,
+    pub dependency_type: TaskDependencyType,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::task::Entity",
+        from = "Column::BlockingTaskId",
+        to = "super::task::Column::Id"
+    )]
+    BlockingTask,
+
+    #[sea_orm(
+        belongs_to = "super::task::Entity",
+        from = "Column::BlockedTaskId",
+        to = "super::task::Column::Id"
+    )]
+    BlockedTask,
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::new_v4()),
+            created_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
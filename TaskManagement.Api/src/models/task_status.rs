@@ -0,0 +1,86 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "task_status_category")]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatusCategory {
+    #[sea_orm(string_value = "todo")]
+    Todo,
+    #[sea_orm(string_value = "doing")]
+    Doing,
+    #[sea_orm(string_value = "done")]
+    Done,
+}
+
+impl TaskStatusCategory {
+    pub fn is_done(&self) -> bool {
+        matches!(self, TaskStatusCategory::Done)
+    }
+}
+
+impl std::str::FromStr for TaskStatusCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "todo" => Ok(TaskStatusCategory::Todo),
+            "doing" => Ok(TaskStatusCategory::Doing),
+            "done" => Ok(TaskStatusCategory::Done),
+            _ => Err(format!("Invalid status category: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "task_statuses")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub category: TaskStatusCategory,
+    pub color: Option<String>,
+    pub position: i32,
+
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::workspace::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspace::Column::Id"
+    )]
+    Workspace,
+
+    #[sea_orm(has_many = "super::task::Entity")]
+    Tasks,
+}
+
+impl Related<super::workspace::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
+impl Related<super::task::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tasks.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::new_v4()),
+            created_at: Set(chrono::Utc::now()),
+            updated_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub struct Model {
     pub task_id: Uuid,
     pub description: String,
     pub completed: bool,
+    pub rank: String,
     
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
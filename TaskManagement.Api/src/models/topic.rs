@@ -12,6 +12,8 @@ pub struct Model {
     pub name: String,
     pub description: Option<String>,
     pub color: String,
+    pub archived_at: Option<DateTimeUtc>,
+    pub deleted_at: Option<DateTimeUtc>,
 
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
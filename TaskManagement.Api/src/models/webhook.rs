@@ -0,0 +1,116 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Events a webhook can subscribe to. They are stored by name in
+/// `webhooks.events`, so new events need no migration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "task.created")]
+    TaskCreated,
+    #[serde(rename = "task.updated")]
+    TaskUpdated,
+    #[serde(rename = "task.completed")]
+    TaskCompleted,
+    #[serde(rename = "task.deleted")]
+    TaskDeleted,
+    #[serde(rename = "substep.completed")]
+    SubstepCompleted,
+    #[serde(rename = "comment.created")]
+    CommentCreated,
+    #[serde(rename = "share_link.created")]
+    ShareLinkCreated,
+    #[serde(rename = "share_link.revoked")]
+    ShareLinkRevoked,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 8] = [
+        WebhookEvent::TaskCreated,
+        WebhookEvent::TaskUpdated,
+        WebhookEvent::TaskCompleted,
+        WebhookEvent::TaskDeleted,
+        WebhookEvent::SubstepCompleted,
+        WebhookEvent::CommentCreated,
+        WebhookEvent::ShareLinkCreated,
+        WebhookEvent::ShareLinkRevoked,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::TaskCreated => "task.created",
+            WebhookEvent::TaskUpdated => "task.updated",
+            WebhookEvent::TaskCompleted => "task.completed",
+            WebhookEvent::TaskDeleted => "task.deleted",
+            WebhookEvent::SubstepCompleted => "substep.completed",
+            WebhookEvent::CommentCreated => "comment.created",
+            WebhookEvent::ShareLinkCreated => "share_link.created",
+            WebhookEvent::ShareLinkRevoked => "share_link.revoked",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WebhookEvent::ALL
+            .into_iter()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| format!("Unknown webhook event: {}", s))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "webhooks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub workspace_id: Uuid,
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature sent with every delivery.
+    pub secret: String,
+    /// Names of the subscribed events.
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::workspace::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspace::Column::Id"
+    )]
+    Workspace,
+
+    #[sea_orm(has_many = "super::webhook_delivery::Entity")]
+    Deliveries,
+}
+
+impl Related<super::workspace::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
+impl Related<super::webhook_delivery::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Deliveries.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::new_v4()),
+            active: Set(true),
+            created_at: Set(chrono::Utc::now()),
+            updated_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
@@ -11,6 +11,9 @@ pub struct Model {
     pub owner_id: Uuid,
     pub name: String,
     pub description: Option<String>,
+    pub is_template: bool,
+    pub archived_at: Option<DateTimeUtc>,
+    pub deleted_at: Option<DateTimeUtc>,
 
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
@@ -30,6 +33,9 @@ pub enum Relation {
 
     #[sea_orm(has_many = "super::workspace_share_link::Entity")]
     ShareLinks,
+
+    #[sea_orm(has_many = "super::workspace_transfer::Entity")]
+    Transfers,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -50,6 +56,12 @@ impl Related<super::workspace_share_link::Entity> for Entity {
     }
 }
 
+impl Related<super::workspace_transfer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transfers.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {
     fn new() -> Self {
         Self {
@@ -0,0 +1,48 @@
+use crate::config::Config;
+use crate::errors::{AppError, AppResult};
+use crate::models::notification;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Delivers notifications to users outside the in-app inbox. Producers never
+/// call a channel directly: they record notifications with
+/// `database::notify`, and a background job hands them to the channel.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Delivers a notification. An error means it was not delivered and it
+    /// will be tried again later.
+    async fn send(&self, notification: &notification::Model) -> AppResult<()>;
+}
+
+/// Builds the channel selected by `NOTIFICATION_CHANNEL`.
+pub fn notification_channel_from_config(
+    config: &Config,
+) -> AppResult<Arc<dyn NotificationChannel>> {
+    match config.notification_channel.as_str() {
+        "log" => Ok(Arc::new(LogChannel)),
+        other => Err(AppError::Internal(format!(
+            "Unknown notification channel: {}",
+            other
+        ))),
+    }
+}
+
+/// Prints notifications to stdout; useful in development.
+pub struct LogChannel;
+
+#[async_trait]
+impl NotificationChannel for LogChannel {
+    async fn send(&self, notification: &notification::Model) -> AppResult<()> {
+        println!(
+            "Notification for {}: {}{}",
+            notification.user_id,
+            notification.title,
+            notification
+                .body
+                .as_deref()
+                .map(|body| format!(" - {}", body))
+                .unwrap_or_default()
+        );
+        Ok(())
+    }
+}
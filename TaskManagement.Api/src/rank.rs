@@ -0,0 +1,226 @@
+//! Lexicographic rank keys for manual ordering.
+//!
+//! Keys are strings over `0-9a-z` that compare with plain byte ordering (the
+//! columns use `COLLATE "C"`). A key never ends in `0`, which guarantees there
+//! is always room for another key between two neighbours, so moving a row
+//! only rewrites that row.
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Keys longer than this are a sign that one spot has been split many times;
+/// callers should rebalance the sibling list instead of storing them.
+pub const MAX_RANK_LEN: usize = 64;
+
+fn digit_value(c: u8) -> usize {
+    DIGITS.iter().position(|&d| d == c).unwrap_or(0)
+}
+
+/// Whether `rank` is a well-formed key: non-empty, at most `MAX_RANK_LEN`
+/// long, made of `0-9a-z` and not ending in `0`. Keys from outside (e.g.
+/// imported documents) must pass this before being stored.
+pub fn is_valid_rank(rank: &str) -> bool {
+    !rank.is_empty()
+        && rank.len() <= MAX_RANK_LEN
+        && rank.bytes().all(|c| DIGITS.contains(&c))
+        && !rank.ends_with('0')
+}
+
+/// Returns a key strictly between `before` and `after`. `None` stands for the
+/// start or end of the list. Returns `None` if the bounds are not strictly
+/// ordered, e.g. when two siblings share a key and the list needs rebalancing.
+pub fn rank_between(before: Option<&str>, after: Option<&str>) -> Option<String> {
+    match (before, after) {
+        (Some(a), Some(b)) if a >= b => None,
+        (None, Some("")) => None,
+        (Some(a), None) => Some(rank_after(a)),
+        _ => Some(midpoint(
+            before.unwrap_or("").as_bytes(),
+            after.map(str::as_bytes),
+        )),
+    }
+}
+
+/// Returns a short key that sorts after `rank`, used when appending.
+pub fn rank_after(rank: &str) -> String {
+    let bytes = rank.as_bytes();
+    match bytes.iter().position(|&c| c != b'z') {
+        Some(p) => {
+            let mut key = bytes[..p].to_vec();
+            key.push(DIGITS[digit_value(bytes[p]) + 1]);
+            String::from_utf8(key).unwrap_or_default()
+        }
+        None => format!("{}i", rank),
+    }
+}
+
+/// Returns `count` evenly spaced keys of equal length, for seeding or
+/// rebalancing a whole list.
+pub fn rank_sequence(count: usize) -> Vec<String> {
+    let base = DIGITS.len();
+    let mut width = 1;
+    let mut capacity = base;
+    while capacity <= count {
+        width += 1;
+        capacity *= base;
+    }
+
+    (1..=count)
+        .map(|mut n| {
+            let mut key = vec![b'0'; width];
+            for slot in key.iter_mut().rev() {
+                *slot = DIGITS[n % base];
+                n /= base;
+            }
+            key.push(b'i');
+            String::from_utf8(key).unwrap_or_default()
+        })
+        .collect()
+}
+
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> String {
+    if let Some(b) = b {
+        // Copy the shared prefix (treating a missing digit in `a` as `0`).
+        let mut n = 0;
+        while n < b.len() && a.get(n).copied().unwrap_or(b'0') == b[n] {
+            n += 1;
+        }
+        if n > 0 {
+            let rest = a.get(n..).unwrap_or(&[]);
+            let prefix = String::from_utf8_lossy(&b[..n]);
+            return format!("{}{}", prefix, midpoint(rest, Some(&b[n..])));
+        }
+    }
+
+    let digit_a = a.first().map_or(0, |&c| digit_value(c));
+    let digit_b = b
+        .and_then(|b| b.first())
+        .map_or(DIGITS.len(), |&c| digit_value(c));
+
+    if digit_b - digit_a > 1 {
+        return (DIGITS[(digit_a + digit_b) / 2] as char).to_string();
+    }
+
+    match b {
+        Some(b) if b.len() > 1 => (b[0] as char).to_string(),
+        _ => format!(
+            "{}{}",
+            DIGITS[digit_a] as char,
+            midpoint(a.get(1..).unwrap_or(&[]), None)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_between(before: Option<&str>, after: Option<&str>) -> String {
+        let key = rank_between(before, after).expect("bounds are ordered");
+        assert!(is_valid_rank(&key), "{:?} is not a valid key", key);
+        if let Some(before) = before {
+            assert!(before < key.as_str(), "{:?} !< {:?}", before, key);
+        }
+        if let Some(after) = after {
+            assert!(key.as_str() < after, "{:?} !< {:?}", key, after);
+        }
+        key
+    }
+
+    /// Keys seeded by migration 006: `lpad(n, 10, '0') || 'i'`.
+    fn seeded_rank(n: usize) -> String {
+        format!("{:010}i", n)
+    }
+
+    #[test]
+    fn rank_between_open_bounds() {
+        assert_between(None, None);
+        assert_between(Some("i"), None);
+        assert_between(None, Some("i"));
+        assert_between(Some("z"), None);
+        assert_between(None, Some("01"));
+    }
+
+    #[test]
+    fn rank_between_rejects_unordered_bounds() {
+        assert_eq!(rank_between(Some("i"), Some("i")), None);
+        assert_eq!(rank_between(Some("j"), Some("i")), None);
+        assert_eq!(rank_between(None, Some("")), None);
+    }
+
+    #[test]
+    fn rank_between_adjacent_and_prefix_keys() {
+        assert_between(Some("a"), Some("b"));
+        assert_between(Some("i"), Some("ii"));
+        assert_between(Some("az"), Some("b"));
+        assert_between(Some("a1"), Some("a2"));
+        assert_between(Some("zz"), Some("zzi"));
+    }
+
+    #[test]
+    fn rank_between_repeated_splits() {
+        let low = "a".to_string();
+        let mut high = "b".to_string();
+        for _ in 0..40 {
+            high = assert_between(Some(&low), Some(&high));
+        }
+
+        let mut low = "a".to_string();
+        let high = "b".to_string();
+        for _ in 0..40 {
+            low = assert_between(Some(&low), Some(&high));
+        }
+    }
+
+    #[test]
+    fn rank_between_seeded_keys() {
+        for n in [1, 9, 10, 35, 36, 99, 100, 999_999] {
+            let before = seeded_rank(n);
+            let after = seeded_rank(n + 1);
+            assert_between(Some(&before), Some(&after));
+            assert_between(None, Some(&before));
+            assert_between(Some(&after), None);
+        }
+    }
+
+    #[test]
+    fn rank_after_sorts_after() {
+        for rank in ["i", "a", "z", "zz", "0000000001i", "y"] {
+            let key = rank_after(rank);
+            assert!(rank < key.as_str(), "{:?} !< {:?}", rank, key);
+            assert!(is_valid_rank(&key));
+        }
+    }
+
+    #[test]
+    fn midpoint_stays_between() {
+        assert_eq!(midpoint(b"", None), "i");
+        assert_eq!(midpoint(b"a", Some(b"c")), "b");
+        let key = midpoint(b"a", Some(b"b"));
+        assert!("a" < key.as_str() && key.as_str() < "b");
+        let key = midpoint(b"0000000009i", Some(b"0000000010i"));
+        assert!("0000000009i" < key.as_str() && key.as_str() < "0000000010i");
+    }
+
+    #[test]
+    fn rank_sequence_is_sorted_and_valid() {
+        assert!(rank_sequence(0).is_empty());
+        for count in [1, 35, 36, 37, 1_500] {
+            let keys = rank_sequence(count);
+            assert_eq!(keys.len(), count);
+            assert!(keys.iter().all(|key| is_valid_rank(key)));
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+            assert!(keys.iter().all(|key| key.len() == keys[0].len()));
+        }
+    }
+
+    #[test]
+    fn is_valid_rank_rejects_malformed_keys() {
+        assert!(is_valid_rank("i"));
+        assert!(is_valid_rank(&seeded_rank(10)));
+        assert!(!is_valid_rank(""));
+        assert!(!is_valid_rank("a0"));
+        assert!(!is_valid_rank("A"));
+        assert!(!is_valid_rank("a-b"));
+        assert!(!is_valid_rank(&"a".repeat(MAX_RANK_LEN + 1)));
+    }
+}
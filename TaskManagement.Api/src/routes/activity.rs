@@ -0,0 +1,131 @@
+use crate::auth::AuthUser;
+use crate::database::{
+    get_task_history, get_workspace_activity, ActivityFilter, ActivityListResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::activity_log::{ActivityAction, ActivityEntity};
+use rocket::serde::json::Json;
+use rocket::{get, State};
+use sea_orm::{ActiveEnum, DatabaseConnection};
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(rocket::FromForm)]
+pub struct HistoryPage {
+    #[field(name = "$top")]
+    pub top: Option<u32>,
+    #[field(name = "$skip")]
+    pub skip: Option<u32>,
+}
+
+#[derive(rocket::FromForm)]
+pub struct ActivityFilters {
+    pub entity: Option<String>, // task, topic, substep, comment, ...
+    pub action: Option<String>, // created, updated, deleted, ...
+    pub actor_id: Option<String>,
+    pub task_id: Option<String>,
+    pub since: Option<String>, // RFC 3339, inclusive
+    pub until: Option<String>, // RFC 3339, exclusive
+    #[field(name = "$top")]
+    pub top: Option<u32>,
+    #[field(name = "$skip")]
+    pub skip: Option<u32>,
+}
+
+fn page_size(top: Option<u32>) -> AppResult<u64> {
+    let top = top.unwrap_or(DEFAULT_PAGE_SIZE);
+    if top == 0 || top > MAX_PAGE_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "$top must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(top as u64)
+}
+
+fn parse_uuid(value: &str, name: &str) -> AppResult<Uuid> {
+    Uuid::parse_str(value).map_err(|_| AppError::BadRequest(format!("Invalid {} format", name)))
+}
+
+fn parse_date(value: &str, name: &str) -> AppResult<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|_| AppError::BadRequest(format!("Invalid {} date, expected RFC 3339", name)))
+}
+
+/// Changes to the task and to its substeps, comments, attachments, time
+/// entries, reminders and dependencies, newest first.
+#[get("/<task_id>/history?<page..>")]
+pub async fn get_task_history_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+    page: HistoryPage,
+) -> AppResult<Json<ActivityListResponse>> {
+    let task_uuid = parse_uuid(task_id, "task ID")?;
+    let limit = page_size(page.top)?;
+    let history =
+        get_task_history(db, user.id, task_uuid, limit, page.skip.unwrap_or(0) as u64).await?;
+    Ok(Json(history))
+}
+
+#[get("/<workspace_id>/activity?<filters..>")]
+pub async fn get_workspace_activity_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    filters: ActivityFilters,
+) -> AppResult<Json<ActivityListResponse>> {
+    let workspace_uuid = parse_uuid(workspace_id, "workspace ID")?;
+    let limit = page_size(filters.top)?;
+
+    let filter = ActivityFilter {
+        entity: filters
+            .entity
+            .map(|entity| {
+                ActivityEntity::try_from_value(&entity.to_lowercase())
+                    .map_err(|_| AppError::BadRequest(format!("Invalid entity: {}", entity)))
+            })
+            .transpose()?,
+        action: filters
+            .action
+            .map(|action| {
+                ActivityAction::try_from_value(&action.to_lowercase())
+                    .map_err(|_| AppError::BadRequest(format!("Invalid action: {}", action)))
+            })
+            .transpose()?,
+        actor_id: filters
+            .actor_id
+            .as_deref()
+            .map(|id| parse_uuid(id, "actor ID"))
+            .transpose()?,
+        task_id: filters
+            .task_id
+            .as_deref()
+            .map(|id| parse_uuid(id, "task ID"))
+            .transpose()?,
+        since: filters
+            .since
+            .as_deref()
+            .map(|date| parse_date(date, "since"))
+            .transpose()?,
+        until: filters
+            .until
+            .as_deref()
+            .map(|date| parse_date(date, "until"))
+            .transpose()?,
+    };
+
+    let activity = get_workspace_activity(
+        db,
+        user.id,
+        workspace_uuid,
+        filter,
+        limit,
+        filters.skip.unwrap_or(0) as u64,
+    )
+    .await?;
+    Ok(Json(activity))
+}
@@ -0,0 +1,130 @@
+use crate::auth::AuthUser;
+use crate::config::Config;
+use crate::database::{
+    create_attachment, delete_attachment, get_attachment_by_id, get_attachment_content,
+    get_attachments_by_task, AttachmentResponse, AttachmentUpload,
+};
+use crate::errors::{AppError, AppResult};
+use crate::storage::{BlobReader, BlobStorage};
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::ContentType;
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, State};
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The contents of an attachment, streamed from blob storage as a download.
+pub struct AttachmentDownload {
+    attachment: AttachmentResponse,
+    reader: BlobReader,
+}
+
+impl<'r> Responder<'r, 'static> for AttachmentDownload {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let content_type =
+            ContentType::parse_flexible(&self.attachment.mime_type).unwrap_or(ContentType::Binary);
+        let filename = self.attachment.filename.replace(['"', '\\'], "_");
+
+        Response::build()
+            .header(content_type)
+            .raw_header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            )
+            .raw_header("X-Content-Type-Options", "nosniff")
+            .streamed_body(self.reader)
+            .ok()
+    }
+}
+
+#[get("/<task_id>/attachments")]
+pub async fn get_task_attachments(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<Vec<AttachmentResponse>>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let attachments = get_attachments_by_task(db, user.id, task_uuid).await?;
+    Ok(Json(attachments))
+}
+
+/// Uploads the raw request body as an attachment. The body's Content-Type
+/// is recorded as the attachment's MIME type.
+#[post("/<task_id>/attachments?<filename>", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_task_attachment(
+    db: &State<DatabaseConnection>,
+    storage: &State<Arc<dyn BlobStorage>>,
+    config: &State<Config>,
+    user: AuthUser,
+    task_id: &str,
+    filename: String,
+    content_type: Option<&ContentType>,
+    data: Data<'_>,
+) -> AppResult<Json<AttachmentResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let mime_type = content_type
+        .map(|ct| format!("{}/{}", ct.top(), ct.sub()))
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    // Read one byte past the limit so an oversized upload can be told apart
+    let mut stream = data.open((config.attachment_max_bytes + 1).bytes());
+    let attachment = create_attachment(
+        db,
+        storage.inner().as_ref(),
+        config,
+        user.id,
+        task_uuid,
+        AttachmentUpload {
+            filename,
+            mime_type,
+        },
+        &mut stream,
+    )
+    .await?;
+    Ok(Json(attachment))
+}
+
+#[get("/<attachment_id>")]
+pub async fn get_attachment(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    attachment_id: &str,
+) -> AppResult<Json<AttachmentResponse>> {
+    let attachment_uuid = Uuid::parse_str(attachment_id)
+        .map_err(|_| AppError::BadRequest("Invalid attachment ID format".to_string()))?;
+    let attachment = get_attachment_by_id(db, user.id, attachment_uuid).await?;
+    Ok(Json(attachment))
+}
+
+#[get("/<attachment_id>/content")]
+pub async fn download_attachment(
+    db: &State<DatabaseConnection>,
+    storage: &State<Arc<dyn BlobStorage>>,
+    user: AuthUser,
+    attachment_id: &str,
+) -> AppResult<AttachmentDownload> {
+    let attachment_uuid = Uuid::parse_str(attachment_id)
+        .map_err(|_| AppError::BadRequest("Invalid attachment ID format".to_string()))?;
+    let (attachment, reader) =
+        get_attachment_content(db, storage.inner().as_ref(), user.id, attachment_uuid).await?;
+    Ok(AttachmentDownload { attachment, reader })
+}
+
+#[delete("/<attachment_id>")]
+pub async fn delete_attachment_route(
+    db: &State<DatabaseConnection>,
+    storage: &State<Arc<dyn BlobStorage>>,
+    user: AuthUser,
+    attachment_id: &str,
+) -> AppResult<Json<bool>> {
+    let attachment_uuid = Uuid::parse_str(attachment_id)
+        .map_err(|_| AppError::BadRequest("Invalid attachment ID format".to_string()))?;
+    let deleted = delete_attachment(db, storage.inner().as_ref(), user.id, attachment_uuid).await?;
+    Ok(Json(deleted))
+}
@@ -0,0 +1,60 @@
+use crate::auth::AuthUser;
+use crate::database::{
+    create_comment, delete_comment, get_comments_by_task, update_comment, CommentResponse,
+    CreateCommentRequest, UpdateCommentRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<task_id>/comments")]
+pub async fn get_task_comments(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<Vec<CommentResponse>>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let comments = get_comments_by_task(db, user.id, task_uuid).await?;
+    Ok(Json(comments))
+}
+
+#[post("/<task_id>/comments", data = "<request>")]
+pub async fn create_task_comment(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<CreateCommentRequest>,
+) -> AppResult<Json<CommentResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let comment = create_comment(db, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(comment))
+}
+
+#[put("/<comment_id>", data = "<request>")]
+pub async fn update_comment_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    comment_id: &str,
+    request: Json<UpdateCommentRequest>,
+) -> AppResult<Json<CommentResponse>> {
+    let comment_uuid = Uuid::parse_str(comment_id)
+        .map_err(|_| AppError::BadRequest("Invalid comment ID format".to_string()))?;
+    let comment = update_comment(db, user.id, comment_uuid, request.into_inner()).await?;
+    Ok(Json(comment))
+}
+
+#[delete("/<comment_id>")]
+pub async fn delete_comment_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    comment_id: &str,
+) -> AppResult<Json<bool>> {
+    let comment_uuid = Uuid::parse_str(comment_id)
+        .map_err(|_| AppError::BadRequest("Invalid comment ID format".to_string()))?;
+    let deleted = delete_comment(db, user.id, comment_uuid).await?;
+    Ok(Json(deleted))
+}
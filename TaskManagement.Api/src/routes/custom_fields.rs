@@ -0,0 +1,77 @@
+use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::database::{
+    create_custom_field, delete_custom_field, list_custom_fields, update_custom_field,
+    CreateCustomFieldRequest, CustomFieldResponse, UpdateCustomFieldRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<workspace_id>/custom-fields")]
+pub async fn get_custom_fields(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<CustomFieldResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let fields = list_custom_fields(db, user.id, workspace_uuid).await?;
+    Ok(Json(fields))
+}
+
+#[post("/<workspace_id>/custom-fields", data = "<request>")]
+pub async fn create_custom_field_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateCustomFieldRequest>,
+) -> AppResult<Json<CustomFieldResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let field = create_custom_field(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(field))
+}
+
+#[put("/<workspace_id>/custom-fields/<field_id>", data = "<request>")]
+pub async fn update_custom_field_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    workspace_id: &str,
+    field_id: &str,
+    request: Json<UpdateCustomFieldRequest>,
+) -> AppResult<Json<CustomFieldResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let field_uuid = Uuid::parse_str(field_id)
+        .map_err(|_| AppError::BadRequest("Invalid custom field ID format".to_string()))?;
+    let field = update_custom_field(
+        db,
+        cache,
+        user.id,
+        workspace_uuid,
+        field_uuid,
+        request.into_inner(),
+    )
+    .await?;
+    Ok(Json(field))
+}
+
+#[delete("/<workspace_id>/custom-fields/<field_id>")]
+pub async fn delete_custom_field_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    workspace_id: &str,
+    field_id: &str,
+) -> AppResult<Json<bool>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let field_uuid = Uuid::parse_str(field_id)
+        .map_err(|_| AppError::BadRequest("Invalid custom field ID format".to_string()))?;
+    let deleted = delete_custom_field(db, cache, user.id, workspace_uuid, field_uuid).await?;
+    Ok(Json(deleted))
+}
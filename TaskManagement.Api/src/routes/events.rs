@@ -0,0 +1,81 @@
+use crate::auth::AuthUser;
+use crate::database::find_owned_workspace;
+use crate::errors::{AppError, AppResult};
+use crate::events::EventHub;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::tokio::time::{interval, Duration};
+use rocket::{get, Shutdown, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+/// How often an idle stream sends a comment so proxies keep it open, and
+/// how often an open stream re-checks that the user still owns the
+/// workspace.
+const HEARTBEAT_SECONDS: u64 = 15;
+
+/// Streams the workspace's task, topic and substep changes as server-sent
+/// events named like `task.updated`. A `resync` event means the stream fell
+/// behind and skipped changes, so the client should refetch what it shows.
+/// A `revoked` event means the workspace was transferred or deleted; the
+/// stream ends after it.
+#[get("/<workspace_id>/events")]
+pub async fn workspace_events(
+    db: &State<DatabaseConnection>,
+    hub: &State<EventHub>,
+    user: AuthUser,
+    workspace_id: &str,
+    mut shutdown: Shutdown,
+) -> AppResult<EventStream![]> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    find_owned_workspace(db.inner(), user.id, workspace_uuid).await?;
+
+    let db = db.inner().clone();
+    let mut events = hub.subscribe();
+    let mut access_check = interval(Duration::from_secs(HEARTBEAT_SECONDS));
+    access_check.tick().await;
+
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                received = events.recv() => match received {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => {
+                        if !still_owns(&db, user.id, workspace_uuid).await {
+                            yield Event::empty().event("revoked");
+                            break;
+                        }
+                        yield Event::empty().event("resync");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = access_check.tick() => {
+                    if !still_owns(&db, user.id, workspace_uuid).await {
+                        yield Event::empty().event("revoked");
+                        break;
+                    }
+                    continue;
+                }
+                _ = &mut shutdown => break,
+            };
+
+            if event.workspace_id == workspace_uuid {
+                yield Event::json(&event).event(event.name());
+            }
+        }
+    }
+    .heartbeat(Duration::from_secs(HEARTBEAT_SECONDS)))
+}
+
+/// Whether the user can still see the workspace. Errors other than the
+/// workspace being gone keep the stream open, so a database hiccup does not
+/// disconnect every client.
+async fn still_owns(db: &DatabaseConnection, user_id: Uuid, workspace_id: Uuid) -> bool {
+    !matches!(
+        find_owned_workspace(db, user_id, workspace_id).await,
+        Err(AppError::NotFound(_))
+    )
+}
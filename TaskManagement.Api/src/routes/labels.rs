@@ -0,0 +1,77 @@
+use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::database::{
+    create_label, delete_label, list_labels, update_label, CreateLabelRequest, LabelResponse,
+    UpdateLabelRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<workspace_id>/labels")]
+pub async fn get_labels(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<LabelResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let labels = list_labels(db, user.id, workspace_uuid).await?;
+    Ok(Json(labels))
+}
+
+#[post("/<workspace_id>/labels", data = "<request>")]
+pub async fn create_label_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateLabelRequest>,
+) -> AppResult<Json<LabelResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let label = create_label(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(label))
+}
+
+#[put("/<workspace_id>/labels/<label_id>", data = "<request>")]
+pub async fn update_label_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    workspace_id: &str,
+    label_id: &str,
+    request: Json<UpdateLabelRequest>,
+) -> AppResult<Json<LabelResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let label_uuid = Uuid::parse_str(label_id)
+        .map_err(|_| AppError::BadRequest("Invalid label ID format".to_string()))?;
+    let label = update_label(
+        db,
+        cache,
+        user.id,
+        workspace_uuid,
+        label_uuid,
+        request.into_inner(),
+    )
+    .await?;
+    Ok(Json(label))
+}
+
+#[delete("/<workspace_id>/labels/<label_id>")]
+pub async fn delete_label_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    workspace_id: &str,
+    label_id: &str,
+) -> AppResult<Json<bool>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let label_uuid = Uuid::parse_str(label_id)
+        .map_err(|_| AppError::BadRequest("Invalid label ID format".to_string()))?;
+    let deleted = delete_label(db, cache, user.id, workspace_uuid, label_uuid).await?;
+    Ok(Json(deleted))
+}
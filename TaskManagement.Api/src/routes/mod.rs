@@ -1,13 +1,41 @@
+pub mod activity;
+pub mod attachments;
 pub mod auth;
+pub mod comments;
+pub mod custom_fields;
+pub mod events;
+pub mod labels;
+pub mod notifications;
+pub mod reminders;
 pub mod shared;
 pub mod substeps;
+pub mod task_statuses;
+pub mod task_types;
 pub mod tasks;
+pub mod time_entries;
 pub mod topics;
+pub mod trash;
+pub mod undo;
+pub mod webhooks;
 pub mod workspaces;
 
+pub use activity::*;
+pub use attachments::*;
 pub use auth::*;
+pub use comments::*;
+pub use custom_fields::*;
+pub use events::*;
+pub use labels::*;
+pub use notifications::*;
+pub use reminders::*;
 pub use shared::*;
 pub use substeps::*;
+pub use task_statuses::*;
+pub use task_types::*;
 pub use tasks::*;
+pub use time_entries::*;
 pub use topics::*;
+pub use trash::*;
+pub use undo::*;
+pub use webhooks::*;
 pub use workspaces::*;
@@ -0,0 +1,81 @@
+use crate::auth::AuthUser;
+use crate::database::{
+    delete_notification, get_notifications_by_user, mark_all_notifications_read,
+    mark_notification_read, NotificationListResponse, NotificationResponse,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(rocket::FromForm)]
+pub struct NotificationFilters {
+    pub unread: Option<bool>,
+    #[field(name = "$top")]
+    pub top: Option<u32>,
+    #[field(name = "$skip")]
+    pub skip: Option<u32>,
+}
+
+#[get("/?<filters..>")]
+pub async fn get_notifications(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    filters: NotificationFilters,
+) -> AppResult<Json<NotificationListResponse>> {
+    let top = filters.top.unwrap_or(DEFAULT_PAGE_SIZE);
+    if top == 0 || top > MAX_PAGE_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "$top must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let notifications = get_notifications_by_user(
+        db,
+        user.id,
+        filters.unread.unwrap_or(false),
+        top as u64,
+        filters.skip.unwrap_or(0) as u64,
+    )
+    .await?;
+    Ok(Json(notifications))
+}
+
+#[post("/<notification_id>/read")]
+pub async fn mark_notification_read_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    notification_id: &str,
+) -> AppResult<Json<NotificationResponse>> {
+    let notification_uuid = Uuid::parse_str(notification_id)
+        .map_err(|_| AppError::BadRequest("Invalid notification ID format".to_string()))?;
+    let notification = mark_notification_read(db, user.id, notification_uuid).await?;
+    Ok(Json(notification))
+}
+
+/// Returns how many notifications were marked as read.
+#[post("/read-all")]
+pub async fn mark_all_notifications_read_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+) -> AppResult<Json<u64>> {
+    let marked = mark_all_notifications_read(db, user.id).await?;
+    Ok(Json(marked))
+}
+
+#[delete("/<notification_id>")]
+pub async fn delete_notification_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    notification_id: &str,
+) -> AppResult<Json<bool>> {
+    let notification_uuid = Uuid::parse_str(notification_id)
+        .map_err(|_| AppError::BadRequest("Invalid notification ID format".to_string()))?;
+    let deleted = delete_notification(db, user.id, notification_uuid).await?;
+    Ok(Json(deleted))
+}
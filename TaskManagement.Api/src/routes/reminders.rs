@@ -0,0 +1,60 @@
+use crate::auth::AuthUser;
+use crate::database::{
+    create_reminder, delete_reminder, get_reminders_by_task, update_reminder,
+    CreateReminderRequest, ReminderResponse, UpdateReminderRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<task_id>/reminders")]
+pub async fn get_task_reminders(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<Vec<ReminderResponse>>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let reminders = get_reminders_by_task(db, user.id, task_uuid).await?;
+    Ok(Json(reminders))
+}
+
+#[post("/<task_id>/reminders", data = "<request>")]
+pub async fn create_task_reminder(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<CreateReminderRequest>,
+) -> AppResult<Json<ReminderResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let reminder = create_reminder(db, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(reminder))
+}
+
+#[put("/<reminder_id>", data = "<request>")]
+pub async fn update_reminder_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    reminder_id: &str,
+    request: Json<UpdateReminderRequest>,
+) -> AppResult<Json<ReminderResponse>> {
+    let reminder_uuid = Uuid::parse_str(reminder_id)
+        .map_err(|_| AppError::BadRequest("Invalid reminder ID format".to_string()))?;
+    let reminder = update_reminder(db, user.id, reminder_uuid, request.into_inner()).await?;
+    Ok(Json(reminder))
+}
+
+#[delete("/<reminder_id>")]
+pub async fn delete_reminder_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    reminder_id: &str,
+) -> AppResult<Json<bool>> {
+    let reminder_uuid = Uuid::parse_str(reminder_id)
+        .map_err(|_| AppError::BadRequest("Invalid reminder ID format".to_string()))?;
+    let deleted = delete_reminder(db, user.id, reminder_uuid).await?;
+    Ok(Json(deleted))
+}
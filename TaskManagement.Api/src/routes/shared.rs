@@ -1,7 +1,8 @@
 use crate::auth::AuthUser;
 use crate::database::{
-    get_tasks_by_workspace, get_topics_by_workspace, get_workspace_by_share_token,
-    WorkspaceResponse,
+    get_custom_fields_by_workspace, get_labels_by_workspace, get_task_statuses_by_workspace,
+    get_task_types_by_workspace, get_tasks_by_workspace, get_topics_by_workspace,
+    get_workspace_by_share_token, WorkspaceResponse,
 };
 use crate::errors::AppResult;
 use rocket::serde::json::Json;
@@ -12,6 +13,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SharedWorkspaceResponse {
     pub workspace: WorkspaceResponse,
+    pub statuses: Vec<crate::database::TaskStatusResponse>,
+    pub task_types: Vec<crate::database::TaskTypeResponse>,
+    pub labels: Vec<crate::database::LabelResponse>,
+    pub custom_fields: Vec<crate::database::CustomFieldResponse>,
     pub topics: Vec<crate::database::TopicResponse>,
     pub tasks: Vec<crate::database::TaskResponse>,
 }
@@ -23,11 +28,19 @@ pub async fn get_shared_workspace(
     token: &str,
 ) -> AppResult<Json<SharedWorkspaceResponse>> {
     let workspace = get_workspace_by_share_token(db, token).await?;
+    let statuses = get_task_statuses_by_workspace(db, workspace.id).await?;
+    let task_types = get_task_types_by_workspace(db, workspace.id).await?;
+    let labels = get_labels_by_workspace(db, workspace.id).await?;
+    let custom_fields = get_custom_fields_by_workspace(db, workspace.id).await?;
     let topics = get_topics_by_workspace(db, workspace.id).await?;
     let tasks = get_tasks_by_workspace(db, workspace.id).await?;
 
     Ok(Json(SharedWorkspaceResponse {
         workspace: WorkspaceResponse::from(workspace),
+        statuses,
+        task_types,
+        labels,
+        custom_fields,
         topics,
         tasks,
     }))
@@ -1,8 +1,10 @@
 use crate::auth::AuthUser;
 use crate::cache::Cache;
+use crate::config::Config;
 use crate::database::{
-    create_substep, delete_substep, get_substep_by_id, get_substeps_by_task, update_substep,
-    CreateSubstepRequest, SubstepResponse, UpdateSubstepRequest,
+    create_substep, delete_substep, get_substep_by_id, get_substeps_by_task, move_substep,
+    promote_substep_to_task, update_substep, CreateSubstepRequest, MoveSubstepRequest,
+    SubstepResponse, TaskResponse, UndoTokenResponse, UpdateSubstepRequest,
 };
 use crate::errors::{AppError, AppResult};
 use rocket::serde::json::Json;
@@ -28,6 +30,7 @@ pub async fn get_task_substeps(
 pub async fn create_task_substep(
     db: &State<DatabaseConnection>,
     cache: &State<Cache>,
+    config: &State<Config>,
     user: AuthUser,
     task_id: &str,
     request: Json<CreateSubstepRequest>,
@@ -35,7 +38,7 @@ pub async fn create_task_substep(
     let task_uuid = Uuid::parse_str(task_id)
         .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
     
-    let substep = create_substep(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    let substep = create_substep(db, cache, config, user.id, task_uuid, request.into_inner()).await?;
     Ok(Json(substep))
 }
 
@@ -55,6 +58,7 @@ pub async fn get_substep(
 #[put("/<substep_id>", data = "<request>")]
 pub async fn update_substep_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
     user: AuthUser,
     substep_id: &str,
     request: Json<UpdateSubstepRequest>,
@@ -62,19 +66,51 @@ pub async fn update_substep_route(
     let substep_uuid = Uuid::parse_str(substep_id)
         .map_err(|_| AppError::BadRequest("Invalid substep ID format".to_string()))?;
     
-    let substep = update_substep(db, user.id, substep_uuid, request.into_inner()).await?;
+    let substep = update_substep(db, cache, user.id, substep_uuid, request.into_inner()).await?;
     Ok(Json(substep))
 }
 
 #[delete("/<substep_id>")]
 pub async fn delete_substep_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
     user: AuthUser,
     substep_id: &str,
-) -> AppResult<Json<bool>> {
+) -> AppResult<Json<UndoTokenResponse>> {
     let substep_uuid = Uuid::parse_str(substep_id)
         .map_err(|_| AppError::BadRequest("Invalid substep ID format".to_string()))?;
     
-    let deleted = delete_substep(db, user.id, substep_uuid).await?;
-    Ok(Json(deleted))
+    let undo = delete_substep(db, cache, config, user.id, substep_uuid).await?;
+    Ok(Json(undo))
+}
+
+#[post("/<substep_id>/move", data = "<request>")]
+pub async fn move_substep_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    substep_id: &str,
+    request: Json<MoveSubstepRequest>,
+) -> AppResult<Json<SubstepResponse>> {
+    let substep_uuid = Uuid::parse_str(substep_id)
+        .map_err(|_| AppError::BadRequest("Invalid substep ID format".to_string()))?;
+
+    let substep = move_substep(db, cache, user.id, substep_uuid, request.into_inner()).await?;
+    Ok(Json(substep))
+}
+
+#[post("/<substep_id>/promote")]
+pub async fn promote_substep_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    substep_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let substep_uuid = Uuid::parse_str(substep_id)
+        .map_err(|_| AppError::BadRequest("Invalid substep ID format".to_string()))?;
+
+    let task = promote_substep_to_task(db, cache, config, user.id, substep_uuid).await?;
+    Ok(Json(task))
 }
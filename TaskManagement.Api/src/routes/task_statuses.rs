@@ -0,0 +1,107 @@
+use crate::auth::AuthUser;
+use crate::database::{
+    create_task_status, delete_task_status, list_task_status_transitions, list_task_statuses,
+    set_task_status_transitions, update_task_status, CreateTaskStatusRequest,
+    SetTaskStatusTransitionsRequest, TaskStatusResponse, TaskStatusTransitionResponse,
+    UpdateTaskStatusRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<workspace_id>/statuses")]
+pub async fn get_task_statuses(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<TaskStatusResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let statuses = list_task_statuses(db, user.id, workspace_uuid).await?;
+    Ok(Json(statuses))
+}
+
+#[post("/<workspace_id>/statuses", data = "<request>")]
+pub async fn create_task_status_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateTaskStatusRequest>,
+) -> AppResult<Json<TaskStatusResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let status = create_task_status(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(status))
+}
+
+#[put("/<workspace_id>/statuses/<status_id>", data = "<request>")]
+pub async fn update_task_status_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    status_id: &str,
+    request: Json<UpdateTaskStatusRequest>,
+) -> AppResult<Json<TaskStatusResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let status_uuid = Uuid::parse_str(status_id)
+        .map_err(|_| AppError::BadRequest("Invalid status ID format".to_string()))?;
+    let status = update_task_status(
+        db,
+        user.id,
+        workspace_uuid,
+        status_uuid,
+        request.into_inner(),
+    )
+    .await?;
+    Ok(Json(status))
+}
+
+#[delete("/<workspace_id>/statuses/<status_id>?<replacement_status_id>")]
+pub async fn delete_task_status_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    status_id: &str,
+    replacement_status_id: Option<&str>,
+) -> AppResult<Json<bool>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let status_uuid = Uuid::parse_str(status_id)
+        .map_err(|_| AppError::BadRequest("Invalid status ID format".to_string()))?;
+    let replacement_uuid = replacement_status_id
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|_| AppError::BadRequest("Invalid replacement status ID format".to_string()))?;
+    let deleted =
+        delete_task_status(db, user.id, workspace_uuid, status_uuid, replacement_uuid).await?;
+    Ok(Json(deleted))
+}
+
+#[get("/<workspace_id>/status-transitions")]
+pub async fn get_task_status_transitions(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<TaskStatusTransitionResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let transitions = list_task_status_transitions(db, user.id, workspace_uuid).await?;
+    Ok(Json(transitions))
+}
+
+#[put("/<workspace_id>/status-transitions", data = "<request>")]
+pub async fn set_task_status_transitions_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<SetTaskStatusTransitionsRequest>,
+) -> AppResult<Json<Vec<TaskStatusTransitionResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let transitions =
+        set_task_status_transitions(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(transitions))
+}
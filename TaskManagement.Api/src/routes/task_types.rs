@@ -0,0 +1,75 @@
+use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::database::{
+    create_task_type, delete_task_type, list_task_types, update_task_type, CreateTaskTypeRequest,
+    TaskTypeResponse, UpdateTaskTypeRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[get("/<workspace_id>/task-types")]
+pub async fn get_task_types(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<TaskTypeResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let task_types = list_task_types(db, user.id, workspace_uuid).await?;
+    Ok(Json(task_types))
+}
+
+#[post("/<workspace_id>/task-types", data = "<request>")]
+pub async fn create_task_type_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateTaskTypeRequest>,
+) -> AppResult<Json<TaskTypeResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let task_type = create_task_type(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(task_type))
+}
+
+#[put("/<workspace_id>/task-types/<task_type_id>", data = "<request>")]
+pub async fn update_task_type_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    task_type_id: &str,
+    request: Json<UpdateTaskTypeRequest>,
+) -> AppResult<Json<TaskTypeResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let task_type_uuid = Uuid::parse_str(task_type_id)
+        .map_err(|_| AppError::BadRequest("Invalid task type ID format".to_string()))?;
+    let task_type = update_task_type(
+        db,
+        user.id,
+        workspace_uuid,
+        task_type_uuid,
+        request.into_inner(),
+    )
+    .await?;
+    Ok(Json(task_type))
+}
+
+#[delete("/<workspace_id>/task-types/<task_type_id>")]
+pub async fn delete_task_type_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    workspace_id: &str,
+    task_type_id: &str,
+) -> AppResult<Json<bool>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let task_type_uuid = Uuid::parse_str(task_type_id)
+        .map_err(|_| AppError::BadRequest("Invalid task type ID format".to_string()))?;
+    let deleted = delete_task_type(db, cache, user.id, workspace_uuid, task_type_uuid).await?;
+    Ok(Json(deleted))
+}
@@ -1,12 +1,20 @@
 use crate::auth::AuthUser;
 use crate::cache::Cache;
+use crate::config::Config;
 use crate::database::{
-    create_task, delete_task, get_task_by_id, get_tasks_by_topic, get_tasks_by_user_filtered,
-    update_task, CreateTaskRequest, SortField, SortOrder, TaskFilterOptions, TaskResponse,
-    UpdateTaskRequest,
+    add_task_assignee, add_task_label, bulk_update_tasks, create_task, create_task_dependency,
+    delete_task, delete_task_dependency, get_assigned_tasks, get_task_by_id, get_task_subtree,
+    get_tasks_by_topic, get_tasks_by_user_filtered, list_task_dependencies, move_task,
+    remove_task_assignee, remove_task_label, restore_task, set_task_assignees, set_task_labels,
+    set_task_parent, update_task, update_task_custom_fields, AssigneeFilter, BulkTaskRequest,
+    BulkTaskResponse, BulkTaskSelection, CreateTaskDependencyRequest, CreateTaskRequest,
+    CustomFieldFilter, LabelMatch, MoveTaskRequest, SetCustomFieldValuesRequest,
+    SetTaskAssigneesRequest, SetTaskLabelsRequest, SetTaskParentRequest, SortField, SortOrder,
+    TaskDependenciesResponse, TaskDependencyResponse, TaskFilterOptions, TaskResponse,
+    TaskTreeNode, UndoTokenResponse, UpdateTaskRequest,
 };
 use crate::errors::{AppError, AppResult};
-use crate::models::task::TaskType;
+use crate::models::task::TaskPriority;
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, put, State};
 use sea_orm::DatabaseConnection;
@@ -19,9 +27,16 @@ pub struct TaskFilters {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
     // Filtering options
-    pub task_type: Option<String>, // Can be comma-separated for multiple values
+    pub task_type: Option<String>, // Type IDs or names, comma-separated for multiple values
     pub completed: Option<bool>,
-    pub urgent: Option<bool>,
+    pub status_id: Option<String>, // Can be comma-separated for multiple values
+    pub parent_id: Option<String>, // A task ID, or "none" for top-level tasks only
+    pub labels: Option<String>, // Comma-separated label IDs or names
+    pub labels_match: Option<String>, // "any" (default) or "all"
+    pub custom_field: Vec<String>, // "<field_id>:<op>:<value>", repeatable
+    pub assignee: Option<String>, // "me", a user ID, or "none" for unassigned tasks
+    pub priority: Option<String>, // Comma-separated: none, low, medium, high, critical
+    pub urgent: Option<bool>, // Priority high or above
     pub title: Option<String>, // Partial match
     // Sorting options
     #[field(name = "$orderby")]
@@ -45,63 +60,151 @@ pub async fn get_tasks(
             .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
         get_tasks_by_topic(db, user.id, topic_uuid).await?
     } else {
-        // Parse sorting options
-        let (sort_field, sort_order) = if let Some(order_by) = filters.order_by {
-            parse_order_by(&order_by)?
-        } else {
-            (SortField::CreatedAt, SortOrder::Desc) // Default: latest first
-        };
-
-        // Parse task types if provided
-        let task_types = if let Some(types_str) = filters.task_type {
-            let types: Result<Vec<_>, _> = types_str.split(',').map(|s| s.trim().parse()).collect();
-            Some(types.map_err(|e| AppError::BadRequest(format!("Invalid task type: {}", e)))?)
-        } else {
-            None
-        };
-
-        // Parse workspace filter
-        let workspace_id = if let Some(workspace_id_str) = filters.workspace_id {
-            Some(
-                Uuid::parse_str(&workspace_id_str)
-                    .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?,
-            )
-        } else {
-            None
-        };
-
-        // Parse date filters
-        let start_date = filters
-            .start_date
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
-            .map(|dt| dt.with_timezone(&chrono::Utc));
-
-        let end_date = filters
-            .end_date
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
-            .map(|dt| dt.with_timezone(&chrono::Utc));
-
-        // Create filter options struct
-        let filter_options = TaskFilterOptions {
-            start_date,
-            end_date,
-            task_types,
-            completed: filters.completed,
-            urgent: filters.urgent,
-            title_contains: filters.title,
-            sort_field,
-            sort_order,
-            limit: filters.top.map(|t| t as usize),
-            offset: filters.skip.map(|s| s as usize),
-            workspace_id,
-        };
-
+        let filter_options = task_filter_options(filters)?;
         get_tasks_by_user_filtered(db, cache, user.id, filter_options).await?
     };
 
     Ok(Json(tasks))
 }
 
+/// Parses the task list query string into filter options.
+fn task_filter_options(filters: TaskFilters) -> AppResult<TaskFilterOptions> {
+    // Parse topic filter
+    let topic_id = filters
+        .topic_id
+        .map(|topic_id_str| {
+            Uuid::parse_str(&topic_id_str)
+                .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))
+        })
+        .transpose()?;
+
+    // Parse sorting options
+    let (sort_field, sort_order) = if let Some(order_by) = filters.order_by {
+        parse_order_by(&order_by)?
+    } else {
+        (SortField::CreatedAt, SortOrder::Desc) // Default: latest first
+    };
+
+    // Parse task types if provided
+    let task_types = filters.task_type.map(|types_str| {
+        types_str
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+    });
+
+    // Parse status filter
+    let status_ids = if let Some(status_str) = filters.status_id {
+        let ids: Result<Vec<_>, _> = status_str
+            .split(',')
+            .map(|s| Uuid::parse_str(s.trim()))
+            .collect();
+        Some(ids.map_err(|_| AppError::BadRequest("Invalid status ID format".to_string()))?)
+    } else {
+        None
+    };
+
+    // Parse hierarchy filter
+    let (parent_task_id, top_level_only) = match filters.parent_id.as_deref() {
+        Some("none") => (None, true),
+        Some(parent_id_str) => (
+            Some(
+                Uuid::parse_str(parent_id_str)
+                    .map_err(|_| AppError::BadRequest("Invalid parent ID format".to_string()))?,
+            ),
+            false,
+        ),
+        None => (None, false),
+    };
+
+    // Parse label filter
+    let labels = filters.labels.map(|labels_str| {
+        labels_str
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+    });
+
+    let label_match = match filters.labels_match {
+        Some(match_str) => match_str
+            .parse::<LabelMatch>()
+            .map_err(AppError::BadRequest)?,
+        None => LabelMatch::Any,
+    };
+
+    // Parse custom field filters
+    let custom_fields = filters
+        .custom_field
+        .iter()
+        .map(|f| f.parse::<CustomFieldFilter>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(AppError::BadRequest)?;
+
+    // Parse assignee filter
+    let assignee = filters
+        .assignee
+        .map(|assignee_str| assignee_str.trim().parse::<AssigneeFilter>())
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    // Parse priority filter
+    let priorities = if let Some(priority_str) = filters.priority {
+        let priorities: Result<Vec<_>, _> = priority_str
+            .split(',')
+            .map(|s| s.trim().parse::<TaskPriority>())
+            .collect();
+        Some(priorities.map_err(AppError::BadRequest)?)
+    } else {
+        None
+    };
+
+    // Parse workspace filter
+    let workspace_id = if let Some(workspace_id_str) = filters.workspace_id {
+        Some(
+            Uuid::parse_str(&workspace_id_str)
+                .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?,
+        )
+    } else {
+        None
+    };
+
+    // Parse date filters
+    let start_date = filters
+        .start_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc));
+
+    let end_date = filters
+        .end_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc));
+
+    Ok(TaskFilterOptions {
+        start_date,
+        end_date,
+        task_types,
+        completed: filters.completed,
+        status_ids,
+        parent_task_id,
+        top_level_only,
+        labels,
+        label_match,
+        custom_fields,
+        assignee,
+        priorities,
+        urgent: filters.urgent,
+        title_contains: filters.title,
+        sort_field,
+        sort_order,
+        limit: filters.top.map(|t| t as usize),
+        offset: filters.skip.map(|s| s as usize),
+        workspace_id,
+        topic_id,
+    })
+}
+
 fn parse_order_by(order_by: &str) -> AppResult<(SortField, SortOrder)> {
     let parts: Vec<&str> = order_by.split_whitespace().collect();
 
@@ -124,6 +227,17 @@ fn parse_order_by(order_by: &str) -> AppResult<(SortField, SortOrder)> {
     Ok((field, order))
 }
 
+/// Tasks assigned to the current user in all of their workspaces.
+#[get("/mine?<completed>")]
+pub async fn get_my_tasks(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    completed: Option<bool>,
+) -> AppResult<Json<Vec<TaskResponse>>> {
+    let tasks = get_assigned_tasks(db, user.id, completed).await?;
+    Ok(Json(tasks))
+}
+
 #[get("/<task_id>")]
 pub async fn get_task(
     db: &State<DatabaseConnection>,
@@ -140,34 +254,275 @@ pub async fn get_task(
 pub async fn create_task_route(
     db: &State<DatabaseConnection>,
     cache: &State<Cache>,
+    config: &State<Config>,
     user: AuthUser,
     request: Json<CreateTaskRequest>,
 ) -> AppResult<Json<TaskResponse>> {
-    let task = create_task(db, cache, user.id, request.into_inner()).await?;
+    let task = create_task(db, cache, config, user.id, request.into_inner()).await?;
     Ok(Json(task))
 }
 
 #[put("/<task_id>", data = "<request>")]
 pub async fn update_task_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
     user: AuthUser,
     task_id: &str,
     request: Json<UpdateTaskRequest>,
 ) -> AppResult<Json<TaskResponse>> {
     let task_uuid = Uuid::parse_str(task_id)
         .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
-    let task = update_task(db, user.id, task_uuid, request.into_inner()).await?;
+    let task = update_task(db, cache, user.id, task_uuid, request.into_inner()).await?;
     Ok(Json(task))
 }
 
 #[delete("/<task_id>")]
 pub async fn delete_task_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<UndoTokenResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let undo = delete_task(db, cache, config, user.id, task_uuid).await?;
+    Ok(Json(undo))
+}
+
+#[post("/<task_id>/restore")]
+pub async fn restore_task_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task = restore_task(db, cache, config, user.id, task_uuid).await?;
+    Ok(Json(task))
+}
+
+#[post("/<task_id>/move", data = "<request>")]
+pub async fn move_task_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<MoveTaskRequest>,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task = move_task(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(task))
+}
+
+/// Applies one operation to the tasks in `task_ids`, or to the tasks
+/// matching the same filters as `GET /api/tasks` when `task_ids` is
+/// omitted. `workspace_id` picks the workspace either way.
+#[post("/bulk?<filters..>", data = "<request>")]
+pub async fn bulk_tasks_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    filters: TaskFilters,
+    request: Json<BulkTaskRequest>,
+) -> AppResult<Json<BulkTaskResponse>> {
+    let request = request.into_inner();
+    let options = task_filter_options(filters)?;
+    let workspace_id = options.workspace_id;
+    let selection = match request.task_ids {
+        Some(task_ids) => BulkTaskSelection::Ids(task_ids),
+        None => BulkTaskSelection::Filter(Box::new(options)),
+    };
+
+    let response = bulk_update_tasks(
+        db,
+        cache,
+        config,
+        user.id,
+        workspace_id,
+        selection,
+        request.operation,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+#[get("/<task_id>/dependencies")]
+pub async fn get_task_dependencies(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<TaskDependenciesResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let dependencies = list_task_dependencies(db, user.id, task_uuid).await?;
+    Ok(Json(dependencies))
+}
+
+#[post("/<task_id>/dependencies", data = "<request>")]
+pub async fn create_task_dependency_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<CreateTaskDependencyRequest>,
+) -> AppResult<Json<TaskDependencyResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let dependency =
+        create_task_dependency(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(dependency))
+}
+
+#[delete("/<task_id>/dependencies/<dependency_id>")]
+pub async fn delete_task_dependency_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
     user: AuthUser,
     task_id: &str,
+    dependency_id: &str,
 ) -> AppResult<Json<bool>> {
     let task_uuid = Uuid::parse_str(task_id)
         .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
-    let result = delete_task(db, user.id, task_uuid).await?;
-    Ok(Json(result))
+    let dependency_uuid = Uuid::parse_str(dependency_id)
+        .map_err(|_| AppError::BadRequest("Invalid dependency ID format".to_string()))?;
+    let deleted = delete_task_dependency(db, cache, user.id, task_uuid, dependency_uuid).await?;
+    Ok(Json(deleted))
+}
+
+#[get("/<task_id>/subtree")]
+pub async fn get_task_subtree_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<TaskTreeNode>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let tree = get_task_subtree(db, user.id, task_uuid).await?;
+    Ok(Json(tree))
+}
+
+#[put("/<task_id>/parent", data = "<request>")]
+pub async fn set_task_parent_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<SetTaskParentRequest>,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task = set_task_parent(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(task))
+}
+
+#[put("/<task_id>/labels", data = "<request>")]
+pub async fn set_task_labels_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<SetTaskLabelsRequest>,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task = set_task_labels(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(task))
+}
+
+#[post("/<task_id>/labels/<label_id>")]
+pub async fn add_task_label_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    label_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let label_uuid = Uuid::parse_str(label_id)
+        .map_err(|_| AppError::BadRequest("Invalid label ID format".to_string()))?;
+    let task = add_task_label(db, cache, user.id, task_uuid, label_uuid).await?;
+    Ok(Json(task))
+}
+
+#[delete("/<task_id>/labels/<label_id>")]
+pub async fn remove_task_label_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    label_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let label_uuid = Uuid::parse_str(label_id)
+        .map_err(|_| AppError::BadRequest("Invalid label ID format".to_string()))?;
+    let task = remove_task_label(db, cache, user.id, task_uuid, label_uuid).await?;
+    Ok(Json(task))
+}
+
+#[put("/<task_id>/custom-fields", data = "<request>")]
+pub async fn update_task_custom_fields_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<SetCustomFieldValuesRequest>,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task =
+        update_task_custom_fields(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(task))
+}
+
+#[put("/<task_id>/assignees", data = "<request>")]
+pub async fn set_task_assignees_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<SetTaskAssigneesRequest>,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let task = set_task_assignees(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(task))
+}
+
+#[post("/<task_id>/assignees/<user_id>")]
+pub async fn add_task_assignee_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    user_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let user_uuid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
+    let task = add_task_assignee(db, cache, user.id, task_uuid, user_uuid).await?;
+    Ok(Json(task))
+}
+
+#[delete("/<task_id>/assignees/<user_id>")]
+pub async fn remove_task_assignee_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    user_id: &str,
+) -> AppResult<Json<TaskResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let user_uuid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
+    let task = remove_task_assignee(db, cache, user.id, task_uuid, user_uuid).await?;
+    Ok(Json(task))
 }
@@ -0,0 +1,122 @@
+use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::database::{
+    create_time_entry, delete_time_entry, get_running_timer, get_time_entries_by_task,
+    get_time_report, start_timer, stop_timer, update_time_entry, CreateTimeEntryRequest,
+    StartTimerRequest, TimeEntryResponse, TimeReportResponse, UpdateTimeEntryRequest,
+};
+use crate::errors::{AppError, AppResult};
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+fn parse_report_date(value: &str, name: &str) -> AppResult<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|_| AppError::BadRequest(format!("Invalid {} date, expected RFC 3339", name)))
+}
+
+#[get("/<task_id>/time-entries")]
+pub async fn get_task_time_entries(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+) -> AppResult<Json<Vec<TimeEntryResponse>>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let entries = get_time_entries_by_task(db, user.id, task_uuid).await?;
+    Ok(Json(entries))
+}
+
+#[post("/<task_id>/time-entries", data = "<request>")]
+pub async fn create_task_time_entry(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    task_id: &str,
+    request: Json<CreateTimeEntryRequest>,
+) -> AppResult<Json<TimeEntryResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let entry = create_time_entry(db, cache, user.id, task_uuid, request.into_inner()).await?;
+    Ok(Json(entry))
+}
+
+#[post("/<task_id>/timer/start", data = "<request>")]
+pub async fn start_task_timer(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    task_id: &str,
+    request: Option<Json<StartTimerRequest>>,
+) -> AppResult<Json<TimeEntryResponse>> {
+    let task_uuid = Uuid::parse_str(task_id)
+        .map_err(|_| AppError::BadRequest("Invalid task ID format".to_string()))?;
+    let request = request.map(Json::into_inner).unwrap_or_default();
+    let entry = start_timer(db, user.id, task_uuid, request).await?;
+    Ok(Json(entry))
+}
+
+#[get("/timer")]
+pub async fn get_running_timer_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+) -> AppResult<Json<Option<TimeEntryResponse>>> {
+    let entry = get_running_timer(db, user.id).await?;
+    Ok(Json(entry))
+}
+
+#[post("/timer/stop")]
+pub async fn stop_timer_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+) -> AppResult<Json<TimeEntryResponse>> {
+    let entry = stop_timer(db, cache, user.id).await?;
+    Ok(Json(entry))
+}
+
+#[put("/<entry_id>", data = "<request>")]
+pub async fn update_time_entry_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    entry_id: &str,
+    request: Json<UpdateTimeEntryRequest>,
+) -> AppResult<Json<TimeEntryResponse>> {
+    let entry_uuid = Uuid::parse_str(entry_id)
+        .map_err(|_| AppError::BadRequest("Invalid time entry ID format".to_string()))?;
+    let entry = update_time_entry(db, cache, user.id, entry_uuid, request.into_inner()).await?;
+    Ok(Json(entry))
+}
+
+#[delete("/<entry_id>")]
+pub async fn delete_time_entry_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    entry_id: &str,
+) -> AppResult<Json<bool>> {
+    let entry_uuid = Uuid::parse_str(entry_id)
+        .map_err(|_| AppError::BadRequest("Invalid time entry ID format".to_string()))?;
+    let deleted = delete_time_entry(db, cache, user.id, entry_uuid).await?;
+    Ok(Json(deleted))
+}
+
+/// Time tracked in a workspace between `from` and `to` (RFC 3339), grouped
+/// by topic, task type and user.
+#[get("/<workspace_id>/time-report?<from>&<to>")]
+pub async fn get_time_report_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    from: &str,
+    to: &str,
+) -> AppResult<Json<TimeReportResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let from = parse_report_date(from, "from")?;
+    let to = parse_report_date(to, "to")?;
+    let report = get_time_report(db, user.id, workspace_uuid, from, to).await?;
+    Ok(Json(report))
+}
@@ -1,7 +1,10 @@
 use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::config::Config;
 use crate::database::{
-    create_topic, delete_topic, get_topic_by_id, get_topics_by_user, update_topic,
-    CreateTopicRequest, TopicResponse, UpdateTopicRequest,
+    archive_topic, create_topic, delete_topic, get_topic_by_id, get_topics_by_user,
+    restore_topic, update_topic, CreateTopicRequest, TopicResponse, UndoTokenResponse,
+    UpdateTopicRequest,
 };
 use crate::errors::{AppError, AppResult};
 use rocket::serde::json::Json;
@@ -12,6 +15,7 @@ use uuid::Uuid;
 #[derive(rocket::FromForm)]
 pub struct TopicFilters {
     pub workspace_id: Option<String>,
+    pub include_archived: Option<bool>,
 }
 
 #[get("/?<filters..>")]
@@ -29,7 +33,8 @@ pub async fn get_topics(
         None
     };
 
-    let topics = get_topics_by_user(db, user.id, workspace_id).await?;
+    let include_archived = filters.include_archived.unwrap_or(false);
+    let topics = get_topics_by_user(db, user.id, workspace_id, include_archived).await?;
     Ok(Json(topics))
 }
 
@@ -48,34 +53,79 @@ pub async fn get_topic(
 #[post("/", data = "<request>")]
 pub async fn create_topic_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
     user: AuthUser,
     request: Json<CreateTopicRequest>,
 ) -> AppResult<Json<TopicResponse>> {
-    let topic = create_topic(db, user.id, request.into_inner()).await?;
+    let topic = create_topic(db, cache, config, user.id, request.into_inner()).await?;
     Ok(Json(topic))
 }
 
 #[put("/<topic_id>", data = "<request>")]
 pub async fn update_topic_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
     user: AuthUser,
     topic_id: &str,
     request: Json<UpdateTopicRequest>,
 ) -> AppResult<Json<TopicResponse>> {
     let topic_uuid = Uuid::parse_str(topic_id)
         .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
-    let topic = update_topic(db, user.id, topic_uuid, request.into_inner()).await?;
+    let topic = update_topic(db, cache, user.id, topic_uuid, request.into_inner()).await?;
     Ok(Json(topic))
 }
 
 #[delete("/<topic_id>")]
 pub async fn delete_topic_route(
     db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
     user: AuthUser,
     topic_id: &str,
-) -> AppResult<Json<bool>> {
+) -> AppResult<Json<UndoTokenResponse>> {
     let topic_uuid = Uuid::parse_str(topic_id)
         .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
-    let result = delete_topic(db, user.id, topic_uuid).await?;
-    Ok(Json(result))
+    let undo = delete_topic(db, cache, config, user.id, topic_uuid).await?;
+    Ok(Json(undo))
+}
+
+#[post("/<topic_id>/archive")]
+pub async fn archive_topic_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    topic_id: &str,
+) -> AppResult<Json<TopicResponse>> {
+    let topic_uuid = Uuid::parse_str(topic_id)
+        .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
+    let topic = archive_topic(db, cache, user.id, topic_uuid, true).await?;
+    Ok(Json(topic))
+}
+
+#[post("/<topic_id>/unarchive")]
+pub async fn unarchive_topic_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    user: AuthUser,
+    topic_id: &str,
+) -> AppResult<Json<TopicResponse>> {
+    let topic_uuid = Uuid::parse_str(topic_id)
+        .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
+    let topic = archive_topic(db, cache, user.id, topic_uuid, false).await?;
+    Ok(Json(topic))
+}
+
+#[post("/<topic_id>/restore")]
+pub async fn restore_topic_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    topic_id: &str,
+) -> AppResult<Json<TopicResponse>> {
+    let topic_uuid = Uuid::parse_str(topic_id)
+        .map_err(|_| AppError::BadRequest("Invalid topic ID format".to_string()))?;
+    let topic = restore_topic(db, cache, config, user.id, topic_uuid).await?;
+    Ok(Json(topic))
 }
@@ -0,0 +1,15 @@
+use crate::auth::AuthUser;
+use crate::database::{get_trash_by_user, TrashResponse};
+use crate::errors::AppResult;
+use rocket::serde::json::Json;
+use rocket::{get, State};
+use sea_orm::DatabaseConnection;
+
+#[get("/")]
+pub async fn get_trash(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+) -> AppResult<Json<TrashResponse>> {
+    let trash = get_trash_by_user(db, user.id).await?;
+    Ok(Json(trash))
+}
@@ -0,0 +1,21 @@
+use crate::auth::AuthUser;
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::database::{undo_operation, UndoResponse};
+use crate::errors::AppResult;
+use rocket::serde::json::Json;
+use rocket::{post, State};
+use sea_orm::DatabaseConnection;
+
+/// Reverses the deletion that handed out `token`.
+#[post("/<token>")]
+pub async fn undo_route(
+    db: &State<DatabaseConnection>,
+    cache: &State<Cache>,
+    config: &State<Config>,
+    user: AuthUser,
+    token: &str,
+) -> AppResult<Json<UndoResponse>> {
+    let undone = undo_operation(db, cache, config, user.id, token).await?;
+    Ok(Json(undone))
+}
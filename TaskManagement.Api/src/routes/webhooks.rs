@@ -0,0 +1,140 @@
+use crate::auth::AuthUser;
+use crate::config::Config;
+use crate::database::{
+    create_webhook, delete_webhook, get_webhook_deliveries, get_webhooks_by_workspace,
+    retry_webhook_delivery, update_webhook, CreateWebhookRequest, UpdateWebhookRequest,
+    WebhookDeliveryResponse, WebhookResponse,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::webhook_delivery::WebhookDeliveryStatus;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put, State};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(rocket::FromForm)]
+pub struct DeliveryFilters {
+    pub status: Option<String>, // pending, succeeded or dead
+    #[field(name = "$top")]
+    pub top: Option<u32>,
+    #[field(name = "$skip")]
+    pub skip: Option<u32>,
+}
+
+fn parse_delivery_status(value: &str) -> AppResult<WebhookDeliveryStatus> {
+    match value.to_lowercase().as_str() {
+        "pending" => Ok(WebhookDeliveryStatus::Pending),
+        "succeeded" => Ok(WebhookDeliveryStatus::Succeeded),
+        "dead" => Ok(WebhookDeliveryStatus::Dead),
+        _ => Err(AppError::BadRequest(format!(
+            "Invalid delivery status: {}",
+            value
+        ))),
+    }
+}
+
+#[get("/<workspace_id>/webhooks")]
+pub async fn get_webhooks(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let webhooks = get_webhooks_by_workspace(db, user.id, workspace_uuid).await?;
+    Ok(Json(webhooks))
+}
+
+#[post("/<workspace_id>/webhooks", data = "<request>")]
+pub async fn create_webhook_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateWebhookRequest>,
+) -> AppResult<Json<WebhookResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let webhook = create_webhook(db, config, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(webhook))
+}
+
+#[put("/<webhook_id>", data = "<request>")]
+pub async fn update_webhook_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    webhook_id: &str,
+    request: Json<UpdateWebhookRequest>,
+) -> AppResult<Json<WebhookResponse>> {
+    let webhook_uuid = Uuid::parse_str(webhook_id)
+        .map_err(|_| AppError::BadRequest("Invalid webhook ID format".to_string()))?;
+    let webhook = update_webhook(db, config, user.id, webhook_uuid, request.into_inner()).await?;
+    Ok(Json(webhook))
+}
+
+#[delete("/<webhook_id>")]
+pub async fn delete_webhook_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    webhook_id: &str,
+) -> AppResult<Json<bool>> {
+    let webhook_uuid = Uuid::parse_str(webhook_id)
+        .map_err(|_| AppError::BadRequest("Invalid webhook ID format".to_string()))?;
+    let deleted = delete_webhook(db, user.id, webhook_uuid).await?;
+    Ok(Json(deleted))
+}
+
+/// Delivery history of a webhook, newest first.
+#[get("/<webhook_id>/deliveries?<filters..>")]
+pub async fn get_webhook_deliveries_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    webhook_id: &str,
+    filters: DeliveryFilters,
+) -> AppResult<Json<Vec<WebhookDeliveryResponse>>> {
+    let webhook_uuid = Uuid::parse_str(webhook_id)
+        .map_err(|_| AppError::BadRequest("Invalid webhook ID format".to_string()))?;
+    let status = filters
+        .status
+        .as_deref()
+        .map(parse_delivery_status)
+        .transpose()?;
+
+    let top = filters.top.unwrap_or(DEFAULT_PAGE_SIZE);
+    if top == 0 || top > MAX_PAGE_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "$top must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let deliveries = get_webhook_deliveries(
+        db,
+        user.id,
+        webhook_uuid,
+        status,
+        top as u64,
+        filters.skip.unwrap_or(0) as u64,
+    )
+    .await?;
+    Ok(Json(deliveries))
+}
+
+#[post("/<webhook_id>/deliveries/<delivery_id>/retry")]
+pub async fn retry_webhook_delivery_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    webhook_id: &str,
+    delivery_id: &str,
+) -> AppResult<Json<WebhookDeliveryResponse>> {
+    let webhook_uuid = Uuid::parse_str(webhook_id)
+        .map_err(|_| AppError::BadRequest("Invalid webhook ID format".to_string()))?;
+    let delivery_uuid = Uuid::parse_str(delivery_id)
+        .map_err(|_| AppError::BadRequest("Invalid delivery ID format".to_string()))?;
+    let delivery = retry_webhook_delivery(db, user.id, webhook_uuid, delivery_uuid).await?;
+    Ok(Json(delivery))
+}
@@ -1,22 +1,37 @@
 use crate::auth::AuthUser;
+use crate::config::Config;
 use crate::database::{
-    create_workspace, create_workspace_share_link, delete_workspace, get_workspace_by_id,
-    get_workspaces_by_user, list_workspace_share_links, revoke_workspace_share_link,
-    update_workspace, CreateWorkspaceRequest, CreateWorkspaceShareLinkRequest,
-    UpdateWorkspaceRequest, WorkspaceResponse, WorkspaceShareLinkResponse,
+    accept_workspace_transfer, archive_workspace, cancel_workspace_transfer, clone_workspace,
+    create_workspace, create_workspace_share_link, create_workspace_transfer,
+    decline_workspace_transfer, delete_workspace, export_workspace, get_workspace_by_id,
+    get_workspace_templates_by_user, get_workspaces_by_user, import_workspace,
+    list_incoming_workspace_transfers, list_workspace_share_links, list_workspace_transfers,
+    parse_workspace_export, restore_workspace, revoke_workspace_share_link,
+    save_workspace_as_template, update_workspace, CloneWorkspaceRequest, CreateWorkspaceRequest,
+    CreateWorkspaceShareLinkRequest, CreateWorkspaceTransferRequest, ImportWorkspaceOptions,
+    SaveWorkspaceTemplateRequest, UpdateWorkspaceRequest, WorkspaceExport, WorkspaceResponse,
+    WorkspaceShareLinkResponse, WorkspaceTransferResponse,
 };
 use crate::errors::{AppError, AppResult};
+use rocket::data::{Data, ToByteUnit};
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, put, State};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-#[get("/")]
+#[derive(rocket::FromForm)]
+pub struct WorkspaceFilters {
+    pub include_archived: Option<bool>,
+}
+
+#[get("/?<filters..>")]
 pub async fn get_workspaces(
     db: &State<DatabaseConnection>,
     user: AuthUser,
+    filters: WorkspaceFilters,
 ) -> AppResult<Json<Vec<WorkspaceResponse>>> {
-    let workspaces = get_workspaces_by_user(db, user.id).await?;
+    let include_archived = filters.include_archived.unwrap_or(false);
+    let workspaces = get_workspaces_by_user(db, user.id, include_archived).await?;
     Ok(Json(workspaces))
 }
 
@@ -35,10 +50,11 @@ pub async fn get_workspace(
 #[post("/", data = "<request>")]
 pub async fn create_workspace_route(
     db: &State<DatabaseConnection>,
+    config: &State<Config>,
     user: AuthUser,
     request: Json<CreateWorkspaceRequest>,
 ) -> AppResult<Json<WorkspaceResponse>> {
-    let workspace = create_workspace(db, user.id, request.into_inner()).await?;
+    let workspace = create_workspace(db, config, user.id, request.into_inner()).await?;
     Ok(Json(workspace))
 }
 
@@ -67,9 +83,129 @@ pub async fn delete_workspace_route(
     Ok(Json(result))
 }
 
+#[post("/<workspace_id>/archive")]
+pub async fn archive_workspace_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let workspace = archive_workspace(db, user.id, workspace_uuid, true).await?;
+    Ok(Json(workspace))
+}
+
+#[post("/<workspace_id>/unarchive")]
+pub async fn unarchive_workspace_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let workspace = archive_workspace(db, user.id, workspace_uuid, false).await?;
+    Ok(Json(workspace))
+}
+
+#[post("/<workspace_id>/restore")]
+pub async fn restore_workspace_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let workspace = restore_workspace(db, config, user.id, workspace_uuid).await?;
+    Ok(Json(workspace))
+}
+
+#[post("/<workspace_id>/clone", data = "<request>")]
+pub async fn clone_workspace_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CloneWorkspaceRequest>,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let workspace = clone_workspace(db, config, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(workspace))
+}
+
+#[post("/<workspace_id>/template", data = "<request>")]
+pub async fn save_workspace_as_template_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<SaveWorkspaceTemplateRequest>,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let template =
+        save_workspace_as_template(db, config, user.id, workspace_uuid, request.into_inner())
+            .await?;
+    Ok(Json(template))
+}
+
+#[get("/templates")]
+pub async fn get_workspace_templates(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+) -> AppResult<Json<Vec<WorkspaceResponse>>> {
+    let templates = get_workspace_templates_by_user(db, user.id).await?;
+    Ok(Json(templates))
+}
+
+#[get("/<workspace_id>/export")]
+pub async fn export_workspace_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<WorkspaceExport>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let export = export_workspace(db, user.id, workspace_uuid).await?;
+    Ok(Json(export))
+}
+
+/// Imports an export document from the request body. The body is read
+/// directly, up to `WORKSPACE_IMPORT_MAX_BYTES`, instead of through the JSON
+/// guard and its much smaller limit.
+#[post("/import?<name>", data = "<document>")]
+pub async fn import_workspace_route(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthUser,
+    name: Option<String>,
+    document: Data<'_>,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let body = document
+        .open(config.workspace_import_max_bytes.bytes())
+        .into_bytes()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Failed to read export document: {}", e)))?;
+    if !body.is_complete() {
+        return Err(AppError::Validation(format!(
+            "Export documents cannot be larger than {} bytes",
+            config.workspace_import_max_bytes
+        )));
+    }
+    let document: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| AppError::Validation(format!("Invalid export document: {}", e)))?;
+
+    let export = parse_workspace_export(document)?;
+    let options = ImportWorkspaceOptions { name };
+    let workspace = import_workspace(db, config, user.id, export, options).await?;
+    Ok(Json(workspace))
+}
+
 #[post("/<workspace_id>/share-links", data = "<request>")]
 pub async fn create_workspace_share_link_route(
     db: &State<DatabaseConnection>,
+    config: &State<Config>,
     user: AuthUser,
     workspace_id: &str,
     request: Json<CreateWorkspaceShareLinkRequest>,
@@ -77,7 +213,8 @@ pub async fn create_workspace_share_link_route(
     let workspace_uuid = Uuid::parse_str(workspace_id)
         .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
     let share_link =
-        create_workspace_share_link(db, user.id, workspace_uuid, request.into_inner()).await?;
+        create_workspace_share_link(db, config, user.id, workspace_uuid, request.into_inner())
+            .await?;
     Ok(Json(share_link))
 }
 
@@ -107,3 +244,77 @@ pub async fn revoke_workspace_share_link_route(
     let link = revoke_workspace_share_link(db, user.id, workspace_uuid, share_link_uuid).await?;
     Ok(Json(link))
 }
+
+#[post("/<workspace_id>/transfers", data = "<request>")]
+pub async fn create_workspace_transfer_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    request: Json<CreateWorkspaceTransferRequest>,
+) -> AppResult<Json<WorkspaceTransferResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let transfer =
+        create_workspace_transfer(db, user.id, workspace_uuid, request.into_inner()).await?;
+    Ok(Json(transfer))
+}
+
+#[get("/<workspace_id>/transfers")]
+pub async fn get_workspace_transfers(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+) -> AppResult<Json<Vec<WorkspaceTransferResponse>>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let transfers = list_workspace_transfers(db, user.id, workspace_uuid).await?;
+    Ok(Json(transfers))
+}
+
+#[delete("/<workspace_id>/transfers/<transfer_id>")]
+pub async fn cancel_workspace_transfer_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    workspace_id: &str,
+    transfer_id: &str,
+) -> AppResult<Json<WorkspaceTransferResponse>> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)
+        .map_err(|_| AppError::BadRequest("Invalid workspace ID format".to_string()))?;
+    let transfer_uuid = Uuid::parse_str(transfer_id)
+        .map_err(|_| AppError::BadRequest("Invalid transfer ID format".to_string()))?;
+    let transfer = cancel_workspace_transfer(db, user.id, workspace_uuid, transfer_uuid).await?;
+    Ok(Json(transfer))
+}
+
+#[get("/transfers/incoming")]
+pub async fn get_incoming_workspace_transfers(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+) -> AppResult<Json<Vec<WorkspaceTransferResponse>>> {
+    let transfers = list_incoming_workspace_transfers(db, user.id).await?;
+    Ok(Json(transfers))
+}
+
+#[post("/transfers/<transfer_id>/accept")]
+pub async fn accept_workspace_transfer_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    transfer_id: &str,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let transfer_uuid = Uuid::parse_str(transfer_id)
+        .map_err(|_| AppError::BadRequest("Invalid transfer ID format".to_string()))?;
+    let workspace = accept_workspace_transfer(db, user.id, transfer_uuid).await?;
+    Ok(Json(workspace))
+}
+
+#[post("/transfers/<transfer_id>/decline")]
+pub async fn decline_workspace_transfer_route(
+    db: &State<DatabaseConnection>,
+    user: AuthUser,
+    transfer_id: &str,
+) -> AppResult<Json<WorkspaceTransferResponse>> {
+    let transfer_uuid = Uuid::parse_str(transfer_id)
+        .map_err(|_| AppError::BadRequest("Invalid transfer ID format".to_string()))?;
+    let transfer = decline_workspace_transfer(db, user.id, transfer_uuid).await?;
+    Ok(Json(transfer))
+}
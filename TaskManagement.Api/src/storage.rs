@@ -0,0 +1,191 @@
+use crate::config::Config;
+use crate::errors::{AppError, AppResult};
+use async_trait::async_trait;
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::primitives::ByteStream;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::AsyncRead;
+
+pub type BlobReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Where attachment contents live. Keys are relative, `/`-separated paths
+/// chosen by the caller; metadata stays in the database.
+#[async_trait]
+pub trait BlobStorage: Send + Sync {
+    /// Stores the contents of a local file under `key`, replacing any blob
+    /// already there. The file itself is left in place.
+    async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> AppResult<()>;
+
+    async fn get(&self, key: &str) -> AppResult<BlobReader>;
+
+    /// Deleting a missing blob is not an error.
+    async fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+/// Builds the backend selected by `ATTACHMENT_STORAGE`.
+pub fn blob_storage_from_config(config: &Config) -> AppResult<Arc<dyn BlobStorage>> {
+    match config.attachment_storage.as_str() {
+        "local" => Ok(Arc::new(LocalBlobStorage::new(
+            &config.attachment_local_dir,
+        ))),
+        "s3" => Ok(Arc::new(S3BlobStorage::new(config)?)),
+        other => Err(AppError::Internal(format!(
+            "Unknown attachment storage backend: {}",
+            other
+        ))),
+    }
+}
+
+fn storage_error(action: &str, key: &str, error: impl std::fmt::Display) -> AppError {
+    AppError::Internal(format!("Failed to {} blob {}: {}", action, key, error))
+}
+
+/// Keeps blobs as files below a root directory.
+pub struct LocalBlobStorage {
+    root: PathBuf,
+}
+
+impl LocalBlobStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> AppResult<PathBuf> {
+        // Keys are generated by us, but never let one escape the root
+        if key
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..")
+        {
+            return Err(AppError::Internal(format!("Invalid blob key: {}", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStorage for LocalBlobStorage {
+    async fn put_file(&self, key: &str, path: &Path, _content_type: &str) -> AppResult<()> {
+        let target = self.path_for(key)?;
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| storage_error("store", key, e))?;
+        }
+
+        tokio::fs::copy(path, &target)
+            .await
+            .map_err(|e| storage_error("store", key, e))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<BlobReader> {
+        let file = tokio::fs::File::open(self.path_for(key)?)
+            .await
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    AppError::NotFound("Attachment content not found".to_string())
+                }
+                _ => storage_error("read", key, e),
+            })?;
+        Ok(Box::pin(file))
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(storage_error("delete", key, e)),
+        }
+    }
+}
+
+/// Keeps blobs in an S3 bucket. With `S3_ENDPOINT` set it talks to any
+/// S3-compatible server (MinIO and the like) using path-style addressing.
+pub struct S3BlobStorage {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl S3BlobStorage {
+    pub fn new(config: &Config) -> AppResult<Self> {
+        let bucket = config.s3_bucket.clone().ok_or_else(|| {
+            AppError::Internal("S3_BUCKET is required for S3 storage".to_string())
+        })?;
+
+        let mut builder = aws_sdk_s3::config::Builder::new()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(config.s3_region.clone()));
+
+        if let (Some(access_key), Some(secret_key)) =
+            (&config.s3_access_key_id, &config.s3_secret_access_key)
+        {
+            builder = builder.credentials_provider(Credentials::new(
+                access_key.clone(),
+                secret_key.clone(),
+                None,
+                None,
+                "config",
+            ));
+        }
+
+        if let Some(endpoint) = &config.s3_endpoint {
+            builder = builder.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Ok(Self {
+            client: aws_sdk_s3::Client::from_conf(builder.build()),
+            bucket,
+        })
+    }
+}
+
+#[async_trait]
+impl BlobStorage for S3BlobStorage {
+    async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> AppResult<()> {
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| storage_error("store", key, e))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| storage_error("store", key, e))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<BlobReader> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(service_error) if service_error.is_no_such_key() => {
+                    AppError::NotFound("Attachment content not found".to_string())
+                }
+                _ => storage_error("read", key, e),
+            })?;
+
+        Ok(Box::pin(object.body.into_async_read()))
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| storage_error("delete", key, e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use crate::config::Config;
+use crate::errors::{AppError, AppResult};
+use crate::models::{webhook, webhook_delivery};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use sha2::Sha256;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// `sha256=<hex>`: HMAC-SHA256 of `<timestamp>.<body>` keyed with the
+/// webhook's secret. Receivers should recompute it and reject stale
+/// timestamps.
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Result of one attempt to deliver a webhook.
+#[derive(Debug)]
+pub enum DeliveryOutcome {
+    Delivered { status: u16 },
+    Failed { status: Option<u16>, error: String },
+}
+
+pub fn sign_payload(secret: &str, timestamp: i64, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Host names that only mean something inside the local network.
+const INTERNAL_HOST_SUFFIXES: &[&str] = &[
+    ".localhost",
+    ".local",
+    ".localdomain",
+    ".internal",
+    ".home.arpa",
+];
+
+/// Whether an address is reachable on the public internet. Loopback,
+/// private, link-local (cloud metadata services), shared and reserved
+/// ranges are not.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            // Mapped and NAT64 addresses reach the IPv4 address inside them
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(ip);
+            }
+            let segments = ip.segments();
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., a, b, c, d] = ip.octets();
+                return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00 // unique local
+                || (segments[0] & 0xffc0) == 0xfe80 // link-local
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8)) // documentation
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // shared address space
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+        || (a == 198 && (18..20).contains(&b)) // benchmarking
+        || a >= 240)
+}
+
+/// Rejects webhook URLs whose host is an internal name or a non-public IP
+/// address, so deliveries cannot be aimed at the server's own network.
+/// Names that resolve to such addresses are caught at send time by
+/// `PublicResolver`.
+pub fn check_webhook_host(url: &Url, allow_private: bool) -> Result<(), String> {
+    if allow_private {
+        return Ok(());
+    }
+
+    // The URL parser normalizes IP hosts, so `0x7f.1` arrives as `127.0.0.1`
+    let public = match url.host_str() {
+        Some(host) if host.starts_with('[') => host
+            .trim_matches(|c| c == '[' || c == ']')
+            .parse::<Ipv6Addr>()
+            .is_ok_and(|ip| is_public_ip(IpAddr::V6(ip))),
+        Some(host) => match host.parse::<Ipv4Addr>() {
+            Ok(ip) => is_public_ipv4(ip),
+            Err(_) => {
+                let name = host.trim_end_matches('.').to_ascii_lowercase();
+                // Single-label names are resolved against the local search
+                // domain
+                name.contains('.')
+                    && name != "localhost"
+                    && !INTERNAL_HOST_SUFFIXES
+                        .iter()
+                        .any(|suffix| name.ends_with(suffix))
+            }
+        },
+        None => false,
+    };
+
+    if public {
+        Ok(())
+    } else {
+        Err("Webhook URL must point to a public host".to_string())
+    }
+}
+
+/// Resolves receiver host names and drops every non-public address, so a
+/// name that resolves (or is later rebound) to an internal address is
+/// never connected to.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} does not resolve to a public address", name.as_str()).into(),
+                );
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Posts signed webhook deliveries to their receivers.
+pub struct WebhookSender {
+    client: reqwest::Client,
+    timeout: Duration,
+    allow_private_targets: bool,
+}
+
+impl WebhookSender {
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        Self::new(
+            Duration::from_secs(config.webhook_timeout_seconds.max(1)),
+            config.webhook_allow_private_targets,
+        )
+    }
+
+    pub fn new(timeout: Duration, allow_private_targets: bool) -> AppResult<Self> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!("task-management-api/", env!("CARGO_PKG_VERSION")))
+            // A redirect would resend the signed payload somewhere the
+            // subscription never named
+            .redirect(reqwest::redirect::Policy::none());
+        if !allow_private_targets {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = builder
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build webhook client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            timeout,
+            allow_private_targets,
+        })
+    }
+
+    /// How long a single attempt may take.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Makes one attempt; any 2xx response counts as delivered.
+    pub async fn send(
+        &self,
+        webhook: &webhook::Model,
+        delivery: &webhook_delivery::Model,
+    ) -> DeliveryOutcome {
+        // IP address hosts skip the resolver, and subscriptions may predate
+        // the host rules
+        let target = Url::parse(&webhook.url)
+            .map_err(|e| e.to_string())
+            .and_then(|url| check_webhook_host(&url, self.allow_private_targets));
+        if let Err(error) = target {
+            return DeliveryOutcome::Failed {
+                status: None,
+                error,
+            };
+        }
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = sign_payload(&webhook.secret, timestamp, &delivery.payload);
+
+        let result = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => DeliveryOutcome::Delivered {
+                status: response.status().as_u16(),
+            },
+            Ok(response) => DeliveryOutcome::Failed {
+                status: Some(response.status().as_u16()),
+                error: format!("Receiver responded with {}", response.status()),
+            },
+            Err(e) => DeliveryOutcome::Failed {
+                status: None,
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(url: &str) -> Result<(), String> {
+        check_webhook_host(&Url::parse(url).expect("valid URL"), false)
+    }
+
+    #[test]
+    fn sign_payload_matches_known_vector() {
+        assert_eq!(
+            sign_payload("whsec_test", 1_700_000_000, r#"{"event":"task.created"}"#),
+            "sha256=aabc548901ea3b50be05eb85dc114164830b27c602dcb16a1623b007eff48c20"
+        );
+    }
+
+    #[test]
+    fn sign_payload_covers_timestamp_and_body() {
+        let signature = sign_payload("secret", 1, "{}");
+        assert_ne!(signature, sign_payload("secret", 2, "{}"));
+        assert_ne!(signature, sign_payload("secret", 1, "{ }"));
+        assert_ne!(signature, sign_payload("other", 1, "{}"));
+    }
+
+    #[test]
+    fn check_webhook_host_allows_public_hosts() {
+        assert!(check("https://example.com/hook").is_ok());
+        assert!(check("https://hooks.example.com.:8443/hook").is_ok());
+        assert!(check("http://93.184.216.34/hook").is_ok());
+        assert!(check("http://[2606:4700::1111]/hook").is_ok());
+    }
+
+    #[test]
+    fn check_webhook_host_rejects_loopback() {
+        assert!(check("http://127.0.0.1/hook").is_err());
+        assert!(check("http://127.1.2.3:8080/hook").is_err());
+        assert!(check("http://0x7f.1/hook").is_err());
+        assert!(check("http://[::1]/hook").is_err());
+        assert!(check("http://localhost/hook").is_err());
+        assert!(check("http://LOCALHOST./hook").is_err());
+        assert!(check("http://api.localhost/hook").is_err());
+    }
+
+    #[test]
+    fn check_webhook_host_rejects_private_and_link_local() {
+        assert!(check("http://10.0.0.1/hook").is_err());
+        assert!(check("http://10.255.255.255/hook").is_err());
+        assert!(check("http://172.16.0.1/hook").is_err());
+        assert!(check("http://192.168.1.1/hook").is_err());
+        assert!(check("http://100.64.0.1/hook").is_err());
+        assert!(check("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(check("http://169.254.0.1/hook").is_err());
+        assert!(check("http://0.0.0.0/hook").is_err());
+        assert!(check("http://[fd00::1]/hook").is_err());
+        assert!(check("http://[fe80::1]/hook").is_err());
+    }
+
+    #[test]
+    fn check_webhook_host_rejects_embedded_ipv4() {
+        assert!(check("http://[::ffff:127.0.0.1]/hook").is_err());
+        assert!(check("http://[::ffff:169.254.169.254]/hook").is_err());
+        assert!(check("http://[64:ff9b::10.0.0.1]/hook").is_err());
+        assert!(check("http://[64:ff9b::a9fe:a9fe]/hook").is_err());
+        assert!(check("http://[::ffff:93.184.216.34]/hook").is_ok());
+    }
+
+    #[test]
+    fn check_webhook_host_rejects_internal_names() {
+        assert!(check("http://metadata.google.internal/hook").is_err());
+        assert!(check("http://printer.local/hook").is_err());
+        assert!(check("http://router.home.arpa/hook").is_err());
+        assert!(check("http://intranet/hook").is_err());
+        assert!(check("http://db./hook").is_err());
+    }
+
+    #[test]
+    fn check_webhook_host_can_allow_private_targets() {
+        let url = Url::parse("http://127.0.0.1:9000/hook").expect("valid URL");
+        assert!(check_webhook_host(&url, true).is_ok());
+    }
+}